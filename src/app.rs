@@ -2,7 +2,14 @@ use crate::error::Error;
 use crate::popup::Popup;
 use crate::romodoro::Pomodoro;
 use crate::settings::*;
-use crate::stats::pixela::graph::Graph;
+use crate::stats::heatmap::Heatmap;
+use crate::stats::pixela::graph::{Graph, GraphRange};
+use crate::stats::pixela::graph_stats::GraphStats;
+use crate::stats::pixela::remote_pixel::{RemoteEdit, RemotePixel};
+use crate::stats::pixela::send_preview::SendPreview;
+use crate::stats::pixela::subjects::{Progress, Subject};
+use crate::stats::pixela::upload_archive::ArchiveView;
+use crate::tasks::taskwarrior::TaskwarriorTask;
 use crate::timers::counters::CounterMode;
 use crate::ui::app_ui::AppWidget;
 use crate::ui::popup::popup_area;
@@ -33,6 +40,9 @@ pub struct App {
     #[derivative(Debug = "ignore")]
     clipboard: Option<Clipboard>,
     event_tx: tokio::sync::mpsc::Sender<Event>,
+    local_stats: bool,
+    heatmap: Option<Heatmap>,
+    archive: Option<ArchiveView>,
 }
 pub enum Event {
     TimerTick(i64),
@@ -40,11 +50,30 @@ pub enum Event {
     TerminalEvent,
     OverwriteTimerSettings,
     OverwriteTimerForSubject(usize),
+    /// reads the selected days from Pixela and asks before sending them
+    PreviewPixels,
+    PreviewReceived(Result<SendPreview, Error>),
     SendPixels,
     DeletePixel,
     RequestGraph,
+    RefreshGraph,
+    RequestHeatmap,
+    RequestTodayOnPixela,
     RestartTimer,
-    GraphReceived(Result<Graph, Error>),
+    /// the subject, range and page that were asked for
+    GraphReceived(Subject, GraphRange, i64, Result<Graph, Error>),
+    HeatmapReceived(Result<Graph, Error>),
+    GraphStatsReceived(Result<GraphStats, Error>),
+    TodayOnPixelaReceived(String, Result<Progress, Error>),
+    EditRemotePixel(RemotePixel, RemoteEdit),
+    RemotePixelEdited(Result<RemotePixel, Error>),
+    /// the last this many days of every subject
+    PullHistory(i64),
+    /// the graphs that came in and a line for every one that didn't
+    HistoryPulled(Vec<Graph>, Vec<String>),
+    TaskwarriorTasksReceived(Result<Vec<TaskwarriorTask>, Error>),
+    /// something running on its own, like a webhook delivery, went wrong
+    BackgroundError(Error),
 }
 impl App {
     pub fn new(
@@ -61,6 +90,9 @@ impl App {
             clipboard: Clipboard::new().ok(),
             event_tx,
             popup_size: Rect::default(),
+            local_stats: false,
+            heatmap: None,
+            archive: None,
         }
     }
     pub async fn run(
@@ -224,7 +256,7 @@ impl App {
         }
     }
     pub async fn overwrite_timer_settings(&mut self) {
-        self.pomodoro.timer.restart().await;
+        self.pomodoro.restart_timer().await;
         self.update_settings().await;
     }
 
@@ -289,6 +321,35 @@ impl App {
         &mut self.selected_tab
     }
 
+    /// The Stats tab falls back to the local dashboard when Pixela is off.
+    pub fn local_stats_shown(&self) -> bool {
+        self.local_stats || self.pomodoro.pixela_client().is_none()
+    }
+
+    pub fn toggle_local_stats(&mut self) {
+        self.local_stats = !self.local_stats;
+    }
+
+    pub fn heatmap(&self) -> Option<&Heatmap> {
+        self.heatmap.as_ref()
+    }
+
+    pub fn heatmap_mut(&mut self) -> Option<&mut Heatmap> {
+        self.heatmap.as_mut()
+    }
+
+    pub fn set_heatmap(&mut self, heatmap: Option<Heatmap>) {
+        self.heatmap = heatmap;
+    }
+
+    pub fn archive_mut(&mut self) -> Option<&mut ArchiveView> {
+        self.archive.as_mut()
+    }
+
+    pub fn set_archive(&mut self, archive: Option<ArchiveView>) {
+        self.archive = archive;
+    }
+
     pub fn popup_size(&self) -> Rect {
         self.popup_size
     }
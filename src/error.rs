@@ -1,6 +1,9 @@
 use std::{fmt::Display, io};
 
-use crate::{popup::Popup, stats::pixel::Pixel};
+use crate::{
+    popup::Popup,
+    stats::{pixel::Pixel, pixela::pixela_client::BatchSummary},
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum SettingsError {
@@ -24,6 +27,9 @@ pub enum SettingsError {
 
     #[error("Incorrect setting value: {0}")]
     WrongSetting(String),
+
+    #[error("No sync folder set, add a dir to [sync_settings] first")]
+    SyncDirNotSet,
 }
 #[derive(thiserror::Error, Debug)]
 pub enum StatsError {
@@ -35,12 +41,31 @@ pub enum StatsError {
     SubjectsAreEmpty,
     #[error("The sync has failed")]
     SubjectsSyncFailed,
-    #[error("Cannot create a subject with these parameters")]
-    SubjectCreationFailed,
     #[error("Can't upload this pixel")]
     WrongPixelData,
     #[error("Your graph cannot store progress this small")]
     QuantityIsNotBigEnough,
+    #[error("Invalid export option: {0}")]
+    InvalidExportOption(String),
+    #[error("Import failed: {0}")]
+    ImportFailed(String),
+    #[error("Invalid quantity: {0}")]
+    InvalidQuantity(String),
+    #[error("The aggregate graph {0} isn't one of your graphs")]
+    AggregateGraphNotFound(String),
+}
+#[derive(thiserror::Error, Debug)]
+pub enum IntegrationError {
+    #[error("Webhook {0} responded with {1}")]
+    WebhookRejected(String, reqwest::StatusCode),
+    #[error("ActivityWatch responded with {0}")]
+    ActivityWatchRejected(reqwest::StatusCode),
+    #[error("Export through `{0}` failed")]
+    ExportFailed(String),
+    #[error("`{0}` didn't finish in time")]
+    TimedOut(String),
+    #[error("The webhook queue couldn't be read, it was moved to {0}")]
+    QueueUnreadable(String),
 }
 #[derive(thiserror::Error, Debug)]
 pub enum PixelaResponseError {
@@ -48,12 +73,32 @@ pub enum PixelaResponseError {
     RetryableError(String, reqwest::StatusCode),
     #[error("{1}: {0}")]
     FatalError(String, reqwest::StatusCode),
-    #[error("{}", .errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n"))]
+    #[error("{summary}\n{}", grouped(errors))]
     FatalSendingPixelsError {
         errors: Vec<FatalError>,
         pixels: Vec<Pixel>,
+        summary: BatchSummary,
     },
 }
+/// Every distinct message once, with how often it came up.
+fn grouped(errors: &[FatalError]) -> String {
+    let mut counted: Vec<(String, usize)> = vec![];
+    for error in errors {
+        let message = error.to_string();
+        match counted.iter_mut().find(|(known, _)| *known == message) {
+            Some((_, count)) => *count += 1,
+            None => counted.push((message, 1)),
+        }
+    }
+    counted
+        .into_iter()
+        .map(|(message, count)| match count {
+            1 => message,
+            count => format!("{count}× {message}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 #[derive(Debug)]
 pub struct FatalError {
     message: String,
@@ -98,10 +143,15 @@ pub enum Error {
     #[error("Pixela Error: {0}")]
     PixelaResponseError(#[from] PixelaResponseError),
 
+    #[error("Integration Error: {0}")]
+    IntegrationError(#[from] IntegrationError),
+
     #[error("Toml Deserialization Error: {0}")]
     TomlDeError(#[from] toml::de::Error),
     #[error("Toml Serialization Error: {0}")]
     TomlSerError(#[from] toml::ser::Error),
+    #[error("Json Error: {0}")]
+    JsonError(#[from] serde_json::Error),
     #[error("Async Error: {0}")]
     AsyncError(String),
     #[error("Misc Error: {0}")]
@@ -115,5 +165,29 @@ impl Error {
             None
         }
     }
+    /// Pixela or the network failing for now, asking again later might work.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::RequestError(err) => err.is_timeout() || err.is_connect() || err.is_request(),
+            Error::PixelaResponseError(PixelaResponseError::RetryableError(_, _)) => true,
+            _ => false,
+        }
+    }
 }
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn only_retryable_pixela_errors_are_transient() {
+        let busy =
+            PixelaResponseError::RetryableError("busy".into(), StatusCode::SERVICE_UNAVAILABLE);
+        let missing = PixelaResponseError::FatalError("no graph".into(), StatusCode::NOT_FOUND);
+        assert!(Error::from(busy).is_transient());
+        assert!(!Error::from(missing).is_transient());
+        assert!(!Error::from(StatsError::SubjectsAreEmpty).is_transient());
+    }
+}
@@ -2,10 +2,22 @@ use crossterm::event::{KeyCode, KeyEvent};
 
 use crate::{
     app::{App, Event},
+    error::Error,
     popup::Popup,
     settings::Mode,
-    stats::pixela::graph::Graph,
-    ui::popup::list_height,
+    stats::{
+        heatmap::{Heatmap, HEATMAP_DAYS},
+        pixela::{
+            graph::{Graph, GraphRange},
+            graph_stats::GraphStats,
+            helpers::get_from_pixela,
+            history_pull::{DayOrigin, HistoryMerge},
+            pixela_client::PixelaClient,
+            subjects::Subject,
+            upload_archive::UploadArchive,
+        },
+    },
+    ui::{helpers::format_duration, popup::list_height},
     utils::tabs::Tabs,
 };
 
@@ -23,13 +35,50 @@ impl App {
             Event::TerminalEvent => {}
             Event::OverwriteTimerSettings => self.overwrite_timer_settings().await,
             Event::OverwriteTimerForSubject(index) => self.overwrite_timer_for_subject(index).await,
+            Event::PreviewPixels => {
+                let Some(client) = self.pomodoro().pixela_client() else {
+                    return;
+                };
+                let tx_clone = self.event_tx().clone();
+                let user = client.user.clone();
+                let rq_client = client.client.clone();
+                let selected = client.get_selected_pixels();
+                let carry_over = client.carry_over();
+
+                tokio::spawn(async move {
+                    let result =
+                        PixelaClient::preview_send(user, rq_client, selected, carry_over).await;
+                    if let Err(e) = tx_clone.send(Event::PreviewReceived(result)).await {
+                        eprintln!("Failed to send PreviewReceived event: {}", e);
+                    }
+                });
+            }
+            Event::PreviewReceived(res) => match res {
+                Ok(preview) => self.set_popup(Popup::pixel_confirm_list(
+                    preview.message(),
+                    Box::new(App::ask_send_pixels),
+                    preview,
+                )),
+                Err(e) => self.set_popup(e.into()),
+            },
             Event::SendPixels => {
+                let retention = self.settings().borrow().archive_settings.retention_days;
                 if let Some(pixela) = self.pomodoro_mut().pixela_client_as_mut() {
-                    match pixela.send_pixels().await {
-                        Ok(pixels) => self.set_popup(Popup::pixel_list(
-                            "Sucessfuly sent these pixels".into(),
-                            pixels,
-                        )),
+                    let sent = pixela.send_pixels().await;
+                    if let Err(e) = UploadArchive::prune(retention) {
+                        if let Err(e) = self.event_tx().send(Event::BackgroundError(e)).await {
+                            eprintln!("Failed to send BackgroundError event: {}", e);
+                        }
+                    }
+                    match sent {
+                        Ok((pixels, summary)) => {
+                            self.pomodoro().emit_pixels_synced(pixels.len());
+                            self.set_popup(Popup::pixel_list(
+                                format!("Sent these pixels. {summary}"),
+                                pixels,
+                            ));
+                            let _ = self.event_tx().send(Event::RequestTodayOnPixela).await;
+                        }
                         Err(e) => self.set_popup(e.into()),
                     }
                 }
@@ -42,6 +91,104 @@ impl App {
                 }
             }
             Event::RequestGraph => {
+                if let Some(client) = self.pomodoro_mut().pixela_client_as_mut() {
+                    if let Some(index) = client.subjects.state().selected() {
+                        let subject = client.get_subject(index).unwrap();
+                        let (range, page) = (client.graph_range(), client.graph_page());
+                        // the cached graph shows right away, a download replaces it when it's old
+                        let cached = Graph::cached(&subject)
+                            .filter(|graph| graph.shows(&subject, range, page));
+                        let stale = cached.as_ref().is_none_or(|graph| graph.is_stale());
+                        if cached.is_some() {
+                            client.set_current_graph(cached);
+                        }
+                        if stale {
+                            self.download_graph(subject.clone(), range, page);
+                        }
+                        self.download_graph_stats(subject);
+                    }
+                }
+            }
+            Event::RefreshGraph => {
+                let stale_graph = self
+                    .pomodoro()
+                    .pixela_client()
+                    .and_then(|client| client.current_graph())
+                    .filter(|graph| graph.is_stale())
+                    .map(|graph| (graph.subject().clone(), graph.range(), graph.page()));
+                if let Some((subject, range, page)) = stale_graph {
+                    self.download_graph(subject.clone(), range, page);
+                    self.download_graph_stats(subject);
+                }
+            }
+            Event::GraphStatsReceived(res) => {
+                if let Some(client) = self.pomodoro_mut().pixela_client_as_mut() {
+                    // the panel is extra, so without it the graph just takes the whole width
+                    client.set_graph_stats(res.ok());
+                }
+            }
+            Event::RequestTodayOnPixela => {
+                let Some(client) = self.pomodoro().pixela_client() else {
+                    return;
+                };
+                let Some(subject) = client.get_current_subject() else {
+                    return;
+                };
+                if !client.logged_in() || subject.is_dummy() {
+                    return;
+                }
+                let tx_clone = self.event_tx().clone();
+                let user = client.user.clone();
+                let rq_client = client.client.clone();
+
+                tokio::spawn(async move {
+                    let today = chrono::Local::now().format("%Y%m%d").to_string();
+                    let result = get_from_pixela(&subject, &today, &rq_client, user.token()).await;
+                    let event = Event::TodayOnPixelaReceived(subject.id().to_string(), result);
+                    if let Err(e) = tx_clone.send(event).await {
+                        eprintln!("Failed to send TodayOnPixelaReceived event: {}", e);
+                    }
+                });
+            }
+            Event::EditRemotePixel(pixel, edit) => {
+                let Some(client) = self.pomodoro().pixela_client() else {
+                    return;
+                };
+                let tx_clone = self.event_tx().clone();
+                let user = client.user.clone();
+                let rq_client = client.client.clone();
+
+                tokio::spawn(async move {
+                    let result = pixel.apply(edit, user, rq_client).await;
+                    if let Err(e) = tx_clone.send(Event::RemotePixelEdited(result)).await {
+                        eprintln!("Failed to send RemotePixelEdited event: {}", e);
+                    }
+                });
+            }
+            Event::RemotePixelEdited(res) => match res {
+                Ok(pixel) => {
+                    if let Some(client) = self.pomodoro_mut().pixela_client_as_mut() {
+                        if let Some(graph) = client
+                            .current_graph_mut()
+                            .as_mut()
+                            .filter(|graph| graph.subject().id() == pixel.subject.id())
+                        {
+                            graph.set_quantity(pixel.date, pixel.progress());
+                            let saved = graph.save_graph();
+                            self.set_popup_opt(Error::handle_error_and_consume_data(saved));
+                        }
+                    }
+                    self.download_graph_stats(pixel.subject);
+                    let _ = self.event_tx().send(Event::RequestTodayOnPixela).await;
+                }
+                Err(e) => self.set_popup(e.into()),
+            },
+            Event::TodayOnPixelaReceived(subject_id, res) => {
+                if let Some(client) = self.pomodoro_mut().pixela_client_as_mut() {
+                    client.set_today_on_pixela(subject_id, res.ok());
+                }
+            }
+            Event::RequestHeatmap => {
                 let tx_clone = self.event_tx().clone();
                 if let Some(client) = self.pomodoro_mut().pixela_client_as_mut() {
                     if let Some(index) = client.subjects.state().selected() {
@@ -50,40 +197,181 @@ impl App {
                         let subject = client.get_subject(index).unwrap();
 
                         tokio::spawn(async move {
-                            let result = Graph::download_graph(user, rq_client, subject).await;
+                            let result =
+                                Graph::download_days(user, rq_client, subject, HEATMAP_DAYS).await;
+                            if let Err(e) = tx_clone.send(Event::HeatmapReceived(result)).await {
+                                eprintln!("Failed to send HeatmapReceived event: {}", e);
+                            }
+                        });
+                    }
+                }
+            }
+            Event::PullHistory(days) => {
+                let tx_clone = self.event_tx().clone();
+                if let Some(client) = self.pomodoro().pixela_client() {
+                    let user = client.user.clone();
+                    let rq_client = client.client.clone();
+                    let subjects: Vec<Subject> = client
+                        .subjects()
+                        .into_iter()
+                        .filter(|subject| !subject.is_dummy())
+                        .cloned()
+                        .collect();
+                    let end = chrono::Local::now().date_naive();
+                    let start = end - chrono::Duration::days(days - 1);
 
-                            if let Err(e) = tx_clone.send(Event::GraphReceived(result)).await {
-                                eprintln!("Failed to send GraphDataReceived event: {}", e);
+                    tokio::spawn(async move {
+                        // one graph failing doesn't keep the others from being pulled
+                        let mut graphs = Vec::new();
+                        let mut failures = Vec::new();
+                        for subject in subjects {
+                            let name = subject.graph_name().to_string();
+                            match Graph::download_span(
+                                user.clone(),
+                                rq_client.clone(),
+                                subject,
+                                start,
+                                end,
+                            )
+                            .await
+                            {
+                                Ok(graph) => graphs.push(graph),
+                                Err(e) => failures.push(format!("{name} couldn't be pulled: {e}")),
                             }
+                        }
+                        let pulled = Event::HistoryPulled(graphs, failures);
+                        if let Err(e) = tx_clone.send(pulled).await {
+                            eprintln!("Failed to send HistoryPulled event: {}", e);
+                        }
+                    });
+                }
+            }
+            Event::BackgroundError(e) => self.set_popup(e.into()),
+            Event::HistoryPulled(graphs, failures) => {
+                let mut merge = HistoryMerge::new(self.pomodoro().history(), &graphs);
+                let sessions = std::mem::take(&mut merge.sessions);
+                let added = sessions.len();
+                match self.pomodoro_mut().record_sessions(sessions) {
+                    Ok(()) => {
+                        let mut message = format!(
+                            "Added {added} sessions · {} local, {} remote, {} on both",
+                            merge.count(DayOrigin::Local),
+                            merge.count(DayOrigin::Remote),
+                            merge.count(DayOrigin::Both),
+                        );
+                        if !failures.is_empty() {
+                            message.push_str(&format!(" · {} graphs failed", failures.len()));
+                        }
+                        let days = merge.days.iter().map(|day| {
+                            format!(
+                                "{} {} · {} here, {} on Pixela · {}",
+                                day.date.format("%d %b %Y"),
+                                day.subject_name,
+                                format_duration(day.local as i64 * 60),
+                                format_duration(day.remote as i64 * 60),
+                                day.origin
+                            )
                         });
+                        let lines = failures.into_iter().chain(days).collect();
+                        self.set_popup(Popup::message_list(message, lines));
                     }
+                    Err(e) => self.set_popup(e.into()),
                 }
             }
-            Event::GraphReceived(res) => {
+            Event::HeatmapReceived(res) => match res {
+                Ok(graph) => {
+                    let today = chrono::Local::now().date_naive();
+                    self.set_heatmap(Some(Heatmap::from_graph(&graph, today)));
+                }
+                Err(e) => self.set_popup(e.into()),
+            },
+            Event::GraphReceived(subject, range, page, res) => {
                 if let Some(client) = self.pomodoro_mut().pixela_client_as_mut() {
-                    match res {
-                        Ok(g) => client.set_current_graph(Some(g)),
-                        Err(e) => self.set_popup(e.into()),
-                    }
+                    let popup = match res {
+                        Ok(g) => {
+                            let saved = g.save_graph();
+                            // a slow download doesn't replace the view the user moved on to
+                            let selected = client.subjects.state().selected();
+                            let viewed = selected.and_then(|index| client.get_subject(index));
+                            if viewed.is_some_and(|viewed| {
+                                g.shows(&viewed, client.graph_range(), client.graph_page())
+                            }) {
+                                client.set_current_graph(Some(g));
+                            }
+                            Error::handle_error_and_consume_data(saved)
+                        }
+                        Err(e) => match client.current_graph_mut() {
+                            // offline, the cached graph of this view stays with a note instead
+                            Some(cached)
+                                if e.is_transient() && cached.shows(&subject, range, page) =>
+                            {
+                                cached.set_refresh_failed(true);
+                                None
+                            }
+                            _ => Some(e.into()),
+                        },
+                    };
+                    self.set_popup_opt(popup);
                 }
             }
+            Event::TaskwarriorTasksReceived(res) => match res {
+                Ok(items) => self.show_taskwarrior_tasks(items),
+                Err(e) => self.set_popup(e.into()),
+            },
             Event::RestartTimer => {
-                self.pomodoro_mut().timer.restart().await;
+                self.pomodoro_mut().restart_timer().await;
             }
         }
     }
+    fn download_graph(&self, subject: Subject, range: GraphRange, page: i64) {
+        let Some(client) = self.pomodoro().pixela_client() else {
+            return;
+        };
+        let tx_clone = self.event_tx().clone();
+        let user = client.user.clone();
+        let rq_client = client.client.clone();
+
+        tokio::spawn(async move {
+            let result = Graph::download_graph(user, rq_client, subject.clone(), range, page).await;
+            let event = Event::GraphReceived(subject, range, page, result);
+            if let Err(e) = tx_clone.send(event).await {
+                eprintln!("Failed to send GraphDataReceived event: {}", e);
+            }
+        });
+    }
+    fn download_graph_stats(&self, subject: Subject) {
+        let Some(client) = self.pomodoro().pixela_client() else {
+            return;
+        };
+        let tx_clone = self.event_tx().clone();
+        let user = client.user.clone();
+        let rq_client = client.client.clone();
+
+        tokio::spawn(async move {
+            let result = GraphStats::download(user, rq_client, subject).await;
+            if let Err(e) = tx_clone.send(Event::GraphStatsReceived(result)).await {
+                eprintln!("Failed to send GraphStatsReceived event: {}", e);
+            }
+        });
+    }
     async fn handle_key_event(&mut self, key_event: KeyEvent) {
         //global
+        let typing_task = self.pomodoro().tasks().is_typing()
+            || self.popup().is_some_and(|popup| popup.takes_input());
         match key_event.code {
-            KeyCode::Char('Q') => self.exit(),
+            KeyCode::Char('Q') if !typing_task => self.exit(),
             KeyCode::BackTab
-                if self.popup().is_none() && !(self.settings().borrow().mode() == Mode::Input) =>
+                if self.popup().is_none()
+                    && !(self.settings().borrow().mode() == Mode::Input)
+                    && !typing_task =>
             {
                 self.settings().borrow_mut().change_mode(Mode::Normal);
                 self.selected_tab_mut().prev();
             }
             KeyCode::Tab
-                if self.popup().is_none() && !(self.settings().borrow().mode() == Mode::Input) =>
+                if self.popup().is_none()
+                    && !(self.settings().borrow().mode() == Mode::Input)
+                    && !typing_task =>
             {
                 self.settings().borrow_mut().change_mode(Mode::Normal);
                 self.selected_tab_mut().next();
@@ -98,6 +386,7 @@ impl App {
         let settings_mode = self.settings().borrow().mode();
         match self.selected_tab() {
             Tabs::TimerTab => self.handle_timer_tab(key_event).await,
+            Tabs::TasksTab => self.handle_tasks_tab(key_event).await,
             Tabs::SettingsTab => match settings_mode {
                 Mode::Modify => self.handle_settings_modify(key_event).await,
                 Mode::Input => self.handle_settings_input(key_event).await,
@@ -109,5 +398,6 @@ impl App {
     async fn overwrite_timer_for_subject(&mut self, index: usize) {
         self.pomodoro_mut().restart_timer().await;
         self.pomodoro_mut().set_current_subject_index(index);
+        let _ = self.event_tx().send(Event::RequestTodayOnPixela).await;
     }
 }
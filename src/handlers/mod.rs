@@ -2,4 +2,5 @@ pub mod event_handler;
 pub mod pixela_handlers;
 pub mod popup_handlers;
 pub mod settings_handlers;
+pub mod tasks_handlers;
 pub mod timer_tab_handlers;
@@ -5,16 +5,74 @@ use crate::{
     error::Error,
     popup::Popup,
     settings::PomodoroSettings,
-    stats::pixela::pixela_client::{PixelaClient, PixelaTabs},
+    stats::{
+        heatmap::Heatmap,
+        history::{ExportFormat, ExportOptions, History, HistoryFilter},
+        pixela::{
+            pixela_client::PixelaTabs,
+            remote_pixel::{RemoteEdit, RemotePixel},
+            send_preview::SendPreview,
+            upload_archive::{ArchiveView, UploadArchive},
+        },
+    },
+    ui::helpers::format_quantity,
 };
 
+const REMOTE_EDITS: [&str; 3] = ["Set exact value", "Subtract", "Delete"];
+const PULL_RANGES: [(&str, i64); 4] = [
+    ("Last 30 days", 30),
+    ("Last 90 days", 90),
+    ("Last year", 365),
+    ("Last 3 years", 3 * 365),
+];
+const EXPORT_RANGES: [(&str, Option<i64>); 3] = [
+    ("Last 7 days", Some(7)),
+    ("Last 30 days", Some(30)),
+    ("Everything", None),
+];
+
 impl App {
     pub async fn handle_pixela_keybinds(&mut self, key_event: KeyEvent) {
+        // the heatmap and the archive cover the tab and take its keys until closed
+        if self.heatmap().is_some() {
+            return self.handle_heatmap_keybinds(key_event);
+        }
+        if self.archive_mut().is_some() {
+            return self.handle_archive_keybinds(key_event);
+        }
+        if self.local_stats_shown() {
+            return self.handle_local_stats_keybinds(key_event);
+        }
+        match key_event.code {
+            KeyCode::Char('E') => return self.pick_export_format(),
+            KeyCode::Char('v') => return self.toggle_local_stats(),
+            KeyCode::Char('Y') => return self.sync_devices(true),
+            KeyCode::Char('A') => return self.open_archive(),
+            _ => {}
+        }
         if let Some(pixela_client) = self.pomodoro_mut().pixela_client_as_mut() {
             match key_event.code {
                 KeyCode::Char('L') if !pixela_client.logged_in() => {
-                    let res = pixela_client.log_in().await;
+                    let res = match pixela_client.log_in().await {
+                        Ok(()) => pixela_client
+                            .reconcile_uploads()
+                            .await
+                            .and_then(|_| pixela_client.check_aggregate_graph()),
+                        Err(e) => Err(e),
+                    };
+                    let logged_in = pixela_client.logged_in();
+                    let untracked = pixela_client.untracked_graphs().to_vec();
                     self.set_popup_opt(Error::handle_error_and_consume_data(res));
+                    if logged_in && !untracked.is_empty() && self.popup().is_none() {
+                        self.set_popup(Popup::message_list(
+                            "These graphs have a unit rtuion doesn't know, map it in [stats_setting.unit_aliases]".into(),
+                            untracked,
+                        ));
+                    }
+                    if logged_in {
+                        let _ = self.event_tx().send(Event::RefreshGraph).await;
+                        let _ = self.event_tx().send(Event::RequestTodayOnPixela).await;
+                    }
                 }
                 KeyCode::Right | KeyCode::Char('l') => pixela_client.change_focused_pane(true),
                 KeyCode::Left | KeyCode::Char('h') => pixela_client.change_focused_pane(false),
@@ -32,7 +90,12 @@ impl App {
                         } else {
                             self.ask_overwrite_subject(index);
                         }
-                        if let Some(subject) = self.pomodoro().get_current_subject() {
+                        // count graphs get a pixel per block, whatever its length
+                        if let Some(subject) = self
+                            .pomodoro()
+                            .get_current_subject()
+                            .filter(|subject| !subject.is_count())
+                        {
                             let time = (subject.get_min_increment() * 60) as i64;
                             self.pomodoro_mut()
                                 .set_setting(PomodoroSettings::WorkTime(time))
@@ -44,6 +107,23 @@ impl App {
                 KeyCode::Char('G') => {
                     let _ = self.event_tx().send(Event::RequestGraph).await;
                 }
+                KeyCode::Char('R') | KeyCode::Char('[') | KeyCode::Char(']') => {
+                    match key_event.code {
+                        KeyCode::Char('R') => pixela_client.next_graph_range(),
+                        KeyCode::Char('[') => pixela_client.page_graph(true),
+                        _ => pixela_client.page_graph(false),
+                    }
+                    let _ = self.event_tx().send(Event::RequestGraph).await;
+                }
+                KeyCode::Char('e')
+                    if pixela_client.logged_in() && pixela_client.current_graph().is_some() =>
+                {
+                    self.pick_remote_pixel();
+                }
+                KeyCode::Char('U') if pixela_client.logged_in() => self.pick_pull_range(),
+                KeyCode::Char('H') if pixela_client.logged_in() => {
+                    let _ = self.event_tx().send(Event::RequestHeatmap).await;
+                }
                 KeyCode::Char(' ') | KeyCode::Enter
                     if pixela_client.focused_pane() == PixelaTabs::Pixels =>
                 {
@@ -60,13 +140,7 @@ impl App {
                         && !pixela_client.pixels_to_send_is_empty()
                         && pixela_client.logged_in) =>
                 {
-                    let pixels =
-                        PixelaClient::combine_similiar_pixels(pixela_client.get_selected_pixels());
-                    self.set_popup(Popup::pixel_confirm_list(
-                        "These pixels will be sent".into(),
-                        Box::new(App::ask_send_pixels),
-                        pixels,
-                    ));
+                    let _ = self.event_tx().send(Event::PreviewPixels).await;
                 }
                 KeyCode::Char('d') if pixela_client.focused_pane() == PixelaTabs::Pixels => {
                     self.set_popup(Popup::yes_no(
@@ -78,8 +152,277 @@ impl App {
             };
         }
     }
-    fn ask_send_pixels(&mut self) {
-        let _ = self.event_tx().try_send(Event::SendPixels);
+    fn handle_local_stats_keybinds(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Char('E') => self.pick_export_format(),
+            KeyCode::Char('H') => {
+                let today = chrono::Local::now().date_naive();
+                let heatmap = Heatmap::from_sessions(self.pomodoro().history(), today);
+                self.set_heatmap(Some(heatmap));
+            }
+            KeyCode::Char('v') => self.toggle_local_stats(),
+            KeyCode::Char('Y') => self.sync_devices(true),
+            _ => {}
+        }
+    }
+    fn handle_heatmap_keybinds(&mut self, key_event: KeyEvent) {
+        let Some(heatmap) = self.heatmap_mut() else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Right | KeyCode::Char('l') => heatmap.move_cursor(7),
+            KeyCode::Left | KeyCode::Char('h') => heatmap.move_cursor(-7),
+            KeyCode::Down | KeyCode::Char('j') => heatmap.move_cursor(1),
+            KeyCode::Up | KeyCode::Char('k') => heatmap.move_cursor(-1),
+            KeyCode::Esc | KeyCode::Char('H') => self.set_heatmap(None),
+            _ => {}
+        }
+    }
+    /// Merges what the other machines put in the sync folder. `report` shows the
+    /// outcome even when nothing conflicted, at startup only conflicts are worth a popup.
+    pub fn sync_devices(&mut self, report: bool) {
+        match self.pomodoro_mut().sync_folder() {
+            Ok(sync) if report || !sync.conflicts.is_empty() || sync.skipped_rows > 0 => {
+                let mut message = format!(
+                    "Took in {} pixels and {} sessions, {} pixels were sent or deleted elsewhere",
+                    sync.pixels_added, sync.sessions_added, sync.pixels_removed
+                );
+                if sync.skipped_rows > 0 {
+                    message.push_str(&format!(
+                        ". Skipped {} unreadable history rows of other machines",
+                        sync.skipped_rows
+                    ));
+                }
+                let conflicts = sync
+                    .conflicts
+                    .iter()
+                    .map(|conflict| {
+                        format!(
+                            "{} on {}, kept this machine's",
+                            conflict.description, conflict.device
+                        )
+                    })
+                    .collect();
+                self.set_popup(Popup::message_list(message, conflicts));
+            }
+            Ok(_) => {}
+            Err(e) => self.set_popup(e.into()),
+        }
+    }
+    fn pick_pull_range(&mut self) {
+        let ranges = PULL_RANGES
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        self.set_popup(Popup::picker(
+            "Pull the history of every subject from Pixela".into(),
+            ranges,
+            Box::new(|app: &mut App, index| {
+                let _ = app
+                    .event_tx()
+                    .try_send(Event::PullHistory(PULL_RANGES[index].1));
+            }),
+        ));
+    }
+    fn open_archive(&mut self) {
+        let retention = self.settings().borrow().archive_settings.retention_days;
+        let uploads = UploadArchive::prune(retention).and_then(|_| UploadArchive::load());
+        match uploads {
+            Ok(uploads) => self.set_archive(Some(ArchiveView::new(uploads))),
+            Err(e) => self.set_popup(e.into()),
+        }
+    }
+    fn handle_archive_keybinds(&mut self, key_event: KeyEvent) {
+        let Some(archive) = self.archive_mut() else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Down | KeyCode::Char('j') => archive.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => archive.select_previous(),
+            KeyCode::Char('f') => archive.next_subject(),
+            KeyCode::Char('x') => archive.toggle_failed_only(),
+            KeyCode::Esc | KeyCode::Char('A') => self.set_archive(None),
+            _ => {}
+        }
+    }
+    fn pick_remote_pixel(&mut self) {
+        let Some(graph) = self
+            .pomodoro()
+            .pixela_client()
+            .and_then(|client| client.current_graph())
+        else {
+            return;
+        };
+        let subject = graph.subject().clone();
+        let pixels: Vec<RemotePixel> = graph
+            .data()
+            .iter()
+            .rev()
+            .filter_map(|dp| {
+                let day = dp.day()?;
+                Some(RemotePixel::new(
+                    subject.clone(),
+                    day,
+                    dp.quantity().get_as_float(),
+                ))
+            })
+            .collect();
+        let items = pixels
+            .iter()
+            .map(|pixel| {
+                format!(
+                    "{}: {}",
+                    pixel.date.format("%a %d %b %Y"),
+                    format_quantity(pixel.quantity, subject.unit())
+                )
+            })
+            .collect();
+        self.set_popup(Popup::picker(
+            format!("Which day of {} to change on Pixela?", subject.graph_name()),
+            items,
+            Box::new(move |app: &mut App, index| {
+                app.pick_remote_edit(pixels[index].clone());
+            }),
+        ));
+    }
+    fn pick_remote_edit(&mut self, pixel: RemotePixel) {
+        let items = REMOTE_EDITS.iter().map(|name| name.to_string()).collect();
+        self.set_popup(Popup::picker(
+            format!(
+                "{}: {}",
+                pixel.date.format("%a %d %b %Y"),
+                format_quantity(pixel.quantity, pixel.subject.unit())
+            ),
+            items,
+            Box::new(move |app: &mut App, index| match index {
+                0 | 1 => app.ask_remote_quantity(pixel, index == 1),
+                _ => app.confirm_remote_edit(pixel, RemoteEdit::Delete),
+            }),
+        ));
+    }
+    fn ask_remote_quantity(&mut self, pixel: RemotePixel, subtract: bool) {
+        let unit = pixel.subject.unit().to_string().to_lowercase();
+        let message = match subtract {
+            true => format!("How many {unit} to take away?"),
+            false => format!("New value in {unit}"),
+        };
+        self.set_popup(Popup::input(
+            message,
+            String::new(),
+            Box::new(move |app: &mut App, input| {
+                match RemoteEdit::parse_quantity(&input, &pixel.subject) {
+                    Ok(quantity) if subtract => {
+                        app.confirm_remote_edit(pixel, RemoteEdit::Subtract(quantity))
+                    }
+                    Ok(quantity) => app.confirm_remote_edit(pixel, RemoteEdit::Set(quantity)),
+                    Err(e) => app.set_popup(e.into()),
+                }
+            }),
+        ));
+    }
+    fn confirm_remote_edit(&mut self, pixel: RemotePixel, edit: RemoteEdit) {
+        let unit = pixel.subject.unit();
+        let message = format!(
+            "{}, {}: {} → {}. Change it on Pixela?",
+            pixel.subject.graph_name(),
+            pixel.date.format("%a %d %b %Y"),
+            format_quantity(pixel.quantity, unit),
+            format_quantity(edit.after(pixel.quantity), unit)
+        );
+        self.set_popup(Popup::yes_no(
+            message,
+            Box::new(move |app: &mut App| {
+                let _ = app.event_tx().try_send(Event::EditRemotePixel(pixel, edit));
+            }),
+        ));
+    }
+    fn pick_export_format(&mut self) {
+        let formats = ExportFormat::ALL
+            .iter()
+            .map(|format| format.extension().to_uppercase())
+            .collect();
+        self.set_popup(Popup::picker(
+            "Export your session history as".into(),
+            formats,
+            Box::new(|app: &mut App, index| app.pick_export_range(ExportFormat::ALL[index])),
+        ));
+    }
+    fn pick_export_range(&mut self, format: ExportFormat) {
+        let ranges = EXPORT_RANGES
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        self.set_popup(Popup::picker(
+            "Which sessions?".into(),
+            ranges,
+            Box::new(move |app: &mut App, index| {
+                let from = EXPORT_RANGES[index].1.map(|days| {
+                    chrono::Local::now().date_naive() - chrono::Duration::days(days - 1)
+                });
+                let filter = HistoryFilter {
+                    from,
+                    ..Default::default()
+                };
+                app.pick_export_subject(ExportOptions {
+                    format,
+                    filter,
+                    output: None,
+                });
+            }),
+        ));
+    }
+    fn pick_export_subject(&mut self, options: ExportOptions) {
+        let subjects: Vec<String> = self
+            .pomodoro()
+            .pixela_client()
+            .map(|client| {
+                client
+                    .subjects()
+                    .iter()
+                    .filter(|subject| !subject.is_dummy())
+                    .map(|subject| subject.id().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if subjects.is_empty() {
+            self.export_history(options);
+            return;
+        }
+        let mut items = vec!["All subjects".to_string()];
+        items.extend(subjects.iter().cloned());
+        self.set_popup(Popup::picker(
+            "Which subject?".into(),
+            items,
+            Box::new(move |app: &mut App, index| {
+                let mut options = options;
+                options.filter.subject = index.checked_sub(1).map(|index| subjects[index].clone());
+                app.export_history(options);
+            }),
+        ));
+    }
+    fn export_history(&mut self, options: ExportOptions) {
+        let result = History::export_path(options.format).and_then(|path| {
+            std::fs::write(&path, options.render()?)?;
+            Ok(path)
+        });
+        match result {
+            Ok(path) => self.set_popup(Popup::message_list(
+                "History exported to".into(),
+                vec![path.display().to_string()],
+            )),
+            Err(e) => self.set_popup(e.into()),
+        }
+    }
+    pub(crate) fn ask_send_pixels(&mut self, preview: SendPreview) {
+        let Some(pixela_client) = self.pomodoro_mut().pixela_client_as_mut() else {
+            return;
+        };
+        for pixel in preview.excluded() {
+            pixela_client.unselect_day(pixel);
+        }
+        if preview.selected_count() > 0 {
+            let _ = self.event_tx().try_send(Event::SendPixels);
+        }
     }
     fn ask_delete_pixel(&mut self) {
         if self.pomodoro_mut().pixela_client_as_mut().is_some() {
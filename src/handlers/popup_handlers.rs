@@ -31,17 +31,73 @@ impl App {
         self.set_popup(popup);
         if let Some(popup) = self.take_popup() {
             match popup.kind {
-                PopupKind::YesNoPopup(callback) | PopupKind::SendPixelsPopup(callback, _, _) => {
-                    match key_event.code {
-                        KeyCode::Char('y') => {
-                            callback(self);
-                            self.clear_popup()
+                PopupKind::YesNoPopup(callback) => match key_event.code {
+                    KeyCode::Char('y') => {
+                        callback(self);
+                        self.clear_popup()
+                    }
+                    KeyCode::Char('n') => self.clear_popup(),
+                    _ => {}
+                },
+                PopupKind::SendPixelsPopup(callback, mut preview, state) => match key_event.code {
+                    KeyCode::Char('y') => {
+                        self.clear_popup();
+                        callback(self, preview);
+                    }
+                    KeyCode::Char('n') => self.clear_popup(),
+                    KeyCode::Char(' ') | KeyCode::Enter => {
+                        if let Some(index) = state.selected() {
+                            preview.toggle(index);
                         }
-                        KeyCode::Char('n') => self.clear_popup(),
-                        _ => {}
+                        self.set_popup(Popup {
+                            message: popup.message,
+                            kind: PopupKind::SendPixelsPopup(callback, preview, state),
+                            scrollable: true,
+                        });
                     }
-                }
-                PopupKind::ErrorPopup(_) | PopupKind::ListPopup(_, _) => self.clear_popup(),
+                    _ => {}
+                },
+                PopupKind::ErrorPopup(_)
+                | PopupKind::ListPopup(_, _)
+                | PopupKind::MessageListPopup(_, _) => self.clear_popup(),
+                PopupKind::PickerPopup(callback, items, mut state) => match key_event.code {
+                    KeyCode::Enter | KeyCode::Char(' ') => {
+                        self.clear_popup();
+                        if let Some(index) = state.selected().filter(|_| !items.is_empty()) {
+                            callback(self, index.min(items.len() - 1));
+                        }
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') => self.clear_popup(),
+                    key => {
+                        match key {
+                            KeyCode::Down | KeyCode::Char('j') => state.select_next(),
+                            KeyCode::Up | KeyCode::Char('k') => state.select_previous(),
+                            _ => {}
+                        }
+                        self.set_popup(Popup {
+                            message: popup.message,
+                            kind: PopupKind::PickerPopup(callback, items, state),
+                            scrollable: false,
+                        });
+                    }
+                },
+                PopupKind::InputPopup(callback, mut input) => match key_event.code {
+                    KeyCode::Enter => {
+                        self.clear_popup();
+                        callback(self, input);
+                    }
+                    KeyCode::Esc => self.clear_popup(),
+                    key => {
+                        match key {
+                            KeyCode::Char(c) => input.push(c),
+                            KeyCode::Backspace => {
+                                input.pop();
+                            }
+                            _ => {}
+                        }
+                        self.set_popup(Popup::input(popup.message, input, callback));
+                    }
+                },
             }
         }
     }
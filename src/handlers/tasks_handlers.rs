@@ -0,0 +1,208 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    app::{App, Event},
+    error::{Error, SettingsError},
+    popup::Popup,
+    tasks::{
+        taskwarrior::{Taskwarrior, TaskwarriorTask},
+        todo_txt::{self, TodoItem, TodoTxt},
+    },
+};
+
+impl App {
+    pub async fn handle_tasks_tab(&mut self, key_event: KeyEvent) {
+        if self.pomodoro().tasks().is_typing() {
+            self.handle_tasks_input(key_event);
+            return;
+        }
+        let tasks = self.pomodoro_mut().tasks_mut();
+        match key_event.code {
+            KeyCode::Char('a') => tasks.start_input(),
+            KeyCode::Down | KeyCode::Char('j') => tasks.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => tasks.select_previous(),
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                tasks.toggle_active_selected();
+                self.pomodoro_mut().active_task_changed();
+                self.track_active_task_subject();
+                self.save_tasks();
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                if let Some(task) = tasks.selected_mut() {
+                    task.increase_estimate();
+                }
+                self.save_tasks();
+            }
+            KeyCode::Char('-') => {
+                if let Some(task) = tasks.selected_mut() {
+                    task.decrease_estimate();
+                }
+                self.save_tasks();
+            }
+            KeyCode::Char('c') => {
+                if let Some(task) = tasks.selected_mut() {
+                    task.toggle_done();
+                }
+                self.write_back_selected_task();
+                self.save_tasks();
+            }
+            KeyCode::Char('s') => {
+                let subject = self.pomodoro().get_current_subject();
+                self.pomodoro_mut()
+                    .tasks_mut()
+                    .set_selected_subject(subject.as_ref());
+                self.save_tasks();
+            }
+            KeyCode::Char('d') if tasks.selected().is_some() => {
+                self.set_popup(Popup::yes_no(
+                    "This task will be deleted, you sure?".into(),
+                    Box::new(App::delete_selected_task),
+                ));
+            }
+            _ => {}
+        }
+    }
+    fn handle_tasks_input(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc => self.pomodoro_mut().tasks_mut().cancel_input(),
+            KeyCode::Enter => {
+                self.pomodoro_mut().tasks_mut().finish_input();
+                self.save_tasks();
+            }
+            _ => {
+                let mut text = None;
+                if key_event.code == KeyCode::Char('v')
+                    && key_event.modifiers == KeyModifiers::CONTROL
+                {
+                    if let Some(clip) = self.clipboard_mut().as_mut() {
+                        text = clip.get_text().ok();
+                    }
+                }
+                self.pomodoro_mut().tasks_mut().input(key_event, text);
+            }
+        }
+    }
+    /// Switches Pixela tracking to the active task's subject, unless that would reset a
+    /// running session.
+    fn track_active_task_subject(&mut self) {
+        if self.pomodoro().timer.timer_started() {
+            return;
+        }
+        let Some(subject_id) = self
+            .pomodoro()
+            .tasks()
+            .active_task()
+            .and_then(|task| task.subject_id())
+            .map(|id| id.to_string())
+        else {
+            return;
+        };
+        let index = self.pomodoro().pixela_client().and_then(|client| {
+            client
+                .subjects()
+                .iter()
+                .position(|subject| subject.id() == subject_id)
+        });
+        if let Some(index) = index {
+            self.pomodoro_mut().set_current_subject_index(index);
+        }
+    }
+    pub fn pick_todo_task(&mut self) {
+        let settings = self.settings().borrow().todo_txt_settings.clone();
+        let items = match TodoTxt::load(&settings) {
+            Ok(todo) => todo.open_items(),
+            Err(e) => {
+                self.set_popup(e.into());
+                return;
+            }
+        };
+        let message = match items.is_empty() {
+            true => "There are no open tasks in your todo.txt",
+            false => "Pick a todo.txt task to work on",
+        };
+        let labels = items.iter().map(TodoItem::to_line).collect();
+        self.set_popup(Popup::picker(
+            message.into(),
+            labels,
+            Box::new(move |app: &mut App, index| app.activate_todo(&items[index])),
+        ));
+    }
+    fn activate_todo(&mut self, item: &TodoItem) {
+        let settings = self.settings().borrow().todo_txt_settings.clone();
+        let subject = self
+            .pomodoro()
+            .pixela_client()
+            .and_then(|client| item.subject(&settings, &client.subjects()).cloned());
+        self.pomodoro_mut()
+            .tasks_mut()
+            .pick_todo(item, subject.as_ref());
+        self.pomodoro_mut().active_task_changed();
+        self.track_active_task_subject();
+        self.save_tasks();
+    }
+    /// Asks Taskwarrior for the pending tasks, they come back as an event.
+    pub fn pick_taskwarrior_task(&mut self) {
+        let settings = self.settings().borrow().taskwarrior_settings.clone();
+        if !settings.enabled {
+            let error = SettingsError::WrongSetting("Taskwarrior is not enabled".into());
+            self.set_popup(Error::from(error).into());
+            return;
+        }
+        let tx_clone = self.event_tx().clone();
+        tokio::spawn(async move {
+            let result = Taskwarrior::pending(&settings).await;
+            if let Err(e) = tx_clone.send(Event::TaskwarriorTasksReceived(result)).await {
+                eprintln!("Failed to send TaskwarriorTasksReceived event: {}", e);
+            }
+        });
+    }
+    pub fn show_taskwarrior_tasks(&mut self, items: Vec<TaskwarriorTask>) {
+        let message = match items.is_empty() {
+            true => "There are no pending Taskwarrior tasks",
+            false => "Pick a Taskwarrior task to work on",
+        };
+        let labels = items.iter().map(TaskwarriorTask::label).collect();
+        self.set_popup(Popup::picker(
+            message.into(),
+            labels,
+            Box::new(move |app: &mut App, index| app.activate_taskwarrior(&items[index])),
+        ));
+    }
+    fn activate_taskwarrior(&mut self, item: &TaskwarriorTask) {
+        let settings = self.settings().borrow().taskwarrior_settings.clone();
+        let subject = self
+            .pomodoro()
+            .pixela_client()
+            .and_then(|client| item.subject(&settings, &client.subjects()).cloned());
+        self.pomodoro_mut()
+            .tasks_mut()
+            .pick_taskwarrior(item, subject.as_ref());
+        self.pomodoro_mut().active_task_changed();
+        self.track_active_task_subject();
+        self.save_tasks();
+    }
+    fn write_back_selected_task(&mut self) {
+        let settings = self.settings().borrow().todo_txt_settings.clone();
+        if let Some(task) = self.pomodoro().tasks().selected() {
+            if let Some(taskwarrior) = self.pomodoro().taskwarrior() {
+                taskwarrior.set_done(task);
+            }
+            let popup =
+                Error::handle_error_and_consume_data(todo_txt::write_back(&settings, task, true));
+            if popup.is_some() {
+                self.set_popup_opt(popup);
+            }
+        }
+    }
+    fn delete_selected_task(&mut self) {
+        self.pomodoro_mut().tasks_mut().remove_selected();
+        self.pomodoro_mut().active_task_changed();
+        self.save_tasks();
+    }
+    fn save_tasks(&mut self) {
+        let popup = Error::handle_error_and_consume_data(self.pomodoro().tasks().save());
+        if popup.is_some() {
+            self.set_popup_opt(popup);
+        }
+    }
+}
@@ -20,7 +20,7 @@ impl App {
                     if self.pomodoro().timer.in_work_state()
                         && self.pomodoro().timer.get_running() =>
                 {
-                    self.pomodoro_mut().timer.next_iteration().await;
+                    self.pomodoro_mut().next_phase().await;
                     if let TimerState::Break(_) = self.pomodoro().timer.current_state() {
                         self.handle_flowmodoro_logging();
                     }
@@ -30,6 +30,8 @@ impl App {
             },
         }
         match key_event.code {
+            KeyCode::Char('t') => self.pick_todo_task(),
+            KeyCode::Char('T') => self.pick_taskwarrior_task(),
             KeyCode::Char('r') => {
                 self.set_popup(Popup::yes_no(
                     "Do you want to reset your timer?".to_string(),
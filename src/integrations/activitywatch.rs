@@ -0,0 +1,156 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+
+use crate::{
+    error::{IntegrationError, Result},
+    settings::ActivityWatchSettings,
+    stats::session::{Phase, Session},
+};
+
+const BUCKET_TYPE: &str = "app.rtuion.session";
+const CLIENT_NAME: &str = "rtuion";
+
+#[derive(Debug, Clone)]
+pub struct ActivityWatchSink {
+    client: Client,
+    url: String,
+    bucket_id: String,
+    hostname: String,
+    include_breaks: bool,
+    bucket_created: Arc<AtomicBool>,
+}
+impl ActivityWatchSink {
+    pub fn new(settings: &ActivityWatchSettings, client: Client) -> Option<ActivityWatchSink> {
+        if !settings.enabled {
+            return None;
+        }
+        let hostname = hostname();
+        let bucket_id = settings
+            .bucket
+            .clone()
+            .filter(|bucket| !bucket.is_empty())
+            .unwrap_or_else(|| format!("{CLIENT_NAME}_{hostname}"));
+        Some(ActivityWatchSink {
+            client,
+            url: settings.url.trim_end_matches('/').to_string(),
+            bucket_id,
+            hostname,
+            include_breaks: settings.include_breaks,
+            bucket_created: Arc::new(AtomicBool::new(false)),
+        })
+    }
+    pub fn send(&self, session: &Session) {
+        if session.phase() == Phase::Break && !self.include_breaks {
+            return;
+        }
+        let sink = self.clone();
+        let event = ActivityWatchSink::event_payload(session);
+        tokio::spawn(async move {
+            let _ = sink.push_event(event).await;
+        });
+    }
+    pub fn event_payload(session: &Session) -> Value {
+        let subject = session.subject_name().unwrap_or("None");
+        json!({
+            "timestamp": session.start().to_utc().to_rfc3339(),
+            "duration": session.duration() as f64,
+            "data": {
+                "title": format!("{}: {}", session.phase(), subject),
+                "phase": session.phase(),
+                "plan": session.plan(),
+                "iteration": session.iteration(),
+                "subject": subject,
+                "subject_id": session.subject_id(),
+            }
+        })
+    }
+    async fn push_event(&self, event: Value) -> Result<()> {
+        self.ensure_bucket().await?;
+        let url = format!("{}/api/0/buckets/{}/events", self.url, self.bucket_id);
+        let response = self
+            .client
+            .post(url)
+            .json(&[event])
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(IntegrationError::ActivityWatchRejected(status).into()),
+        }
+    }
+    async fn ensure_bucket(&self) -> Result<()> {
+        if self.bucket_created.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let url = format!("{}/api/0/buckets/{}", self.url, self.bucket_id);
+        let response = self
+            .client
+            .post(url)
+            .json(&json!({
+                "client": CLIENT_NAME,
+                "type": BUCKET_TYPE,
+                "hostname": self.hostname,
+            }))
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await?;
+        match response.status() {
+            // aw-server answers 304 when the bucket already exists
+            status if status.is_success() || status == StatusCode::NOT_MODIFIED => {
+                self.bucket_created.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            status => Err(IntegrationError::ActivityWatchRejected(status).into()),
+        }
+    }
+}
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use super::*;
+    use crate::stats::{pixela::subjects::Subject, session::Plan};
+
+    #[test]
+    fn event_payload_is_tagged_with_phase_and_plan() {
+        let end = Local::now();
+        let start = end - Duration::minutes(25);
+        let session = Session::new(
+            start,
+            end,
+            1500,
+            Phase::Work,
+            Plan::Pomodoro,
+            2,
+            Some(&Subject::new_dummy()),
+        );
+        let payload = ActivityWatchSink::event_payload(&session);
+        assert_eq!(payload["duration"], 1500.0);
+        assert_eq!(payload["data"]["phase"], "work");
+        assert_eq!(payload["data"]["plan"], "pomodoro");
+        assert_eq!(payload["data"]["subject"], "None");
+        assert_eq!(payload["data"]["title"], "work: None");
+    }
+}
@@ -0,0 +1,131 @@
+use std::{collections::HashMap, process::Stdio, time::Duration};
+
+use tokio::{io::AsyncWriteExt, process::Command};
+
+use crate::{error::Result, settings::HookSettings};
+
+use super::session_event::{SessionEvent, SessionEventKind};
+
+#[derive(Debug, Default, Clone)]
+pub struct HookRunner {
+    commands: HashMap<SessionEventKind, String>,
+    timeout: Duration,
+}
+impl HookRunner {
+    pub fn new(settings: &HookSettings) -> HookRunner {
+        let commands = [
+            (SessionEventKind::WorkStart, &settings.on_work_start),
+            (SessionEventKind::BreakStart, &settings.on_break_start),
+            (SessionEventKind::PhaseEnd, &settings.on_phase_end),
+            (SessionEventKind::SessionFinish, &settings.on_session_finish),
+            (SessionEventKind::PixelLogged, &settings.on_pixel_logged),
+            (SessionEventKind::PixelsSynced, &settings.on_pixels_synced),
+        ]
+        .into_iter()
+        .filter_map(|(kind, command)| {
+            command
+                .as_ref()
+                .filter(|command| !command.trim().is_empty())
+                .map(|command| (kind, command.clone()))
+        })
+        .collect();
+        HookRunner {
+            commands,
+            timeout: Duration::from_secs(settings.timeout_secs),
+        }
+    }
+    pub fn run(&self, event: &SessionEvent) {
+        let Some(command) = self.commands.get(&event.kind) else {
+            return;
+        };
+        let command = command.clone();
+        let env = event.env_vars();
+        let payload = serde_json::to_vec(event).expect("event should serialize");
+        let timeout = self.timeout;
+        // hooks are fire and forget, a broken script must never stall the timer
+        tokio::spawn(async move {
+            let _ = HookRunner::run_command(command, env, payload, timeout).await;
+        });
+    }
+    async fn run_command(
+        command: String,
+        env: Vec<(&'static str, String)>,
+        payload: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<()> {
+        let mut child = HookRunner::shell(&command)
+            .envs(env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // the hook is free to ignore stdin, so a broken pipe is not an error
+            let _ = stdin.write_all(&payload).await;
+        }
+        if tokio::time::timeout(timeout, child.wait()).await.is_err() {
+            child.kill().await?;
+        }
+        Ok(())
+    }
+    #[cfg(windows)]
+    fn shell(command: &str) -> Command {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    }
+    #[cfg(not(windows))]
+    fn shell(command: &str) -> Command {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_commands_are_not_registered() {
+        let settings = HookSettings {
+            on_work_start: Some("notify-send work".into()),
+            on_break_start: Some("  ".into()),
+            ..HookSettings::default()
+        };
+        let runner = HookRunner::new(&settings);
+        assert_eq!(
+            runner.commands.keys().collect::<Vec<_>>(),
+            vec![&SessionEventKind::WorkStart]
+        );
+        assert_eq!(runner.timeout, Duration::from_secs(settings.timeout_secs));
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn hook_gets_the_event_in_env_and_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("hook.txt");
+        let command = format!(
+            "printf '%s\\n' \"$RTUION_EVENT\" > {0}; cat >> {0}",
+            out.display()
+        );
+        let env = vec![("RTUION_EVENT", "work_start".to_string())];
+        HookRunner::run_command(command, env, b"{}".to_vec(), Duration::from_secs(5))
+            .await
+            .unwrap();
+        let written = std::fs::read_to_string(&out).unwrap();
+        assert_eq!(written, "work_start\n{}");
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn hung_hooks_are_killed_after_the_timeout() {
+        let started = std::time::Instant::now();
+        HookRunner::run_command("sleep 10".into(), vec![], vec![], Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
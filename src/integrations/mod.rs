@@ -0,0 +1,70 @@
+pub mod activitywatch;
+pub mod hooks;
+pub mod mqtt;
+pub mod org_clock;
+pub mod session_event;
+pub mod timewarrior;
+pub mod webhooks;
+
+use activitywatch::ActivityWatchSink;
+use hooks::HookRunner;
+use mqtt::{MqttPublisher, TimerSnapshot};
+use org_clock::OrgClockWriter;
+use session_event::{SessionEvent, SessionEventKind};
+use timewarrior::TimewarriorExporter;
+use webhooks::WebhookSink;
+
+use crate::{app::Event, settings::Settings, stats::session::Session};
+
+/// Fans timer and stats events out to every configured integration.
+#[derive(Debug)]
+pub struct Integrations {
+    hooks: HookRunner,
+    webhooks: WebhookSink,
+    mqtt: Option<MqttPublisher>,
+    activitywatch: Option<ActivityWatchSink>,
+    timewarrior: Option<TimewarriorExporter>,
+    org_clock: Option<OrgClockWriter>,
+}
+impl Integrations {
+    pub fn new(settings: &Settings, client: reqwest::Client) -> Integrations {
+        Integrations {
+            hooks: HookRunner::new(&settings.hook_settings),
+            webhooks: WebhookSink::new(&settings.webhook_settings, client.clone()),
+            mqtt: MqttPublisher::new(&settings.mqtt_settings),
+            activitywatch: ActivityWatchSink::new(&settings.activitywatch_settings, client.clone()),
+            timewarrior: TimewarriorExporter::new(&settings.timewarrior_settings),
+            org_clock: OrgClockWriter::new(&settings.org_clock_settings),
+        }
+    }
+    pub fn set_event_tx(&mut self, event_tx: tokio::sync::mpsc::Sender<Event>) {
+        self.webhooks.set_event_tx(event_tx);
+    }
+    pub fn dispatch(&self, event: SessionEvent) {
+        self.hooks.run(&event);
+        self.webhooks.send(&event);
+        if let Some(mqtt) = &self.mqtt {
+            mqtt.publish_event(&event);
+        }
+        if let (SessionEventKind::PhaseEnd, Some(session)) = (event.kind, &event.session) {
+            self.record_session(session);
+        }
+    }
+    /// Finished phases go to the time trackers that only care about whole sessions.
+    fn record_session(&self, session: &Session) {
+        if let Some(activitywatch) = &self.activitywatch {
+            activitywatch.send(session);
+        }
+        if let Some(timewarrior) = &self.timewarrior {
+            timewarrior.send(session);
+        }
+        if let Some(org_clock) = &self.org_clock {
+            org_clock.send(session);
+        }
+    }
+    pub fn publish_state(&self, snapshot: TimerSnapshot) {
+        if let Some(mqtt) = &self.mqtt {
+            mqtt.publish_state(snapshot);
+        }
+    }
+}
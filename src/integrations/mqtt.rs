@@ -0,0 +1,171 @@
+use std::{cell::RefCell, time::Duration};
+
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS};
+use serde::Serialize;
+
+use crate::{
+    settings::MqttSettings,
+    stats::{pixela::subjects::Seconds, session::Phase},
+};
+
+use super::session_event::SessionEvent;
+
+const ONLINE: &str = "online";
+const OFFLINE: &str = "offline";
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TimerSnapshot {
+    pub phase: Phase,
+    pub running: bool,
+    pub remaining: Option<Seconds>,
+    pub subject: Option<String>,
+}
+#[derive(Debug)]
+pub struct MqttPublisher {
+    client: AsyncClient,
+    topic_prefix: String,
+    retain: bool,
+    last_snapshot: RefCell<Option<TimerSnapshot>>,
+}
+impl MqttPublisher {
+    pub fn new(settings: &MqttSettings) -> Option<MqttPublisher> {
+        if !settings.enabled {
+            return None;
+        }
+        let topic_prefix = settings.topic_prefix.trim_end_matches('/').to_string();
+        let status_topic = topic(&topic_prefix, "status");
+        let mut options = MqttOptions::new(&settings.client_id, &settings.host, settings.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        options.set_last_will(LastWill::new(
+            &status_topic,
+            OFFLINE,
+            QoS::AtLeastOnce,
+            true,
+        ));
+        if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+            options.set_credentials(username, password);
+        }
+        let (client, mut eventloop) = AsyncClient::new(options, 64);
+        let status_client = client.clone();
+        tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        // announce ourselves again after every (re)connect, the will clears it
+                        let _ = status_client.try_publish(
+                            &status_topic,
+                            QoS::AtLeastOnce,
+                            true,
+                            ONLINE,
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(_) => tokio::time::sleep(Duration::from_secs(5)).await,
+                }
+            }
+        });
+        Some(MqttPublisher {
+            client,
+            topic_prefix,
+            retain: settings.retain,
+            last_snapshot: RefCell::new(None),
+        })
+    }
+    fn publish(&self, name: &str, retain: bool, payload: String) {
+        // never wait on the broker, a full queue just drops the update
+        let _ = self.client.try_publish(
+            topic(&self.topic_prefix, name),
+            QoS::AtMostOnce,
+            retain,
+            payload,
+        );
+    }
+    pub fn publish_state(&self, snapshot: TimerSnapshot) {
+        let mut last = self.last_snapshot.borrow_mut();
+        if last.as_ref() == Some(&snapshot) {
+            return;
+        }
+        let previous = last.replace(snapshot.clone());
+        for (name, payload) in state_messages(previous.as_ref(), &snapshot) {
+            self.publish(name, self.retain, payload);
+        }
+    }
+    pub fn publish_event(&self, event: &SessionEvent) {
+        let payload = serde_json::to_string(event).expect("event should serialize");
+        self.publish("event", false, payload);
+    }
+}
+fn topic(prefix: &str, name: &str) -> String {
+    format!("{prefix}/{name}")
+}
+/// The topics (below the prefix) and payloads for a new snapshot, only the fields that
+/// changed get their own topic again. `state` always carries the whole snapshot.
+fn state_messages(
+    previous: Option<&TimerSnapshot>,
+    snapshot: &TimerSnapshot,
+) -> Vec<(&'static str, String)> {
+    let mut messages = Vec::new();
+    if previous.map(|p| p.phase) != Some(snapshot.phase) {
+        messages.push(("phase", snapshot.phase.to_string()));
+    }
+    if previous.map(|p| p.running) != Some(snapshot.running) {
+        messages.push(("running", snapshot.running.to_string()));
+    }
+    if previous.map(|p| &p.subject) != Some(&snapshot.subject) {
+        messages.push(("subject", snapshot.subject.clone().unwrap_or_default()));
+    }
+    if let Some(remaining) = snapshot.remaining {
+        messages.push(("remaining", remaining.to_string()));
+    }
+    let state = serde_json::to_string(snapshot).expect("snapshot should serialize");
+    messages.push(("state", state));
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(running: bool, remaining: Option<Seconds>) -> TimerSnapshot {
+        TimerSnapshot {
+            phase: Phase::Work,
+            running,
+            remaining,
+            subject: Some("maths".into()),
+        }
+    }
+
+    #[test]
+    fn first_snapshot_publishes_every_topic() {
+        let topics: Vec<&str> = state_messages(None, &snapshot(true, Some(90)))
+            .into_iter()
+            .map(|(topic, _)| topic)
+            .collect();
+        assert_eq!(
+            topics,
+            ["phase", "running", "subject", "remaining", "state"]
+        );
+    }
+
+    #[test]
+    fn only_changed_fields_are_published_again() {
+        let messages = state_messages(Some(&snapshot(true, Some(90))), &snapshot(false, None));
+        assert_eq!(messages[0], ("running", "false".to_string()));
+        assert_eq!(messages[1].0, "state");
+        assert_eq!(messages.len(), 2);
+    }
+
+    #[test]
+    fn state_payload_is_the_whole_snapshot() {
+        let messages = state_messages(None, &snapshot(true, Some(90)));
+        let state: serde_json::Value = serde_json::from_str(&messages[4].1).unwrap();
+        assert_eq!(state["phase"], "work");
+        assert_eq!(state["remaining"], 90);
+        assert_eq!(state["subject"], "maths");
+    }
+
+    #[test]
+    fn topics_sit_below_the_prefix() {
+        assert_eq!(topic("home/rtuion", "phase"), "home/rtuion/phase");
+    }
+}
@@ -0,0 +1,228 @@
+use std::{fs, path::PathBuf};
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::{
+    error::Result,
+    settings::OrgClockSettings,
+    stats::session::{Phase, Session},
+};
+
+const ORG_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %a %H:%M";
+
+#[derive(Debug, Clone)]
+pub struct OrgClockWriter {
+    file: PathBuf,
+    heading: String,
+    tags: Vec<String>,
+}
+impl OrgClockWriter {
+    pub fn new(settings: &OrgClockSettings) -> Option<OrgClockWriter> {
+        if !settings.enabled {
+            return None;
+        }
+        let file = settings.file.as_ref().filter(|file| !file.is_empty())?;
+        Some(OrgClockWriter {
+            file: PathBuf::from(file),
+            heading: settings.heading.clone(),
+            tags: settings.tags.clone(),
+        })
+    }
+    pub fn send(&self, session: &Session) {
+        if session.phase() != Phase::Work {
+            return;
+        }
+        // the file is tiny and written once per phase, so doing it inline keeps writes ordered
+        let _ = self.append(session);
+    }
+    fn append(&self, session: &Session) -> Result<()> {
+        let contents = fs::read_to_string(&self.file).unwrap_or_default();
+        let clock = clock_line(session.start(), session.end());
+        let contents = insert_clock(
+            &contents,
+            &self.heading,
+            session.subject_name(),
+            &self.tags,
+            &clock,
+        );
+        if let Some(parent) = self.file.parent() {
+            if !parent.as_os_str().is_empty() && !parent.exists() {
+                fs::create_dir_all(parent)?
+            }
+        }
+        fs::write(&self.file, contents)?;
+        Ok(())
+    }
+}
+pub fn clock_line(start: DateTime<FixedOffset>, end: DateTime<FixedOffset>) -> String {
+    let minutes = (end - start).num_minutes().max(0);
+    format!(
+        "CLOCK: [{}]--[{}] => {:>2}:{:02}",
+        start.format(ORG_TIMESTAMP_FORMAT),
+        end.format(ORG_TIMESTAMP_FORMAT),
+        minutes / 60,
+        minutes % 60
+    )
+}
+/// Puts `clock` into the LOGBOOK of `subject`'s heading below `heading`, or of `heading`
+/// itself when there is no subject. Missing headings are created, tagged with `tags`.
+pub fn insert_clock(
+    contents: &str,
+    heading: &str,
+    subject: Option<&str>,
+    tags: &[String],
+    clock: &str,
+) -> String {
+    let mut lines: Vec<String> = contents.lines().map(|line| line.to_string()).collect();
+    let (parent, parent_level) = match find_heading(&lines, heading, 0..lines.len(), None) {
+        Some(found) => found,
+        None => {
+            let tags = if subject.is_none() { tags } else { &[] };
+            lines.push(heading_line(1, heading, tags));
+            (lines.len() - 1, 1)
+        }
+    };
+    let target = match subject {
+        Some(subject) => {
+            let subtree_end = subtree_end(&lines, parent, parent_level);
+            match find_heading(
+                &lines,
+                subject,
+                parent + 1..subtree_end,
+                Some(parent_level + 1),
+            ) {
+                Some((index, _)) => index,
+                None => {
+                    lines.insert(subtree_end, heading_line(parent_level + 1, subject, tags));
+                    subtree_end
+                }
+            }
+        }
+        None => parent,
+    };
+    let mut index = target + 1;
+    while lines.get(index).is_some_and(|line| is_planning(line)) {
+        index += 1;
+    }
+    if lines
+        .get(index)
+        .is_some_and(|line| line.trim() == ":PROPERTIES:")
+    {
+        while lines.get(index).is_some_and(|line| line.trim() != ":END:") {
+            index += 1;
+        }
+        index += 1;
+    }
+    if lines
+        .get(index)
+        .is_some_and(|line| line.trim() == ":LOGBOOK:")
+    {
+        lines.insert(index + 1, clock.to_string());
+    } else {
+        lines.splice(
+            index..index,
+            [
+                ":LOGBOOK:".to_string(),
+                clock.to_string(),
+                ":END:".to_string(),
+            ],
+        );
+    }
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    contents
+}
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '*').count();
+    (level > 0 && line[level..].starts_with(' ')).then_some(level)
+}
+fn heading_title(line: &str) -> &str {
+    let title = line.trim_start_matches('*').trim();
+    match title.rsplit_once(' ') {
+        Some((rest, tags)) if tags.len() > 1 && tags.starts_with(':') && tags.ends_with(':') => {
+            rest.trim_end()
+        }
+        _ => title,
+    }
+}
+fn heading_line(level: usize, title: &str, tags: &[String]) -> String {
+    let stars = "*".repeat(level);
+    if tags.is_empty() {
+        format!("{stars} {title}")
+    } else {
+        format!("{stars} {title} :{}:", tags.join(":"))
+    }
+}
+fn find_heading(
+    lines: &[String],
+    title: &str,
+    range: std::ops::Range<usize>,
+    level: Option<usize>,
+) -> Option<(usize, usize)> {
+    range.into_iter().find_map(|index| {
+        let found = heading_level(&lines[index])?;
+        (level.is_none_or(|level| level == found) && heading_title(&lines[index]) == title)
+            .then_some((index, found))
+    })
+}
+fn subtree_end(lines: &[String], heading: usize, level: usize) -> usize {
+    (heading + 1..lines.len())
+        .find(|index| heading_level(&lines[*index]).is_some_and(|found| found <= level))
+        .unwrap_or(lines.len())
+}
+fn is_planning(line: &str) -> bool {
+    let line = line.trim_start();
+    ["SCHEDULED:", "DEADLINE:", "CLOSED:"]
+        .iter()
+        .any(|keyword| line.starts_with(keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock() -> String {
+        let start = DateTime::parse_from_rfc3339("2024-01-02T10:00:00+01:00").unwrap();
+        let end = DateTime::parse_from_rfc3339("2024-01-02T10:25:00+01:00").unwrap();
+        clock_line(start, end)
+    }
+    fn tags() -> Vec<String> {
+        vec!["billable".to_string()]
+    }
+    const CONTENTS: &str =
+        "* Notes\n* Work :job:\n** Reading\n:LOGBOOK:\nCLOCK: old\n:END:\n* Later\n";
+
+    #[test]
+    fn clock_line_has_the_org_format() {
+        assert_eq!(
+            clock(),
+            "CLOCK: [2024-01-02 Tue 10:00]--[2024-01-02 Tue 10:25] =>  0:25"
+        );
+    }
+    #[test]
+    fn clocks_go_first_in_an_existing_logbook() {
+        let clock = clock();
+        let updated = insert_clock(CONTENTS, "Work", Some("Reading"), &tags(), &clock);
+        assert_eq!(
+            updated,
+            format!("* Notes\n* Work :job:\n** Reading\n:LOGBOOK:\n{clock}\nCLOCK: old\n:END:\n* Later\n")
+        );
+    }
+    #[test]
+    fn missing_tasks_get_a_heading_below_the_subject() {
+        let clock = clock();
+        let updated = insert_clock(CONTENTS, "Work", Some("Rust"), &tags(), &clock);
+        assert!(updated.contains(&format!(
+            "** Rust :billable:\n:LOGBOOK:\n{clock}\n:END:\n* Later"
+        )));
+    }
+    #[test]
+    fn empty_files_get_the_subject_heading() {
+        let clock = clock();
+        let created = insert_clock("", "Work", None, &tags(), &clock);
+        assert_eq!(
+            created,
+            format!("* Work :billable:\n:LOGBOOK:\n{clock}\n:END:\n")
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use serde::Serialize;
+use strum_macros::Display;
+
+use crate::stats::{
+    pixela::subjects::{Minutes, Seconds, Subject},
+    session::{Phase, Plan, Session},
+};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, Hash, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum SessionEventKind {
+    WorkStart,
+    BreakStart,
+    PhaseEnd,
+    SessionFinish,
+    PixelLogged,
+    PixelsSynced,
+}
+/// Snapshot of the timer sent to every integration when something happens.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEvent {
+    pub kind: SessionEventKind,
+    pub timestamp: String,
+    pub phase: Phase,
+    pub plan: Plan,
+    pub phase_duration: Seconds,
+    pub phase_elapsed: Seconds,
+    pub total_elapsed: Seconds,
+    pub iteration: u8,
+    pub total_iterations: u8,
+    pub subject_id: Option<String>,
+    pub subject_name: Option<String>,
+    pub minutes: Option<Minutes>,
+    pub pixels: Option<usize>,
+    pub session: Option<Session>,
+}
+impl SessionEvent {
+    pub fn new(kind: SessionEventKind, phase: Phase, plan: Plan) -> SessionEvent {
+        SessionEvent {
+            kind,
+            timestamp: chrono::Local::now().to_rfc3339(),
+            phase,
+            plan,
+            phase_duration: 0,
+            phase_elapsed: 0,
+            total_elapsed: 0,
+            iteration: 0,
+            total_iterations: 0,
+            subject_id: None,
+            subject_name: None,
+            minutes: None,
+            pixels: None,
+            session: None,
+        }
+    }
+    pub fn set_subject(&mut self, subject: Option<&Subject>) {
+        if let Some(subject) = subject.filter(|subject| !subject.is_dummy()) {
+            self.subject_id = Some(subject.id().to_string());
+            self.subject_name = Some(subject.graph_name().to_string());
+        }
+    }
+    pub fn env_vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            ("RTUION_EVENT", self.kind.to_string()),
+            ("RTUION_TIMESTAMP", self.timestamp.clone()),
+            ("RTUION_PHASE", self.phase.to_string()),
+            ("RTUION_PLAN", self.plan.to_string()),
+            ("RTUION_PHASE_DURATION", self.phase_duration.to_string()),
+            ("RTUION_PHASE_ELAPSED", self.phase_elapsed.to_string()),
+            ("RTUION_TOTAL_ELAPSED", self.total_elapsed.to_string()),
+            ("RTUION_ITERATION", self.iteration.to_string()),
+            ("RTUION_TOTAL_ITERATIONS", self.total_iterations.to_string()),
+        ];
+        if let Some(id) = &self.subject_id {
+            vars.push(("RTUION_SUBJECT_ID", id.clone()));
+        }
+        if let Some(name) = &self.subject_name {
+            vars.push(("RTUION_SUBJECT_NAME", name.clone()));
+        }
+        if let Some(minutes) = self.minutes {
+            vars.push(("RTUION_MINUTES", minutes.to_string()));
+        }
+        if let Some(pixels) = self.pixels {
+            vars.push(("RTUION_PIXELS", pixels.to_string()));
+        }
+        vars
+    }
+}
@@ -0,0 +1,135 @@
+use std::{process::Stdio, time::Duration};
+
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+use tokio::{io::AsyncWriteExt, process::Command};
+
+use crate::{
+    error::{IntegrationError, Result},
+    settings::TimewarriorSettings,
+    stats::session::{Phase, Session},
+};
+
+const TIMEW_DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TimewInterval {
+    start: String,
+    end: String,
+    tags: Vec<String>,
+}
+#[derive(Debug, Clone)]
+pub struct TimewarriorExporter {
+    command: String,
+    tags: Vec<String>,
+}
+impl TimewarriorExporter {
+    pub fn new(settings: &TimewarriorSettings) -> Option<TimewarriorExporter> {
+        if !settings.enabled {
+            return None;
+        }
+        Some(TimewarriorExporter {
+            command: settings.command.clone(),
+            tags: settings.tags.clone(),
+        })
+    }
+    pub fn send(&self, session: &Session) {
+        if session.phase() != Phase::Work {
+            return;
+        }
+        let interval = self.interval(session);
+        let command = self.command.clone();
+        tokio::spawn(async move {
+            let _ = TimewarriorExporter::import(command, vec![interval]).await;
+        });
+    }
+    pub fn interval(&self, session: &Session) -> TimewInterval {
+        let mut tags: Vec<String> = session
+            .subject_name()
+            .map(|subject| subject.to_string())
+            .into_iter()
+            .chain(self.tags.iter().cloned())
+            .collect();
+        tags.push(session.plan().to_string());
+        TimewInterval {
+            start: timew_date(session.start()),
+            end: timew_date(session.end()),
+            tags,
+        }
+    }
+    async fn import(command: String, intervals: Vec<TimewInterval>) -> Result<()> {
+        let payload = serde_json::to_vec(&intervals)?;
+        let mut child = Command::new(&command)
+            .arg("import")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(&payload).await?;
+        }
+        match tokio::time::timeout(Duration::from_secs(10), child.wait()).await {
+            Ok(Ok(status)) if status.success() => Ok(()),
+            Ok(_) => Err(IntegrationError::ExportFailed(command).into()),
+            Err(_) => {
+                child.kill().await?;
+                Err(IntegrationError::ExportFailed(command).into())
+            }
+        }
+    }
+}
+fn timew_date(date: DateTime<FixedOffset>) -> String {
+    date.to_utc().format(TIMEW_DATE_FORMAT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+
+    fn session(subject: Option<&str>) -> Session {
+        let start = Local.with_ymd_and_hms(2024, 3, 4, 10, 0, 0).unwrap();
+        let end = Local.with_ymd_and_hms(2024, 3, 4, 10, 25, 0).unwrap();
+        let subject = subject.map(str::to_string);
+        Session::imported(start, end, subject.clone(), subject, "test")
+    }
+
+    fn exporter() -> TimewarriorExporter {
+        TimewarriorExporter::new(&TimewarriorSettings {
+            enabled: true,
+            tags: vec!["rtuion".into(), "focus".into()],
+            ..TimewarriorSettings::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn tags_are_subject_then_configured_tags_then_plan() {
+        let interval = exporter().interval(&session(Some("maths")));
+        assert_eq!(interval.tags, ["maths", "rtuion", "focus", "pomodoro"]);
+    }
+
+    #[test]
+    fn sessions_without_subject_only_get_the_other_tags() {
+        let interval = exporter().interval(&session(None));
+        assert_eq!(interval.tags, ["rtuion", "focus", "pomodoro"]);
+    }
+
+    #[test]
+    fn dates_are_written_in_utc() {
+        let interval = exporter().interval(&session(None));
+        let start = Local
+            .with_ymd_and_hms(2024, 3, 4, 10, 0, 0)
+            .unwrap()
+            .to_utc();
+        assert_eq!(interval.start, start.format(TIMEW_DATE_FORMAT).to_string());
+        assert!(interval.end.ends_with('Z'));
+    }
+
+    #[test]
+    fn disabled_exporter_is_not_created() {
+        assert!(TimewarriorExporter::new(&TimewarriorSettings::default()).is_none());
+    }
+}
@@ -0,0 +1,339 @@
+use std::{fs, path::PathBuf, sync::Arc, time::Duration};
+
+use chrono::Local;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::Mutex;
+
+use crate::{
+    app::Event,
+    error::{Error, IntegrationError, Result},
+    settings::{data_dir, WebhookSettings},
+};
+
+use super::session_event::SessionEvent;
+
+const SIGNATURE_HEADER: &str = "X-Rtuion-Signature";
+const EVENT_HEADER: &str = "X-Rtuion-Event";
+const MAX_QUEUED_DELIVERIES: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QueuedDelivery {
+    url: String,
+    event: String,
+    body: String,
+}
+#[derive(Debug, Clone)]
+pub struct WebhookSink {
+    client: Client,
+    urls: Vec<String>,
+    secret: Option<String>,
+    max_retries: u32,
+    queue_path: Option<PathBuf>,
+    // serializes access to the queue file between spawned deliveries
+    queue_lock: Arc<Mutex<()>>,
+    /// deliveries run on their own, this is where their errors go
+    event_tx: Option<tokio::sync::mpsc::Sender<Event>>,
+}
+impl WebhookSink {
+    pub fn new(settings: &WebhookSettings, client: Client) -> WebhookSink {
+        WebhookSink {
+            client,
+            urls: settings.urls.clone(),
+            secret: settings.secret.clone().filter(|secret| !secret.is_empty()),
+            max_retries: settings.max_retries,
+            queue_path: WebhookSink::default_queue_path().ok(),
+            queue_lock: Arc::new(Mutex::new(())),
+            event_tx: None,
+        }
+    }
+    pub fn set_event_tx(&mut self, event_tx: tokio::sync::mpsc::Sender<Event>) {
+        self.event_tx = Some(event_tx);
+    }
+    fn report(&self, err: Error) {
+        if let Some(event_tx) = &self.event_tx {
+            let _ = event_tx.try_send(Event::BackgroundError(err));
+        }
+    }
+    pub fn with_queue_path(mut self, queue_path: PathBuf) -> WebhookSink {
+        self.queue_path = Some(queue_path);
+        self
+    }
+    fn default_queue_path() -> Result<PathBuf> {
+        Ok(data_dir()?.join("webhooks").join("queue.json"))
+    }
+    pub fn send(&self, event: &SessionEvent) {
+        if self.urls.is_empty() {
+            return;
+        }
+        let body = serde_json::to_string(event).expect("event should serialize");
+        let deliveries: Vec<QueuedDelivery> = self
+            .urls
+            .iter()
+            .map(|url| QueuedDelivery {
+                url: url.clone(),
+                event: event.kind.to_string(),
+                body: body.clone(),
+            })
+            .collect();
+        let sink = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = sink.deliver_all(deliveries).await {
+                sink.report(err);
+            }
+        });
+    }
+    /// Delivers fresh events and, once the receiver answers again, whatever is left in the
+    /// failure queue from earlier runs.
+    pub async fn deliver_all(&self, deliveries: Vec<QueuedDelivery>) -> Result<()> {
+        let mut failed = Vec::new();
+        let mut any_delivered = false;
+        for delivery in deliveries {
+            match self.deliver_with_retries(&delivery).await {
+                Ok(()) => any_delivered = true,
+                Err(err) if WebhookSink::is_permanent(&err) => {}
+                Err(_) => failed.push(delivery),
+            }
+        }
+        if !failed.is_empty() {
+            self.enqueue(failed).await?;
+        } else if any_delivered {
+            self.flush_queue().await?;
+        }
+        Ok(())
+    }
+    pub async fn flush_queue(&self) -> Result<()> {
+        let _guard = self.queue_lock.lock().await;
+        let queued = self.load_queue()?;
+        if queued.is_empty() {
+            return Ok(());
+        }
+        let mut still_failing = Vec::new();
+        for delivery in queued {
+            match self.deliver(&delivery).await {
+                Ok(()) => {}
+                Err(err) if WebhookSink::is_permanent(&err) => {}
+                Err(_) => still_failing.push(delivery),
+            }
+        }
+        self.save_queue(&still_failing)
+    }
+    async fn enqueue(&self, mut deliveries: Vec<QueuedDelivery>) -> Result<()> {
+        let _guard = self.queue_lock.lock().await;
+        // an unreadable queue was moved aside, the new failures still get a fresh one
+        let mut queued = self.load_queue().unwrap_or_else(|err| {
+            self.report(err);
+            Vec::new()
+        });
+        queued.append(&mut deliveries);
+        if queued.len() > MAX_QUEUED_DELIVERIES {
+            queued.drain(..queued.len() - MAX_QUEUED_DELIVERIES);
+        }
+        self.save_queue(&queued)
+    }
+    pub fn load_queue(&self) -> Result<Vec<QueuedDelivery>> {
+        let Some(path) = &self.queue_path else {
+            return Ok(Vec::new());
+        };
+        let Ok(data) = fs::read_to_string(path) else {
+            return Ok(Vec::new());
+        };
+        match serde_json::from_str(&data) {
+            Ok(queued) => Ok(queued),
+            Err(_) => {
+                // the deliveries in it are kept for a look instead of being overwritten
+                let stamp = Local::now().format("%Y%m%d%H%M%S");
+                let aside = path.with_extension(format!("corrupt-{stamp}.json"));
+                fs::rename(path, &aside)?;
+                Err(IntegrationError::QueueUnreadable(aside.display().to_string()).into())
+            }
+        }
+    }
+    fn save_queue(&self, queued: &[QueuedDelivery]) -> Result<()> {
+        let Some(path) = &self.queue_path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)?
+            }
+        }
+        fs::write(path, serde_json::to_string(queued)?)?;
+        Ok(())
+    }
+    async fn deliver_with_retries(&self, delivery: &QueuedDelivery) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            match self.deliver(delivery).await {
+                Ok(()) => return Ok(()),
+                Err(err) if WebhookSink::is_permanent(&err) || attempt >= self.max_retries => {
+                    return Err(err)
+                }
+                Err(_) => {
+                    tokio::time::sleep(Duration::from_millis(500 * 2u64.pow(attempt))).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+    async fn deliver(&self, delivery: &QueuedDelivery) -> Result<()> {
+        let mut request = self
+            .client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &delivery.event)
+            .timeout(Duration::from_secs(10))
+            .body(delivery.body.clone());
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, &delivery.body));
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(IntegrationError::WebhookRejected(delivery.url.clone(), status).into())
+        }
+    }
+    fn is_permanent(err: &Error) -> bool {
+        // client errors will not go away by sending the same payload again
+        match err {
+            Error::IntegrationError(IntegrationError::WebhookRejected(_, status)) => {
+                status.is_client_error()
+                    && *status != StatusCode::REQUEST_TIMEOUT
+                    && *status != StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => false,
+        }
+    }
+}
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any size");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+    use crate::{
+        integrations::session_event::SessionEventKind,
+        stats::session::{Phase, Plan},
+    };
+
+    async fn receive_one(listener: TcpListener, status_line: &str) -> String {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0; 4096];
+        loop {
+            let read = socket.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..read]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        line.to_lowercase()
+                            .strip_prefix("content-length: ")
+                            .map(|l| l.parse::<usize>().unwrap())
+                    })
+                    .unwrap_or(0);
+                if body.len() >= length {
+                    break;
+                }
+            }
+        }
+        let response = format!("{status_line}\r\ncontent-length: 0\r\n\r\n");
+        socket.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request).to_string()
+    }
+    fn sink(urls: Vec<String>, queue_path: PathBuf) -> WebhookSink {
+        let settings = WebhookSettings {
+            urls,
+            secret: Some("hunter2".into()),
+            max_retries: 0,
+        };
+        WebhookSink::new(&settings, Client::new()).with_queue_path(queue_path)
+    }
+    fn deliveries(sink: &WebhookSink) -> Vec<QueuedDelivery> {
+        let event = SessionEvent::new(SessionEventKind::WorkStart, Phase::Work, Plan::Pomodoro);
+        let body = serde_json::to_string(&event).unwrap();
+        sink.urls
+            .iter()
+            .map(|url| QueuedDelivery {
+                url: url.clone(),
+                event: event.kind.to_string(),
+                body: body.clone(),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn posts_signed_payload() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let receiver = tokio::spawn(receive_one(listener, "HTTP/1.1 200 OK"));
+        let dir = tempfile::tempdir().unwrap();
+        let sink = sink(vec![url], dir.path().join("queue.json"));
+        sink.deliver_all(deliveries(&sink)).await.unwrap();
+
+        let request = receiver.await.unwrap();
+        let body = request.split_once("\r\n\r\n").unwrap().1;
+        assert!(body.contains("\"kind\":\"work_start\""));
+        assert!(request
+            .to_lowercase()
+            .contains("x-rtuion-event: work_start"));
+        let signature = format!("x-rtuion-signature: {}", sign("hunter2", body));
+        assert!(request.to_lowercase().contains(&signature));
+    }
+
+    #[tokio::test]
+    async fn failed_deliveries_are_queued_and_flushed() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let sink = sink(vec![url], dir.path().join("queue.json"));
+
+        let receiver = tokio::spawn(receive_one(listener, "HTTP/1.1 503 Service Unavailable"));
+        sink.deliver_all(deliveries(&sink)).await.unwrap();
+        receiver.await.unwrap();
+        assert_eq!(sink.load_queue().unwrap().len(), 1);
+
+        let listener = TcpListener::bind(
+            sink.urls[0]
+                .trim_start_matches("http://")
+                .trim_end_matches("/hook"),
+        )
+        .await
+        .unwrap();
+        let receiver = tokio::spawn(receive_one(listener, "HTTP/1.1 200 OK"));
+        sink.flush_queue().await.unwrap();
+        receiver.await.unwrap();
+        assert!(sink.load_queue().unwrap().is_empty());
+    }
+
+    #[test]
+    fn unreadable_queue_is_moved_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = dir.path().join("queue.json");
+        fs::write(&queue, "[{\"url\": ").unwrap();
+        let sink = sink(vec![], queue.clone());
+
+        assert!(sink.load_queue().is_err());
+        assert!(!queue.exists());
+        let aside: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        assert_eq!(aside, ["[{\"url\": "]);
+        assert!(sink.load_queue().unwrap().is_empty());
+    }
+}
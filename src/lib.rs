@@ -1,10 +1,12 @@
 pub mod app;
 pub mod error;
 pub mod handlers;
+pub mod integrations;
 pub mod popup;
 pub mod romodoro;
 pub mod settings;
 pub mod stats;
+pub mod tasks;
 pub mod timers;
 pub mod ui;
 pub mod utils;
@@ -15,3 +17,18 @@ pub const WORK_TIME_INCR: i64 = 900;
 pub const BREAK_TIME_INCR: i64 = 60;
 pub const MIN_HOUR_INCREMENT: usize = 60;
 pub const MIN_MINS_INCREMENT: usize = 15;
+pub const DEFAULT_HOOK_TIMEOUT: u64 = 10;
+pub const TASKWARRIOR_TIMEOUT_SECS: u64 = 10;
+pub const DEFAULT_WEBHOOK_RETRIES: u32 = 3;
+pub const DEFAULT_MQTT_PORT: u16 = 1883;
+pub const DEFAULT_ACTIVITYWATCH_URL: &str = "http://localhost:5600";
+pub const DEFAULT_TASK_ESTIMATE: u8 = 1;
+/// times todo.txt is read again when it changed while writing a task back
+pub const TODO_TXT_WRITE_ATTEMPTS: usize = 3;
+pub const GRAPH_CACHE_MAX_AGE_MINUTES: i64 = 30;
+pub const DEFAULT_ARCHIVE_RETENTION_DAYS: u32 = 365;
+/// Pixela turns down about a quarter of the requests of non-supporters, those are tried again
+pub const PIXELA_MAX_ATTEMPTS: u32 = 6;
+pub const PIXELA_RETRY_BASE_MS: u64 = 250;
+pub const PIXELA_RETRY_MAX_MS: u64 = 4000;
+pub const PIXELA_CONCURRENT_REQUESTS: usize = 2;
@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::fs;
 use std::rc::Rc;
 
 use crossterm::terminal;
@@ -7,14 +8,23 @@ use rtuion::error::Result;
 use rtuion::popup::Popup;
 use rtuion::romodoro::*;
 use rtuion::settings::Settings;
+use rtuion::stats::history::ExportOptions;
+use rtuion::stats::import::ImportOptions;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("export") => return export_history(&args[1..]),
+        Some("import") => return import_history(&args[1..]),
+        _ => {}
+    }
     let (tx, rx) = tokio::sync::mpsc::channel(4);
     let (tx_events, rx_events) = tokio::sync::mpsc::channel(32);
     let (tx_commands, rx_commands) = tokio::sync::mpsc::channel(4);
     let settings_manager = Rc::new(RefCell::new(Settings::new()?));
     let mut pomodoro = Pomodoro::new(tx, rx_commands, tx_commands, settings_manager.clone());
+    pomodoro.set_event_tx(tx_events.clone());
 
     terminal::enable_raw_mode()?;
     let mut terminal = ratatui::init();
@@ -29,6 +39,9 @@ async fn main() -> Result<()> {
 
     let mut app = App::new(pomodoro, settings_manager, tx_events);
     app.set_popup_opt(pixela_failed_popup);
+    if app.get_settings_ref().borrow().sync_settings.dir.is_some() {
+        app.sync_devices(false);
+    }
     let app_result = app.run(&mut terminal, rx_events, rx).await; // mainloop
     terminal::disable_raw_mode()?;
 
@@ -36,3 +49,19 @@ async fn main() -> Result<()> {
     app.get_settings_ref().borrow().save_to_file()?;
     Ok(app_result?)
 }
+
+fn export_history(args: &[String]) -> Result<()> {
+    let options = ExportOptions::from_args(args)?;
+    let data = options.render()?;
+    match &options.output {
+        Some(path) => fs::write(path, data)?,
+        None => print!("{data}"),
+    }
+    Ok(())
+}
+
+fn import_history(args: &[String]) -> Result<()> {
+    let summary = ImportOptions::from_args(args)?.run()?;
+    println!("{summary}");
+    Ok(())
+}
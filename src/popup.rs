@@ -1,7 +1,10 @@
 
 use ratatui::widgets::ListState;
 
-use crate::{app::App, stats::pixel::Pixel};
+use crate::{
+    app::App,
+    stats::{pixel::Pixel, pixela::send_preview::SendPreview},
+};
 
 #[derive(Debug)]
 pub struct Popup {
@@ -10,24 +13,36 @@ pub struct Popup {
     pub scrollable: bool,
 }
 type Callback = Box<dyn FnOnce(&mut App)>;
+type PickCallback = Box<dyn FnOnce(&mut App, usize)>;
+type InputCallback = Box<dyn FnOnce(&mut App, String)>;
+type PreviewCallback = Box<dyn FnOnce(&mut App, SendPreview)>;
 
 impl std::fmt::Debug for PopupKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             PopupKind::YesNoPopup(_) => write!(f, "YesNoPopup(<callback>)"),
-            PopupKind::SendPixelsPopup(_, pixels, _) => {
-                write!(f, "SendPixelsPopup(<callback>, {:?})", pixels)
+            PopupKind::SendPixelsPopup(_, preview, _) => {
+                write!(f, "SendPixelsPopup(<callback>, {:?})", preview)
             }
             PopupKind::ListPopup(pixels, _) => write!(f, "ListPopup({:?})", pixels),
+            PopupKind::MessageListPopup(lines, _) => write!(f, "MessageListPopup({:?})", lines),
             PopupKind::ErrorPopup(e) => write!(f, "ErrorPopup({:?})", e),
+            PopupKind::PickerPopup(_, items, _) => {
+                write!(f, "PickerPopup(<callback>, {:?})", items)
+            }
+            PopupKind::InputPopup(_, input) => write!(f, "InputPopup(<callback>, {:?})", input),
         }
     }
 }
 pub enum PopupKind {
     YesNoPopup(Callback),
-    SendPixelsPopup(Callback, Vec<Pixel>, ListState),
+    SendPixelsPopup(PreviewCallback, SendPreview, ListState),
     ListPopup(Vec<Pixel>, ListState),
+    /// lines to read, any key closes it
+    MessageListPopup(Vec<String>, ListState),
     ErrorPopup(crate::error::Error),
+    PickerPopup(PickCallback, Vec<String>, ListState),
+    InputPopup(InputCallback, String),
 }
 impl Popup {
     pub fn new(message: String, kind: PopupKind) -> Self {
@@ -36,6 +51,9 @@ impl Popup {
             PopupKind::ErrorPopup(_) => false,
             PopupKind::SendPixelsPopup(_, _, _) => true,
             PopupKind::ListPopup(_, _) => true,
+            PopupKind::MessageListPopup(_, _) => true,
+            PopupKind::PickerPopup(_, _, _) => false,
+            PopupKind::InputPopup(_, _) => false,
         };
         Self {
             message,
@@ -50,10 +68,17 @@ impl Popup {
             scrollable: false,
         }
     }
-    pub fn pixel_confirm_list(message: String, callback: Callback, pixels: Vec<Pixel>) -> Self {
+    /// Rows can be left out with space before confirming, `callback` gets what's left.
+    pub fn pixel_confirm_list(
+        message: String,
+        callback: PreviewCallback,
+        preview: SendPreview,
+    ) -> Self {
+        let mut state = ListState::default();
+        state.select_first();
         Self {
             message,
-            kind: PopupKind::SendPixelsPopup(callback, pixels, ListState::default()),
+            kind: PopupKind::SendPixelsPopup(callback, preview, state),
             scrollable: true,
         }
     }
@@ -64,21 +89,47 @@ impl Popup {
             scrollable: true,
         }
     }
+    pub fn message_list(message: String, lines: Vec<String>) -> Self {
+        Self {
+            message,
+            kind: PopupKind::MessageListPopup(lines, ListState::default()),
+            scrollable: true,
+        }
+    }
+    pub fn picker(message: String, items: Vec<String>, callback: PickCallback) -> Self {
+        let mut state = ListState::default();
+        state.select_first();
+        Self {
+            message,
+            kind: PopupKind::PickerPopup(callback, items, state),
+            scrollable: false,
+        }
+    }
+    pub fn input(message: String, initial: String, callback: InputCallback) -> Self {
+        Self {
+            message,
+            kind: PopupKind::InputPopup(callback, initial),
+            scrollable: false,
+        }
+    }
+    /// Typing goes into the popup, so global keys have to stay quiet.
+    pub fn takes_input(&self) -> bool {
+        matches!(self.kind, PopupKind::InputPopup(_, _))
+    }
     pub fn scroll_down(&mut self, viewport_height: usize) {
         match &mut self.kind {
-            PopupKind::ListPopup(pixels, state) | PopupKind::SendPixelsPopup(_, pixels, state) => {
-                let total_items = pixels.len();
-                let current_offset = state.offset();
-                if current_offset == (total_items.saturating_sub(viewport_height)) {
-                    return;
-                }
-                let new_offset =
-                    (current_offset + 1).min(total_items.saturating_sub(viewport_height));
-
-                *state.offset_mut() = new_offset;
-
-                let last_visible = new_offset + viewport_height.saturating_sub(1);
-                state.select(Some(last_visible.min(total_items.saturating_sub(1))));
+            // the highlighted row is the one space toggles
+            PopupKind::SendPixelsPopup(_, preview, state) => {
+                let last = preview.rows().len().saturating_sub(1);
+                state.select(Some(
+                    state.selected().map_or(0, |index| (index + 1).min(last)),
+                ));
+            }
+            PopupKind::ListPopup(pixels, state) => {
+                scroll_list_down(state, pixels.len(), viewport_height)
+            }
+            PopupKind::MessageListPopup(lines, state) => {
+                scroll_list_down(state, lines.len(), viewport_height)
             }
             _ => {}
         }
@@ -86,19 +137,38 @@ impl Popup {
 
     pub fn scroll_up(&mut self, viewport_height: usize) {
         match &mut self.kind {
-            PopupKind::ListPopup(pixels, state) | PopupKind::SendPixelsPopup(_, pixels, state) => {
-                let current_offset = state.offset();
-                let new_offset = current_offset.saturating_sub(1);
-
-                *state.offset_mut() = new_offset;
-
-                let last_visible = new_offset + viewport_height.saturating_sub(1);
-                state.select(Some(last_visible.min(pixels.len().saturating_sub(1))));
+            PopupKind::SendPixelsPopup(_, _, state) => state.select_previous(),
+            PopupKind::ListPopup(pixels, state) => {
+                scroll_list_up(state, pixels.len(), viewport_height)
+            }
+            PopupKind::MessageListPopup(lines, state) => {
+                scroll_list_up(state, lines.len(), viewport_height)
             }
             _ => {}
         }
     }
 }
+fn scroll_list_down(state: &mut ListState, total_items: usize, viewport_height: usize) {
+    let current_offset = state.offset();
+    if current_offset == (total_items.saturating_sub(viewport_height)) {
+        return;
+    }
+    let new_offset = (current_offset + 1).min(total_items.saturating_sub(viewport_height));
+
+    *state.offset_mut() = new_offset;
+
+    let last_visible = new_offset + viewport_height.saturating_sub(1);
+    state.select(Some(last_visible.min(total_items.saturating_sub(1))));
+}
+fn scroll_list_up(state: &mut ListState, total_items: usize, viewport_height: usize) {
+    let current_offset = state.offset();
+    let new_offset = current_offset.saturating_sub(1);
+
+    *state.offset_mut() = new_offset;
+
+    let last_visible = new_offset + viewport_height.saturating_sub(1);
+    state.select(Some(last_visible.min(total_items.saturating_sub(1))));
+}
 impl From<crate::error::Error> for Popup {
     fn from(value: crate::error::Error) -> Self {
         Popup {
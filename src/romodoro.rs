@@ -1,17 +1,31 @@
 use std::{cell::RefCell, rc::Rc};
 
+use chrono::{DateTime, Local, NaiveDate};
 use tokio_util::sync::CancellationToken;
 
 use crate::{
     app::Event,
-    error::{Result, StatsError},
+    error::{Error, Result, SettingsError, StatsError},
+    integrations::{
+        mqtt::TimerSnapshot,
+        session_event::{SessionEvent, SessionEventKind},
+        Integrations,
+    },
     settings::{PomodoroSettings, Settings},
     stats::pixela::{
         pixela_client::PixelaClient,
         pixela_user::PixelaUser,
         subjects::{Progress, Seconds, Subject, TimeUnit},
     },
+    stats::{
+        analytics::LocalStats,
+        history::History,
+        session::{Phase, Session},
+        sync::{SyncFolder, SyncReport},
+    },
+    tasks::{task_list::TaskList, taskwarrior::Taskwarrior, todo_txt},
     timers::{
+        counters::CounterMode,
         helper_structs::{TimerCommand, TimerState},
         timer::Timer,
     },
@@ -26,6 +40,21 @@ pub struct Pomodoro {
     settings: Rc<RefCell<Settings>>,
     pixela_client: Option<PixelaClient>,
     duration_since_last_save: Seconds,
+    client: reqwest::Client,
+    integrations: Integrations,
+    phase_elapsed: Seconds,
+    phase_announced: bool,
+    phase_started_at: Option<DateTime<Local>>,
+    tasks: TaskList,
+    taskwarrior: Option<Taskwarrior>,
+    history: Vec<Session>,
+    /// the day they were computed for, cleared when the history changes
+    local_stats: Option<(NaiveDate, LocalStats)>,
+    /// reports what goes wrong in spawned work
+    event_tx: Option<tokio::sync::mpsc::Sender<Event>>,
+    /// what went wrong before there was anywhere to report it
+    startup_errors: Vec<Error>,
+    sync: Option<SyncFolder>,
 }
 impl Pomodoro {
     pub fn new(
@@ -37,6 +66,35 @@ impl Pomodoro {
         let mut timer = Timer::from(settings.borrow().timer_settings.clone());
         timer.set_config(settings.clone());
         timer.countdown_command_tx = Some(command_tx);
+        let client = reqwest::Client::new();
+        let integrations = Integrations::new(&settings.borrow(), client.clone());
+        let taskwarrior = Taskwarrior::new(&settings.borrow().taskwarrior_settings);
+        let sync = SyncFolder::from_settings(&settings.borrow());
+        let mut startup_errors = vec![];
+        let history = match History::load() {
+            Ok((history, 0)) => history,
+            Ok((history, skipped)) => {
+                startup_errors.push(
+                    SettingsError::LoadError(format!(
+                        "skipped {skipped} unreadable rows of history.jsonl"
+                    ))
+                    .into(),
+                );
+                history
+            }
+            Err(err) => {
+                startup_errors.push(err);
+                Vec::new()
+            }
+        };
+        let tasks = match TaskList::load() {
+            Ok(tasks) => tasks,
+            // an unreadable file was moved aside, new tasks go to a fresh one
+            Err(err) => {
+                startup_errors.push(err);
+                TaskList::load().unwrap_or_default()
+            }
+        };
 
         Pomodoro {
             timer,
@@ -45,8 +103,30 @@ impl Pomodoro {
             settings,
             pixela_client: None,
             duration_since_last_save: 0,
+            client,
+            integrations,
+            phase_elapsed: 0,
+            phase_announced: false,
+            phase_started_at: None,
+            tasks,
+            taskwarrior,
+            history,
+            local_stats: None,
+            event_tx: None,
+            startup_errors,
+            sync,
         }
     }
+    pub fn set_event_tx(&mut self, event_tx: tokio::sync::mpsc::Sender<Event>) {
+        self.integrations.set_event_tx(event_tx.clone());
+        if let Some(taskwarrior) = &mut self.taskwarrior {
+            taskwarrior.set_event_tx(event_tx.clone());
+        }
+        for err in self.startup_errors.drain(..) {
+            let _ = event_tx.try_send(Event::BackgroundError(err));
+        }
+        self.event_tx = Some(event_tx);
+    }
     pub fn get_current_subject(&self) -> Option<Subject> {
         if let Some(pixela) = &self.pixela_client {
             Some(pixela.get_current_subject()?)
@@ -59,15 +139,179 @@ impl Pomodoro {
         let command_rx = self.command_rx.take().expect("Timer initialized");
         self.timer.set_config(self.settings.clone());
         self.timer.run(sender, command_rx, cancel_token).await;
+        self.publish_state();
     }
     pub async fn cycle(&mut self) {
         if self.timer.get_running() {
             self.timer.stop().await;
         } else {
             self.timer.start().await;
+            self.announce_phase();
+        }
+        self.publish_state();
+    }
+    pub async fn next_phase(&mut self) {
+        self.end_phase();
+        self.timer.next_iteration().await;
+        if self.timer.get_running() {
+            self.announce_phase();
+        }
+        self.publish_state();
+    }
+    fn announce_phase(&mut self) {
+        if self.phase_announced {
+            return;
         }
+        self.phase_announced = true;
+        self.phase_started_at = Some(Local::now());
+        let kind = match self.timer.current_state() {
+            TimerState::Work(_) => SessionEventKind::WorkStart,
+            TimerState::Break(_) => SessionEventKind::BreakStart,
+        };
+        if let (SessionEventKind::WorkStart, Some(taskwarrior), Some(task)) =
+            (kind, &mut self.taskwarrior, self.tasks.active_task())
+        {
+            taskwarrior.start(task);
+        }
+        self.integrations.dispatch(self.session_event(kind));
+    }
+    fn end_phase(&mut self) {
+        let mut event = self.session_event(SessionEventKind::PhaseEnd);
+        event.session = self.finished_session();
+        if let Some(session) = &event.session {
+            self.report(History::append(session));
+            self.history.push(session.clone());
+            self.local_stats = None;
+            if let Some(sync) = self.sync.clone() {
+                let session = session.clone();
+                self.spawn_reported(move || sync.append_session(&session));
+            }
+        }
+        if event.phase == Phase::Work {
+            self.stop_active_task();
+            if let Some(session) = &event.session {
+                self.log_block(session);
+                if let Some(task) = self.tasks.complete_active_pomodoro().cloned() {
+                    if let Some(taskwarrior) = &self.taskwarrior {
+                        taskwarrior.record_pomodoro(&task, session.duration() / 60);
+                    }
+                    let settings = self.settings.borrow().todo_txt_settings.clone();
+                    self.spawn_reported(move || todo_txt::write_back(&settings, &task, false));
+                    let _ = self.tasks.save();
+                }
+            }
+        }
+        self.integrations.dispatch(event);
+        self.phase_elapsed = 0;
+        self.phase_announced = false;
+        self.phase_started_at = None;
+    }
+    /// Shows what went wrong in work done right here as a popup.
+    fn report(&self, result: Result<()>) {
+        if let (Err(err), Some(event_tx)) = (result, &self.event_tx) {
+            let _ = event_tx.try_send(Event::BackgroundError(err));
+        }
+    }
+    /// Runs file work off the timer, what goes wrong shows up as a popup.
+    fn spawn_reported(&self, work: impl FnOnce() -> Result<()> + Send + 'static) {
+        let event_tx = self.event_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            if let (Err(err), Some(event_tx)) = (work(), event_tx) {
+                let _ = event_tx.blocking_send(Event::BackgroundError(err));
+            }
+        });
+    }
+    /// Count graphs get a pixel of 1 for every finished work block.
+    fn log_block(&mut self, session: &Session) {
+        let Some(client) = self.pixela_client.as_mut() else {
+            return;
+        };
+        let Some(subject) = client.get_current_subject().filter(Subject::is_count) else {
+            return;
+        };
+        client.add_pixel(
+            session.start().with_timezone(&Local),
+            Some(subject),
+            Progress::Int(1),
+            session.task().map(String::from),
+        );
+        let saved = client.save_pixels();
+        self.report(saved);
+    }
+    fn stop_active_task(&mut self) {
+        if let Some(taskwarrior) = &mut self.taskwarrior {
+            taskwarrior.stop();
+        }
+    }
+    /// Moves Taskwarrior's started task along when the active task changes mid work phase.
+    pub fn active_task_changed(&mut self) {
+        let working = self.phase_announced && self.timer.in_work_state();
+        let Some(taskwarrior) = &mut self.taskwarrior else {
+            return;
+        };
+        match (working, self.tasks.active_task()) {
+            (true, Some(task)) => taskwarrior.start(task),
+            _ => taskwarrior.stop(),
+        }
+    }
+    fn finished_session(&self) -> Option<Session> {
+        let start = self.phase_started_at?;
+        if self.phase_elapsed == 0 {
+            return None;
+        }
+        let task = match self.timer.in_work_state() {
+            true => self.tasks.active_task().map(|task| task.title().to_string()),
+            false => None,
+        };
+        let session = Session::new(
+            start,
+            Local::now(),
+            self.phase_elapsed,
+            self.timer.current_state().into(),
+            self.timer.counter_mode().into(),
+            self.timer.iteration(),
+            self.get_current_subject().as_ref(),
+        );
+        Some(session.with_task(task))
+    }
+    fn session_event(&self, kind: SessionEventKind) -> SessionEvent {
+        let state = self.timer.current_state();
+        let mode = self.timer.counter_mode();
+        let mut event = SessionEvent::new(kind, state.into(), mode.into());
+        event.phase_duration = match (mode, state) {
+            (CounterMode::Countup, TimerState::Work(_)) => self.phase_elapsed,
+            _ => Timer::get_duration(&state),
+        };
+        event.phase_elapsed = self.phase_elapsed;
+        event.total_elapsed = self.timer.total_elapsed();
+        event.iteration = self.timer.iteration();
+        event.total_iterations = self.timer.total_iterations();
+        event.set_subject(self.get_current_subject().as_ref());
+        event
+    }
+    fn publish_state(&self) {
+        let state = self.timer.current_state();
+        let remaining = match (self.timer.counter_mode(), state) {
+            (CounterMode::Countup, TimerState::Work(_)) => None,
+            _ => Some(self.timer.time_left().max(0)),
+        };
+        self.integrations.publish_state(TimerSnapshot {
+            phase: state.into(),
+            running: self.timer.get_running(),
+            remaining,
+            subject: self
+                .get_current_subject()
+                .map(|subject| subject.graph_name().to_string()),
+        });
+    }
+    pub fn emit_pixels_synced(&self, pixels: usize) {
+        let mut event = self.session_event(SessionEventKind::PixelsSynced);
+        event.pixels = Some(pixels);
+        self.integrations.dispatch(event);
     }
     pub fn try_init_pixela_client(&mut self) -> Result<()> {
+        let sync = self.sync.clone();
+        let retention_days = self.settings.borrow().archive_settings.retention_days;
         let px_stats = &mut self.settings.borrow_mut().stats_setting;
         if px_stats.stats_on {
             if px_stats.pixela_username.is_none() {
@@ -87,7 +331,13 @@ impl Pomodoro {
             if !user.validate_not_empty() {
                 Err(StatsError::UserNotProvided().into())
             } else {
-                self.pixela_client = Some(PixelaClient::try_new(user)?);
+                let mut client = PixelaClient::try_new(user, self.client.clone())?;
+                client.set_sync_folder(sync);
+                client.set_unit_aliases(px_stats.unit_aliases.clone());
+                client.set_carry_over(px_stats.carry_to_next_day);
+                client.set_retention_days(retention_days);
+                client.set_aggregate_graph(px_stats.aggregate_graph.clone());
+                self.pixela_client = Some(client);
                 Ok(())
             }
         } else {
@@ -121,6 +371,14 @@ impl Pomodoro {
         self.timer.set_setting(setting).await
     }
     pub fn log_pixel_from_duration(&mut self) -> Result<()> {
+        // count graphs get their pixel when the block ends, see `log_block`
+        if self
+            .get_current_subject()
+            .is_some_and(|subject| subject.is_count())
+        {
+            self.duration_since_last_save = 0;
+            return Ok(());
+        }
         if self.duration_since_last_save < 60 {
             return Err(crate::error::Error::StatsError(
                 StatsError::QuantityIsNotBigEnough,
@@ -132,11 +390,19 @@ impl Pomodoro {
             let subject = client.get_current_subject();
             let duration =
                 Progress::new_minutes(TimeUnit::Seconds(self.duration_since_last_save + 1));
+            let minutes = duration.get_as_int();
+            let task = self
+                .tasks
+                .active_task()
+                .map(|task| task.title().to_string());
             if let Some(client) = self.pixela_client.as_mut() {
-                client.add_pixel(date, subject, duration);
+                client.add_pixel(date, subject, duration, task);
                 client.save_pixels()?;
             }
             self.duration_since_last_save = 0;
+            let mut event = self.session_event(SessionEventKind::PixelLogged);
+            event.minutes = Some(minutes);
+            self.integrations.dispatch(event);
             Ok(())
         } else {
             Err(crate::error::StatsError::StatsTrackingTurnedOff().into())
@@ -144,8 +410,22 @@ impl Pomodoro {
     }
     pub async fn handle_timer_tick(&mut self, time: Seconds) -> Result<()> {
         self.handle_logging(time)?;
+        let phase_over = time == -1;
+        let finish_event = (phase_over && self.timer.iteration() >= self.timer.total_iterations())
+            .then(|| self.session_event(SessionEventKind::SessionFinish));
+        if phase_over {
+            self.end_phase();
+        } else {
+            self.phase_elapsed += 1;
+        }
         self.timer.handle_timer_response(time).await;
         self.duration_since_last_save += 1;
+        match finish_event {
+            Some(event) => self.integrations.dispatch(event),
+            None if phase_over && self.timer.get_running() => self.announce_phase(),
+            None => {}
+        }
+        self.publish_state();
         Ok(())
     }
     fn handle_logging(&mut self, time: Seconds) -> Result<()> {
@@ -185,6 +465,52 @@ impl Pomodoro {
         if let Some(client) = &mut self.pixela_client {
             client.set_current_subject_index(current_subject_index);
         }
+        self.publish_state();
+    }
+    pub fn tasks(&self) -> &TaskList {
+        &self.tasks
+    }
+    pub fn tasks_mut(&mut self) -> &mut TaskList {
+        &mut self.tasks
+    }
+    pub fn history(&self) -> &[Session] {
+        &self.history
+    }
+    /// The dashboard's numbers, computed again only once the history or the day changed.
+    pub fn local_stats(&mut self) -> &LocalStats {
+        let today = Local::now().date_naive();
+        if self.local_stats.as_ref().map(|(day, _)| *day) != Some(today) {
+            self.local_stats = Some((today, LocalStats::compute(&self.history, today)));
+        }
+        &self.local_stats.as_ref().expect("computed above").1
+    }
+    /// Merges the pixels and sessions of the other machines sharing the sync folder.
+    pub fn sync_folder(&mut self) -> Result<SyncReport> {
+        let sync = self.sync.clone().ok_or(SettingsError::SyncDirNotSet)?;
+        let mut report = SyncReport::default();
+        if let Some(client) = self.pixela_client.as_mut() {
+            let merge = client.merge_synced_pixels()?;
+            report.pixels_added = merge.added;
+            report.pixels_removed = merge.removed;
+            report.conflicts = merge.conflicts;
+        }
+        let mut merge = sync.merge_sessions(&self.history)?;
+        report.sessions_added = merge.sessions.len();
+        report.skipped_rows = merge.skipped_rows;
+        report.conflicts.append(&mut merge.conflicts);
+        self.record_sessions(merge.sessions)?;
+        sync.write_sessions(&self.history)?;
+        Ok(report)
+    }
+    /// Adds sessions the timer didn't record, like ones pulled from Pixela.
+    pub fn record_sessions(&mut self, sessions: Vec<Session>) -> Result<()> {
+        History::append_all(&sessions)?;
+        self.history.extend(sessions);
+        self.local_stats = None;
+        Ok(())
+    }
+    pub fn taskwarrior(&self) -> Option<&Taskwarrior> {
+        self.taskwarrior.as_ref()
     }
     pub fn get_setting_ref(&self) -> Rc<RefCell<Settings>> {
         self.settings.clone()
@@ -197,7 +523,14 @@ impl Pomodoro {
         self.pixela_client = None;
     }
     pub async fn restart_timer(&mut self) {
+        if self.timer.in_work_state() {
+            self.stop_active_task();
+        }
         self.timer.restart().await;
         self.duration_since_last_save = 0;
+        self.phase_elapsed = 0;
+        self.phase_announced = false;
+        self.phase_started_at = None;
+        self.publish_state();
     }
 }
@@ -1,4 +1,4 @@
-use std::fs;
+use std::{collections::HashMap, fs, path::PathBuf};
 
 use crate::error::{Error, Result, SettingsError, StatsError};
 use crate::stats::pixela::pixela_client::PixelaClient;
@@ -28,6 +28,26 @@ pub struct Settings {
     pub ui_settings: UISettings,
     pub timer_settings: TimerSettings,
     pub stats_setting: StatsSettings,
+    #[serde(default)]
+    pub hook_settings: HookSettings,
+    #[serde(default)]
+    pub webhook_settings: WebhookSettings,
+    #[serde(default)]
+    pub mqtt_settings: MqttSettings,
+    #[serde(default)]
+    pub activitywatch_settings: ActivityWatchSettings,
+    #[serde(default)]
+    pub timewarrior_settings: TimewarriorSettings,
+    #[serde(default)]
+    pub org_clock_settings: OrgClockSettings,
+    #[serde(default)]
+    pub todo_txt_settings: TodoTxtSettings,
+    #[serde(default)]
+    pub taskwarrior_settings: TaskwarriorSettings,
+    #[serde(default)]
+    pub archive_settings: ArchiveSettings,
+    #[serde(default)]
+    pub sync_settings: SyncSettings,
 }
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct UISettings {
@@ -42,6 +62,97 @@ pub struct TimerSettings {
     pub iterations: u8,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookSettings {
+    pub on_work_start: Option<String>,
+    pub on_break_start: Option<String>,
+    pub on_phase_end: Option<String>,
+    pub on_session_finish: Option<String>,
+    pub on_pixel_logged: Option<String>,
+    pub on_pixels_synced: Option<String>,
+    pub timeout_secs: u64,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    pub urls: Vec<String>,
+    pub secret: Option<String>,
+    pub max_retries: u32,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub topic_prefix: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub retain: bool,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActivityWatchSettings {
+    pub enabled: bool,
+    pub url: String,
+    pub bucket: Option<String>,
+    pub include_breaks: bool,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimewarriorSettings {
+    pub enabled: bool,
+    pub command: String,
+    pub tags: Vec<String>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrgClockSettings {
+    pub enabled: bool,
+    pub file: Option<String>,
+    pub heading: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TodoTxtSettings {
+    pub path: Option<String>,
+    /// +project -> Pixela graph id
+    pub projects: HashMap<String, String>,
+    pub pomodoro_tag: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaskwarriorSettings {
+    pub enabled: bool,
+    pub command: String,
+    pub follow_phases: bool,
+    pub annotate: bool,
+    pub pomodoro_uda: Option<String>,
+    /// project -> Pixela graph id
+    pub projects: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveSettings {
+    /// uploads older than this are pruned from the archive, 0 keeps them forever
+    pub retention_days: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SyncSettings {
+    /// a folder shared between machines, like a Syncthing or Nextcloud one
+    pub dir: Option<String>,
+    /// the name this machine's files get in `dir`, the hostname when not set and a
+    /// generated one kept in `device_id` when there is none
+    pub device: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub enum PomodoroSettings {
     WorkTime(i64),
@@ -54,6 +165,16 @@ pub struct StatsSettings {
     pub stats_on: bool,
     pub pixela_username: Option<String>,
     pub pixela_token: Option<String>,
+    /// graph units of your own mapped to minutes, hours or count, e.g. `commits = "count"`
+    #[serde(default)]
+    pub unit_aliases: HashMap<String, String>,
+    /// minutes an int hour graph can't take yet go on to the next day instead of waiting
+    /// for more on the same one
+    #[serde(default)]
+    pub carry_to_next_day: bool,
+    /// id of a graph every upload is also added to, like an "all study" graph
+    #[serde(default)]
+    pub aggregate_graph: Option<String>,
 }
 impl StatsSettings {
     pub fn init_stats(&mut self) {
@@ -77,13 +198,18 @@ impl StatsSettings {
     }
 }
 
+/// The directory the config and everything rtuion stores live in.
+pub fn data_dir() -> Result<PathBuf> {
+    let dirs =
+        ProjectDirs::from("rtuion", "rtuion", "rtuion").ok_or(SettingsError::HomeDirNotFound)?;
+    Ok(dirs.config_dir().to_path_buf())
+}
+
 impl Settings {
     pub fn save_to_file(&self) -> Result<()> {
-        let path = ProjectDirs::from("rtuion", "rtuion", "rtuion")
-            .ok_or(SettingsError::HomeDirNotFound)?;
-        let path = path.config_dir();
+        let path = data_dir()?;
         if !path.exists() {
-            fs::create_dir_all(path)?
+            fs::create_dir_all(&path)?
         }
         let toml_cfg: String =
             toml::to_string(&self).expect("Settings should be instantiated correctly");
@@ -91,9 +217,7 @@ impl Settings {
         Ok(())
     }
     pub fn new() -> Result<Settings> {
-        let path = ProjectDirs::from("rtuion", "rtuion", "rtuion")
-            .ok_or(SettingsError::HomeDirNotFound)?;
-        let path = path.config_dir();
+        let path = data_dir()?;
 
         Ok(match &fs::read_to_string(path.join("config.toml")) {
             Ok(data) => toml::from_str(data).unwrap_or_default(),
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use super::{
+    pixela::subjects::Seconds,
+    session::{Phase, Session},
+};
+
+/// Averages, records and the subject breakdown look at this many days.
+pub const WINDOW_DAYS: i64 = 30;
+const NO_SUBJECT: &str = "No subject";
+
+/// Totals computed from the local session history, no Pixela needed.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LocalStats {
+    pub today: Seconds,
+    pub week: Seconds,
+    pub month: Seconds,
+    pub all_time: Seconds,
+    /// work time per subject in the window, largest first
+    pub subjects: Vec<(String, Seconds)>,
+    /// every day of the window, oldest first, zero when nothing was done
+    pub days: Vec<(NaiveDate, Seconds)>,
+    pub sessions: usize,
+    /// per day that had any work in the window
+    pub daily_average: Seconds,
+    pub session_average: Seconds,
+    pub best_day: Option<(NaiveDate, Seconds)>,
+    /// the weakest day that still had some work
+    pub worst_day: Option<(NaiveDate, Seconds)>,
+}
+impl LocalStats {
+    pub fn compute(history: &[Session], today: NaiveDate) -> LocalStats {
+        let week_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        let window_start = today - Duration::days(WINDOW_DAYS - 1);
+        let mut stats = LocalStats::default();
+        let mut per_day: HashMap<NaiveDate, Seconds> = HashMap::new();
+        let mut per_subject: HashMap<String, Seconds> = HashMap::new();
+
+        for session in history
+            .iter()
+            .filter(|session| session.phase() == Phase::Work)
+        {
+            let day = session.start().date_naive();
+            let duration = session.duration();
+            stats.all_time += duration;
+            if day == today {
+                stats.today += duration;
+            }
+            if day >= week_start && day <= today {
+                stats.week += duration;
+            }
+            if day.year() == today.year() && day.month() == today.month() {
+                stats.month += duration;
+            }
+            if day < window_start || day > today {
+                continue;
+            }
+            stats.sessions += 1;
+            *per_day.entry(day).or_default() += duration;
+            let subject = session
+                .subject_name()
+                .or(session.subject_id())
+                .unwrap_or(NO_SUBJECT);
+            *per_subject.entry(subject.to_string()).or_default() += duration;
+        }
+
+        stats.days = (0..WINDOW_DAYS)
+            .map(|offset| window_start + Duration::days(offset))
+            .map(|day| (day, per_day.get(&day).copied().unwrap_or(0)))
+            .collect();
+        let active: Vec<(NaiveDate, Seconds)> = stats
+            .days
+            .iter()
+            .copied()
+            .filter(|(_, seconds)| *seconds > 0)
+            .collect();
+        let window_total: Seconds = active.iter().map(|(_, seconds)| seconds).sum();
+        if !active.is_empty() {
+            stats.daily_average = window_total / active.len() as Seconds;
+            stats.session_average = window_total / stats.sessions as Seconds;
+        }
+        // ties go to the most recent day
+        stats.best_day = active.iter().copied().max_by_key(|(_, seconds)| *seconds);
+        stats.worst_day = active
+            .iter()
+            .copied()
+            .rev()
+            .min_by_key(|(_, seconds)| *seconds);
+
+        stats.subjects = per_subject.into_iter().collect();
+        stats
+            .subjects
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+    use crate::stats::session::Plan;
+
+    fn work(day: u32, minutes: i64, phase: Phase) -> Session {
+        let start = Local.with_ymd_and_hms(2024, 3, day, 10, 0, 0).unwrap();
+        let end = start + Duration::minutes(minutes);
+        Session::new(start, end, minutes * 60, phase, Plan::Pomodoro, 1, None)
+    }
+    /// Computed on Wednesday the 6th, the week started on the 4th
+    fn stats() -> LocalStats {
+        let history = vec![
+            work(1, 50, Phase::Work),
+            work(4, 25, Phase::Work),
+            work(4, 25, Phase::Work),
+            work(4, 5, Phase::Break),
+            work(6, 100, Phase::Work),
+        ];
+        LocalStats::compute(&history, NaiveDate::from_ymd_opt(2024, 3, 6).unwrap())
+    }
+
+    #[test]
+    fn work_is_summed_per_window() {
+        let stats = stats();
+        assert_eq!(stats.today, 100 * 60);
+        assert_eq!(stats.week, 150 * 60);
+        assert_eq!(stats.month, 200 * 60);
+    }
+    #[test]
+    fn breaks_dont_count_as_sessions() {
+        let stats = stats();
+        assert_eq!(stats.sessions, 4);
+        assert_eq!(stats.session_average, 50 * 60);
+    }
+    #[test]
+    fn daily_average_only_counts_days_with_work() {
+        assert_eq!(stats().daily_average, 200 * 60 / 3);
+    }
+    #[test]
+    fn best_day_is_the_busiest() {
+        assert_eq!(stats().best_day.unwrap().1, 100 * 60);
+    }
+    #[test]
+    fn ties_for_worst_day_go_to_the_later_one() {
+        // the 1st and the 4th both have 50 minutes
+        assert_eq!(stats().worst_day.unwrap().0.day(), 4);
+    }
+    #[test]
+    fn sessions_without_subject_are_grouped() {
+        assert_eq!(stats().subjects, vec![(NO_SUBJECT.to_string(), 200 * 60)]);
+    }
+    #[test]
+    fn days_cover_the_window_up_to_today() {
+        let stats = stats();
+        assert_eq!(stats.days.len(), WINDOW_DAYS as usize);
+        assert_eq!(stats.days.last().unwrap().1, 100 * 60);
+    }
+}
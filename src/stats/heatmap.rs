@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use super::{
+    pixela::{
+        graph::{Graph, PixelaColors},
+        subjects::{Minutes, SubjectUnit},
+    },
+    session::{Phase, Session},
+};
+
+/// A year of weeks, plus the partial week we're in.
+pub const HEATMAP_WEEKS: i64 = 53;
+pub const HEATMAP_DAYS: i64 = 365;
+
+/// Minutes per day laid out like a contribution calendar: weeks are columns, weekdays rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    title: String,
+    color: PixelaColors,
+    end: NaiveDate,
+    minutes: HashMap<NaiveDate, Minutes>,
+    /// what `minutes` holds, blocks for count graphs
+    unit: SubjectUnit,
+    cursor: NaiveDate,
+}
+impl Heatmap {
+    pub fn new(
+        title: String,
+        color: PixelaColors,
+        end: NaiveDate,
+        minutes: HashMap<NaiveDate, Minutes>,
+    ) -> Heatmap {
+        Heatmap {
+            title,
+            color,
+            end,
+            minutes,
+            unit: SubjectUnit::Minutes,
+            cursor: end,
+        }
+    }
+    pub fn from_sessions(sessions: &[Session], end: NaiveDate) -> Heatmap {
+        let mut minutes: HashMap<NaiveDate, Minutes> = HashMap::new();
+        for session in sessions
+            .iter()
+            .filter(|session| session.phase() == Phase::Work)
+        {
+            *minutes.entry(session.start().date_naive()).or_default() +=
+                (session.duration() / 60) as Minutes;
+        }
+        Heatmap::new("Local history".into(), PixelaColors::Shibafu, end, minutes)
+    }
+    pub fn from_graph(graph: &Graph, end: NaiveDate) -> Heatmap {
+        let unit = graph.subject().unit();
+        let minutes = graph
+            .data()
+            .iter()
+            .filter_map(|point| {
+                let date = NaiveDate::parse_from_str(point.date(), "%Y%m%d").ok()?;
+                Some((date, point.quantity().minutes(unit)))
+            })
+            .collect();
+        let mut heatmap = Heatmap::new(
+            graph.subject().graph_name().to_string(),
+            graph.subject().color().clone(),
+            end,
+            minutes,
+        );
+        heatmap.unit = unit.local();
+        heatmap
+    }
+    /// The Monday that opens the first column.
+    pub fn start(&self) -> NaiveDate {
+        let this_monday =
+            self.end - Duration::days(self.end.weekday().num_days_from_monday() as i64);
+        this_monday - Duration::weeks(HEATMAP_WEEKS - 1)
+    }
+    /// `None` for the days of the last week that haven't happened yet.
+    pub fn day(&self, week: usize, weekday: usize) -> Option<NaiveDate> {
+        let day = self.start() + Duration::days(week as i64 * 7 + weekday as i64);
+        (day <= self.end).then_some(day)
+    }
+    pub fn minutes_on(&self, day: NaiveDate) -> Minutes {
+        self.minutes.get(&day).copied().unwrap_or(0)
+    }
+    fn visible(&self) -> impl Iterator<Item = Minutes> + '_ {
+        let start = self.start();
+        self.minutes
+            .iter()
+            .filter(move |(day, _)| **day >= start && **day <= self.end)
+            .map(|(_, minutes)| *minutes)
+    }
+    pub fn max(&self) -> Minutes {
+        self.visible().max().unwrap_or(0)
+    }
+    pub fn total(&self) -> Minutes {
+        self.visible().sum()
+    }
+    pub fn active_days(&self) -> usize {
+        self.visible().filter(|minutes| *minutes > 0).count()
+    }
+    /// Shade of a day from 0 (nothing) to 4, relative to the busiest visible day.
+    pub fn level(&self, day: NaiveDate) -> u8 {
+        let minutes = self.minutes_on(day);
+        let max = self.max();
+        if minutes == 0 || max == 0 {
+            return 0;
+        }
+        (minutes * 4).div_ceil(max).clamp(1, 4) as u8
+    }
+    pub fn move_cursor(&mut self, days: i64) {
+        self.cursor = (self.cursor + Duration::days(days)).clamp(self.start(), self.end);
+    }
+    pub fn cursor(&self) -> NaiveDate {
+        self.cursor
+    }
+    pub fn unit(&self) -> &SubjectUnit {
+        &self.unit
+    }
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    pub fn color(&self) -> &PixelaColors {
+        &self.color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a Wednesday
+    fn end() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 6).unwrap()
+    }
+    fn heatmap() -> Heatmap {
+        let minutes = HashMap::from([
+            (end(), 100),
+            (end() - Duration::days(1), 20),
+            (end() - Duration::days(400), 500),
+        ]);
+        Heatmap::new("Thesis".into(), PixelaColors::Sora, end(), minutes)
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        let heatmap = heatmap();
+        assert_eq!(heatmap.start().weekday(), chrono::Weekday::Mon);
+        assert_eq!(heatmap.day(52, 2), Some(end()));
+        assert_eq!(heatmap.day(52, 3), None);
+    }
+    #[test]
+    fn days_outside_the_year_dont_count() {
+        let heatmap = heatmap();
+        assert_eq!(heatmap.max(), 100);
+        assert_eq!(heatmap.active_days(), 2);
+    }
+    #[test]
+    fn days_are_shaded_against_the_busiest() {
+        let heatmap = heatmap();
+        assert_eq!(heatmap.level(end()), 4);
+        assert_eq!(heatmap.level(end() - Duration::days(1)), 1);
+    }
+    #[test]
+    fn cursor_stays_inside_the_year() {
+        let mut heatmap = heatmap();
+        heatmap.move_cursor(7);
+        assert_eq!(heatmap.cursor(), end());
+        heatmap.move_cursor(-1000);
+        assert_eq!(heatmap.cursor(), heatmap.start());
+    }
+}
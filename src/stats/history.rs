@@ -0,0 +1,331 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use chrono::{Local, NaiveDate, Utc};
+
+use crate::{
+    error::{Result, StatsError},
+    settings::data_dir,
+};
+
+use super::session::{Phase, Session};
+
+const ICS_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const CSV_HEADER: &str = "start,end,duration_minutes,phase,plan,iteration,subject_id,subject,task";
+
+/// Every finished session, one JSON object per line in `history.jsonl`.
+/// Unlike the pixels file it is never trimmed, so it can be exported later.
+pub struct History;
+impl History {
+    pub fn path() -> Result<PathBuf> {
+        Ok(data_dir()?.join("history.jsonl"))
+    }
+    pub fn append(session: &Session) -> Result<()> {
+        History::append_all(std::slice::from_ref(session))
+    }
+    pub fn append_all(sessions: &[Session]) -> Result<()> {
+        let path = History::path()?;
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)?
+            }
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        for session in sessions {
+            writeln!(file, "{}", serde_json::to_string(session)?)?;
+        }
+        Ok(())
+    }
+    /// The sessions and how many rows were skipped, see `parse`.
+    pub fn load() -> Result<(Vec<Session>, usize)> {
+        let path = History::path()?;
+        if !path.exists() {
+            return Ok((Vec::new(), 0));
+        }
+        Ok(History::parse(&fs::read_to_string(path)?))
+    }
+    /// Where exports from the stats tab end up, `exports/history-<today>.<ext>`.
+    pub fn export_path(format: ExportFormat) -> Result<PathBuf> {
+        let path = data_dir()?.join("exports");
+        if !path.exists() {
+            fs::create_dir_all(&path)?
+        }
+        let today = Local::now().format("%Y-%m-%d");
+        Ok(path.join(format!("history-{today}.{}", format.extension())))
+    }
+    /// The sessions of a history file and how many rows were skipped.
+    pub(crate) fn parse(contents: &str) -> (Vec<Session>, usize) {
+        // a half written line from a crash or a hand edit shouldn't make the whole history
+        // unreadable, those rows are skipped and counted for the caller to report
+        let mut sessions: Vec<Session> = Vec::new();
+        let mut skipped = 0;
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str(line) {
+                Ok(session) => sessions.push(session),
+                Err(_) => skipped += 1,
+            }
+        }
+        sessions.sort_by_key(|session| session.start());
+        (sessions, skipped)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+    Ics,
+}
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Csv, ExportFormat::Json, ExportFormat::Ics];
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Ics => "ics",
+        }
+    }
+    pub fn render(&self, sessions: &[&Session]) -> Result<String> {
+        Ok(match self {
+            ExportFormat::Csv => to_csv(sessions),
+            ExportFormat::Json => serde_json::to_string_pretty(sessions)?,
+            ExportFormat::Ics => to_ics(sessions),
+        })
+    }
+}
+impl FromStr for ExportFormat {
+    type Err = StatsError;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            "ics" | "ical" | "icalendar" => Ok(ExportFormat::Ics),
+            _ => Err(StatsError::InvalidExportOption(format!(
+                "unknown format {s}"
+            ))),
+        }
+    }
+}
+
+/// Which sessions to export, dates are inclusive and compared in local time.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// graph id or name
+    pub subject: Option<String>,
+}
+impl HistoryFilter {
+    pub fn matches(&self, session: &Session) -> bool {
+        let day = session.start().date_naive();
+        let in_range =
+            self.from.is_none_or(|from| day >= from) && self.to.is_none_or(|to| day <= to);
+        let subject = self.subject.as_ref().is_none_or(|wanted| {
+            [session.subject_id(), session.subject_name()]
+                .into_iter()
+                .flatten()
+                .any(|subject| subject.eq_ignore_ascii_case(wanted))
+        });
+        in_range && subject
+    }
+    pub fn apply<'a>(&self, sessions: &'a [Session]) -> Vec<&'a Session> {
+        sessions
+            .iter()
+            .filter(|session| self.matches(session))
+            .collect()
+    }
+}
+
+/// `rtuion export [--format csv|json|ics] [--from DATE] [--to DATE] [--subject GRAPH] [--output FILE]`
+#[derive(Debug, Default)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub filter: HistoryFilter,
+    pub output: Option<PathBuf>,
+}
+impl ExportOptions {
+    pub fn from_args(args: &[String]) -> Result<ExportOptions> {
+        let mut options = ExportOptions::default();
+        let mut format = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next().ok_or(StatsError::InvalidExportOption(format!(
+                    "{arg} needs a value"
+                )))
+            };
+            match arg.as_str() {
+                "--format" | "-f" => format = Some(value()?.parse()?),
+                "--from" => options.filter.from = Some(parse_date(value()?)?),
+                "--to" => options.filter.to = Some(parse_date(value()?)?),
+                "--subject" | "-s" => options.filter.subject = Some(value()?.clone()),
+                "--output" | "-o" => options.output = Some(PathBuf::from(value()?)),
+                _ => return Err(StatsError::InvalidExportOption(arg.clone()).into()),
+            }
+        }
+        // without --format, the output file's extension decides
+        options.format = match (format, &options.output) {
+            (Some(format), _) => format,
+            (None, Some(output)) => output
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(|ext| ext.parse().ok())
+                .unwrap_or_default(),
+            (None, None) => ExportFormat::default(),
+        };
+        Ok(options)
+    }
+    pub fn render(&self) -> Result<String> {
+        let (sessions, _) = History::load()?;
+        self.format.render(&self.filter.apply(&sessions))
+    }
+}
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        StatsError::InvalidExportOption(format!("{value} is not a YYYY-MM-DD date")).into()
+    })
+}
+
+fn to_csv(sessions: &[&Session]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for session in sessions {
+        let row = [
+            session.start().to_rfc3339(),
+            session.end().to_rfc3339(),
+            format!("{:.2}", session.duration() as f64 / 60.0),
+            session.phase().to_string(),
+            session.plan().to_string(),
+            session.iteration().to_string(),
+            session.subject_id().unwrap_or_default().to_string(),
+            session.subject_name().unwrap_or_default().to_string(),
+            session.task().unwrap_or_default().to_string(),
+        ];
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+fn to_ics(sessions: &[&Session]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//rtuion//rtuion//EN".to_string(),
+    ];
+    let stamp = Utc::now().format(ICS_TIME_FORMAT);
+    for session in sessions {
+        let start = session.start().with_timezone(&Utc).format(ICS_TIME_FORMAT);
+        let end = session.end().with_timezone(&Utc).format(ICS_TIME_FORMAT);
+        let mut summary = match session.phase() {
+            Phase::Work => "Work".to_string(),
+            Phase::Break => "Break".to_string(),
+        };
+        if let Some(subject) = session.subject_name() {
+            summary.push_str(&format!(": {subject}"));
+        }
+        if let Some(task) = session.task() {
+            summary.push_str(&format!(" - {task}"));
+        }
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            // the start time is unique per session, so re-imports update instead of duplicating
+            format!("UID:{start}-{}@rtuion", session.phase()),
+            format!("DTSTAMP:{stamp}"),
+            format!("DTSTART:{start}"),
+            format!("DTEND:{end}"),
+            format!("SUMMARY:{}", ics_text(&summary)),
+            format!("CATEGORIES:{}", session.phase()),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+    let mut ics = lines.join("\r\n");
+    ics.push_str("\r\n");
+    ics
+}
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+    use crate::stats::session::Plan;
+
+    fn work_session() -> Session {
+        let start = Local.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+        Session::new(
+            start,
+            start + chrono::Duration::minutes(25),
+            1500,
+            Phase::Work,
+            Plan::Pomodoro,
+            1,
+            None,
+        )
+        .with_task(Some("Write \"intro\", part 1".into()))
+    }
+
+    #[test]
+    fn rows_that_arent_json_are_skipped() {
+        let row = serde_json::to_string(&work_session()).unwrap();
+        let history = format!("{row}\nnot json\n");
+        assert_eq!(History::parse(&history), (vec![work_session()], 1));
+    }
+    #[test]
+    fn export_format_follows_the_output_extension() {
+        let options: Vec<String> = ["--from", "2024-03-04", "-o", "week.ics"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let options = ExportOptions::from_args(&options).unwrap();
+        assert_eq!(options.format, ExportFormat::Ics);
+        assert_eq!(options.filter.apply(&[work_session()]).len(), 1);
+    }
+    #[test]
+    fn subject_filter_leaves_out_other_subjects() {
+        let filter = HistoryFilter {
+            subject: Some("thesis".into()),
+            ..Default::default()
+        };
+        assert!(filter.apply(&[work_session()]).is_empty());
+    }
+    #[test]
+    fn csv_quotes_the_task() {
+        let csv = ExportFormat::Csv.render(&[&work_session()]).unwrap();
+        assert!(csv.ends_with(",25.00,work,pomodoro,1,,,\"Write \"\"intro\"\", part 1\"\n"));
+    }
+    #[test]
+    fn ics_escapes_the_summary() {
+        let ics = ExportFormat::Ics.render(&[&work_session()]).unwrap();
+        assert!(ics.contains("SUMMARY:Work - Write \"intro\"\\, part 1\r\n"));
+    }
+    #[test]
+    fn rows_with_unreadable_dates_are_skipped() {
+        let start = Local.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+        let end = start + chrono::Duration::minutes(25);
+        let session = Session::imported(start, end, None, None, "x");
+        let good = serde_json::to_string(&session).unwrap();
+        let truncated = good.replace(&start.to_rfc3339(), "2024-03-04T09:");
+        let sessions = History::parse(&format!("{truncated}\n{good}\n"));
+        assert_eq!(sessions, (vec![session], 1));
+    }
+}
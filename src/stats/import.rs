@@ -0,0 +1,551 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone, Utc};
+use serde::Deserialize;
+
+use crate::{
+    error::{Result, StatsError},
+    settings::data_dir,
+};
+
+use super::{history::History, session::Session};
+
+const DATE_FORMATS: [&str; 5] = ["%Y-%m-%d", "%m/%d/%Y", "%d/%m/%Y", "%d.%m.%Y", "%Y/%m/%d"];
+const TIME_FORMATS: [&str; 4] = ["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"];
+const TIMEW_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Column names of a CSV export, matched case-insensitively against its header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvColumns {
+    /// a full date and time, or just the date when `start_time` is set
+    pub start: String,
+    pub start_time: Option<String>,
+    pub end: Option<String>,
+    pub end_time: Option<String>,
+    /// minutes, or `H:MM[:SS]`; only used when there is no end
+    pub duration: Option<String>,
+    /// bare numbers in `duration` are decimal hours instead of minutes
+    pub duration_in_hours: bool,
+    pub project: Option<String>,
+    pub description: Option<String>,
+}
+impl Default for CsvColumns {
+    fn default() -> Self {
+        CsvColumns {
+            start: "start".into(),
+            start_time: None,
+            end: Some("end".into()),
+            end_time: None,
+            duration: Some("duration".into()),
+            duration_in_hours: false,
+            project: Some("project".into()),
+            description: Some("description".into()),
+        }
+    }
+}
+impl CsvColumns {
+    fn toggl() -> CsvColumns {
+        CsvColumns {
+            start: "Start date".into(),
+            start_time: Some("Start time".into()),
+            end: Some("End date".into()),
+            end_time: Some("End time".into()),
+            duration: Some("Duration".into()),
+            duration_in_hours: false,
+            project: Some("Project".into()),
+            description: Some("Description".into()),
+        }
+    }
+    fn clockify() -> CsvColumns {
+        CsvColumns {
+            start: "Start Date".into(),
+            start_time: Some("Start Time".into()),
+            end: Some("End Date".into()),
+            end_time: Some("End Time".into()),
+            duration: Some("Duration (h)".into()),
+            duration_in_hours: true,
+            project: Some("Project".into()),
+            description: Some("Description".into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportSource {
+    Csv(CsvColumns),
+    Toggl(CsvColumns),
+    Clockify(CsvColumns),
+    Timewarrior,
+}
+impl ImportSource {
+    fn from_name(name: &str) -> Result<ImportSource> {
+        match name.to_lowercase().as_str() {
+            "csv" => Ok(ImportSource::Csv(CsvColumns::default())),
+            "toggl" => Ok(ImportSource::Toggl(CsvColumns::toggl())),
+            "clockify" => Ok(ImportSource::Clockify(CsvColumns::clockify())),
+            "timew" | "timewarrior" => Ok(ImportSource::Timewarrior),
+            _ => Err(StatsError::ImportFailed(format!(
+                "unknown source {name}, use csv, toggl, clockify or timew"
+            ))
+            .into()),
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImportSource::Csv(_) => "csv",
+            ImportSource::Toggl(_) => "toggl",
+            ImportSource::Clockify(_) => "clockify",
+            ImportSource::Timewarrior => "timewarrior",
+        }
+    }
+    fn columns_mut(&mut self) -> Option<&mut CsvColumns> {
+        match self {
+            ImportSource::Csv(columns)
+            | ImportSource::Toggl(columns)
+            | ImportSource::Clockify(columns) => Some(columns),
+            ImportSource::Timewarrior => None,
+        }
+    }
+}
+
+/// One interval read from an export, before it is mapped to a subject.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedRow {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub project: Option<String>,
+    pub description: Option<String>,
+}
+
+/// `rtuion import <csv|toggl|clockify|timew> FILE [--subject GRAPH] [--map PROJECT=GRAPH]... [--queue]`
+#[derive(Debug)]
+pub struct ImportOptions {
+    pub source: ImportSource,
+    pub file: PathBuf,
+    /// puts every row on this graph, ignoring projects
+    pub subject: Option<String>,
+    /// project -> Pixela graph id
+    pub projects: HashMap<String, String>,
+    pub date_format: Option<String>,
+    /// also turn the imported sessions into pixels once Pixela is logged in
+    pub queue: bool,
+}
+impl ImportOptions {
+    pub fn from_args(args: &[String]) -> Result<ImportOptions> {
+        let missing = || StatsError::ImportFailed("usage: rtuion import <source> FILE".into());
+        let mut args = args.iter();
+        let source = ImportSource::from_name(args.next().ok_or_else(missing)?)?;
+        let file = PathBuf::from(args.next().ok_or_else(missing)?);
+        let mut options = ImportOptions {
+            source,
+            file,
+            subject: None,
+            projects: HashMap::new(),
+            date_format: None,
+            queue: false,
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .cloned()
+                    .ok_or(StatsError::ImportFailed(format!("{arg} needs a value")))
+            };
+            match arg.as_str() {
+                "--queue" => options.queue = true,
+                "--subject" | "-s" => options.subject = Some(value()?),
+                "--date-format" => options.date_format = Some(value()?),
+                "--map" => {
+                    let mapping = value()?;
+                    let (project, graph) =
+                        mapping
+                            .split_once('=')
+                            .ok_or(StatsError::ImportFailed(format!(
+                                "{mapping} is not PROJECT=GRAPH"
+                            )))?;
+                    options.projects.insert(project.into(), graph.into());
+                }
+                column => {
+                    let columns = options
+                        .source
+                        .columns_mut()
+                        .ok_or(StatsError::ImportFailed(format!(
+                            "{column} only works for CSV sources"
+                        )))?;
+                    match column {
+                        "--start" => columns.start = value()?,
+                        "--start-time" => columns.start_time = Some(value()?),
+                        "--end" => columns.end = Some(value()?),
+                        "--end-time" => columns.end_time = Some(value()?),
+                        "--duration" => columns.duration = Some(value()?),
+                        "--project" => columns.project = Some(value()?),
+                        "--description" => columns.description = Some(value()?),
+                        _ => return Err(StatsError::ImportFailed(column.into()).into()),
+                    }
+                }
+            }
+        }
+        Ok(options)
+    }
+    pub fn read_rows(&self, contents: &str) -> Result<Vec<ImportedRow>> {
+        match &self.source {
+            ImportSource::Csv(columns)
+            | ImportSource::Toggl(columns)
+            | ImportSource::Clockify(columns) => {
+                read_csv(columns, contents, self.date_format.as_deref())
+            }
+            ImportSource::Timewarrior => read_timewarrior(contents, &self.projects),
+        }
+    }
+    pub fn to_session(&self, row: ImportedRow) -> Session {
+        // unmapped projects keep only their name, it's matched against graph names later
+        let subject_id = self
+            .subject
+            .clone()
+            .or_else(|| self.projects.get(row.project.as_ref()?).cloned());
+        Session::imported(
+            row.start,
+            row.end,
+            subject_id,
+            row.project,
+            self.source.name(),
+        )
+        .with_task(row.description)
+    }
+    /// Appends every row that isn't in the history yet.
+    pub fn run(&self) -> Result<ImportSummary> {
+        let contents = fs::read_to_string(&self.file)?;
+        let rows = self.read_rows(&contents)?;
+        let mut known: HashSet<String> =
+            History::load()?.0.iter().map(Session::dedup_key).collect();
+        let mut summary = ImportSummary::default();
+        let mut sessions = Vec::new();
+        for row in rows {
+            let session = self.to_session(row);
+            match known.insert(session.dedup_key()) {
+                true => sessions.push(session),
+                false => summary.duplicates += 1,
+            }
+        }
+        History::append_all(&sessions)?;
+        if self.queue {
+            ImportQueue::push(&sessions)?;
+            summary.queued = sessions.len();
+        }
+        summary.imported = sessions.len();
+        Ok(summary)
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub duplicates: usize,
+    pub queued: usize,
+}
+impl Display for ImportSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Imported {} sessions, skipped {} already in the history",
+            self.imported, self.duplicates
+        )?;
+        if self.queued > 0 {
+            write!(
+                f,
+                ", {} will become pixels on the next Pixela login",
+                self.queued
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Imported sessions waiting for a Pixela login, which is when we learn the graphs.
+pub struct ImportQueue;
+impl ImportQueue {
+    fn path() -> Result<PathBuf> {
+        Ok(data_dir()?.join("import_queue.jsonl"))
+    }
+    pub fn push(sessions: &[Session]) -> Result<()> {
+        let path = ImportQueue::path()?;
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)?
+            }
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        for session in sessions {
+            writeln!(file, "{}", serde_json::to_string(session)?)?;
+        }
+        Ok(())
+    }
+    pub fn load() -> Result<Vec<Session>> {
+        let path = ImportQueue::path()?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(History::parse(&fs::read_to_string(path)?).0)
+    }
+    /// Replaces the queue with the sessions that still couldn't be matched to a graph.
+    pub fn replace(sessions: &[Session]) -> Result<()> {
+        let path = ImportQueue::path()?;
+        if sessions.is_empty() {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        let lines: Vec<String> = sessions
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<std::result::Result<_, _>>()?;
+        fs::write(path, lines.join("\n") + "\n")?;
+        Ok(())
+    }
+}
+
+fn read_csv(
+    columns: &CsvColumns,
+    contents: &str,
+    date_format: Option<&str>,
+) -> Result<Vec<ImportedRow>> {
+    let failed = |message: String| StatsError::ImportFailed(message);
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(contents.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|err| failed(err.to_string()))?
+        .clone();
+    let index = |name: &str| {
+        headers.iter().position(|header| {
+            header
+                .trim_start_matches('\u{feff}')
+                .eq_ignore_ascii_case(name)
+        })
+    };
+    let optional = |name: &Option<String>| name.as_deref().and_then(index);
+    let start = index(&columns.start)
+        .ok_or_else(|| failed(format!("there is no `{}` column", columns.start)))?;
+    let (start_time, end, end_time) = (
+        optional(&columns.start_time),
+        optional(&columns.end),
+        optional(&columns.end_time),
+    );
+    let (duration, project, description) = (
+        optional(&columns.duration),
+        optional(&columns.project),
+        optional(&columns.description),
+    );
+    if end.is_none() && duration.is_none() {
+        return Err(failed("an end or duration column is needed".into()).into());
+    }
+
+    let mut rows = Vec::new();
+    for (line, record) in reader.records().enumerate() {
+        let record = record.map_err(|err| failed(err.to_string()))?;
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .filter(|value| !value.is_empty())
+        };
+        // the header is line 1
+        let row_error = |what: &str| failed(format!("line {}: can't read the {what}", line + 2));
+        let start = field(Some(start))
+            .and_then(|start| parse_datetime(start, field(start_time), date_format))
+            .ok_or_else(|| row_error("start"))?;
+        let end = match (field(end), field(duration)) {
+            (Some(end), _) => parse_datetime(end, field(end_time), date_format),
+            (None, Some(duration)) => {
+                parse_duration(duration, columns.duration_in_hours).map(|duration| start + duration)
+            }
+            (None, None) => None,
+        }
+        .ok_or_else(|| row_error("end"))?;
+        if end <= start {
+            continue;
+        }
+        rows.push(ImportedRow {
+            start,
+            end,
+            project: field(project).map(String::from),
+            description: field(description).map(String::from),
+        });
+    }
+    Ok(rows)
+}
+fn parse_datetime(
+    value: &str,
+    time: Option<&str>,
+    format: Option<&str>,
+) -> Option<DateTime<Local>> {
+    let value = match time {
+        Some(time) => format!("{value} {time}"),
+        None => value.to_string(),
+    };
+    if let Some(format) = format {
+        let naive = NaiveDateTime::parse_from_str(&value, format).ok()?;
+        return Local.from_local_datetime(&naive).earliest();
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(&value) {
+        return Some(datetime.with_timezone(&Local));
+    }
+    DATE_FORMATS
+        .iter()
+        .flat_map(|date| {
+            TIME_FORMATS
+                .iter()
+                .flat_map(move |time| [format!("{date} {time}"), format!("{date}T{time}")])
+        })
+        .find_map(|format| NaiveDateTime::parse_from_str(&value, &format).ok())
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+}
+fn parse_duration(value: &str, in_hours: bool) -> Option<Duration> {
+    if !value.contains(':') {
+        let amount: f64 = value.replace(',', ".").parse().ok()?;
+        let seconds = match in_hours {
+            true => amount * 3600.0,
+            false => amount * 60.0,
+        };
+        return Some(Duration::seconds(seconds.round() as i64));
+    }
+    let parts: Vec<i64> = value
+        .split(':')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    match parts[..] {
+        [hours, minutes] => Some(Duration::hours(hours) + Duration::minutes(minutes)),
+        [hours, minutes, seconds] => {
+            Some(Duration::hours(hours) + Duration::minutes(minutes) + Duration::seconds(seconds))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TimewInterval {
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    annotation: Option<String>,
+}
+fn read_timewarrior(
+    contents: &str,
+    projects: &HashMap<String, String>,
+) -> Result<Vec<ImportedRow>> {
+    let intervals: Vec<TimewInterval> = serde_json::from_str(contents)?;
+    let parse = |value: &str| {
+        NaiveDateTime::parse_from_str(value, TIMEW_FORMAT)
+            .ok()
+            .map(|naive| Utc.from_utc_datetime(&naive).with_timezone(&Local))
+    };
+    Ok(intervals
+        .into_iter()
+        .filter_map(|interval| {
+            // the interval that is still running has no end yet
+            let start = parse(&interval.start)?;
+            let end = parse(interval.end.as_deref()?)?;
+            let project = interval
+                .tags
+                .iter()
+                .find(|tag| projects.contains_key(*tag))
+                .or(interval.tags.first())
+                .cloned();
+            Some(ImportedRow {
+                start,
+                end,
+                project,
+                description: interval.annotation,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(args: &[&str]) -> ImportOptions {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        ImportOptions::from_args(&args).unwrap()
+    }
+
+    fn toggl_rows() -> (ImportOptions, Vec<ImportedRow>) {
+        let toggl = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n\
+            Me,me@example.com,,Thesis,,\"Intro, draft\",No,2024-03-04,09:00:00,2024-03-04,09:25:00,00:25:00,\n";
+        let import = options(&["toggl", "export.csv", "--map", "Thesis=thesis-graph"]);
+        let rows = import.read_rows(toggl).unwrap();
+        (import, rows)
+    }
+    fn csv_rows() -> (ImportOptions, Vec<ImportedRow>) {
+        let csv = "when,minutes,what\n2024-03-04 10:00,50,reading\n";
+        let import = options(&[
+            "csv",
+            "log.csv",
+            "--start",
+            "when",
+            "--duration",
+            "minutes",
+            "--project",
+            "what",
+        ]);
+        let rows = import.read_rows(csv).unwrap();
+        (import, rows)
+    }
+
+    #[test]
+    fn toggl_rows_span_start_to_end() {
+        let (_, rows) = toggl_rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].end - rows[0].start, Duration::minutes(25));
+    }
+    #[test]
+    fn toggl_projects_are_mapped_to_subjects() {
+        let (import, rows) = toggl_rows();
+        let session = import.to_session(rows[0].clone());
+        assert_eq!(session.subject_id(), Some("thesis-graph"));
+        assert_eq!(session.task(), Some("Intro, draft"));
+        assert_eq!(session.source(), Some("toggl"));
+    }
+    #[test]
+    fn csv_columns_can_be_named() {
+        let (_, rows) = csv_rows();
+        assert_eq!(rows[0].end - rows[0].start, Duration::minutes(50));
+        assert_eq!(rows[0].project.as_deref(), Some("reading"));
+    }
+    #[test]
+    fn same_interval_gets_the_same_key() {
+        let (import, rows) = csv_rows();
+        assert_eq!(
+            import.to_session(rows[0].clone()).dedup_key(),
+            import.to_session(rows[0].clone()).dedup_key()
+        );
+    }
+
+    #[test]
+    fn clockify_durations_are_decimal_hours() {
+        let clockify = "Project,Description,Start Date,Start Time,Duration (h)\n\
+            Thesis,Intro,03/04/2024,09:00:00,1.5\n";
+        let rows = options(&["clockify", "report.csv"])
+            .read_rows(clockify)
+            .unwrap();
+        assert_eq!(rows[0].end - rows[0].start, Duration::minutes(90));
+    }
+    #[test]
+    fn reads_timewarrior_export() {
+        let export = r#"[{"id":2,"start":"20240304T080000Z","end":"20240304T082500Z","tags":["deep","thesis"]},
+            {"id":1,"start":"20240304T090000Z","tags":["thesis"]}]"#;
+        let import = options(&["timew", "export.json", "--map", "thesis=thesis-graph"]);
+        let rows = import.read_rows(export).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].project.as_deref(), Some("thesis"));
+    }
+}
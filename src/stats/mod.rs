@@ -1,2 +1,8 @@
+pub mod analytics;
+pub mod heatmap;
+pub mod history;
+pub mod import;
 pub mod pixel;
 pub mod pixela;
+pub mod session;
+pub mod sync;
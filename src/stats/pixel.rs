@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use super::pixela::{
-    complex_pixel::{ComplexPixel, IsRounded},
-    subjects::Minutes,
+use super::{
+    pixela::{
+        complex_pixel::{ComplexPixel, IsRounded},
+        subjects::Minutes,
+    },
+    sync::stable_id,
 };
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, PartialOrd, Clone)]
@@ -14,8 +17,28 @@ pub enum Pixel {
 pub struct SimplePixel {
     progress: Minutes,
     date: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task: Option<String>,
+    /// assigned when the pixel is first saved, see `SyncFolder`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
 }
 impl Pixel {
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Pixel::Simple(pixel) => pixel.id.as_deref(),
+            Pixel::Complex(pixel) => pixel.id(),
+        }
+    }
+    /// Gives the pixel a stable id if it doesn't have one yet.
+    pub fn ensure_id(&mut self) {
+        if self.id().is_none() {
+            match self {
+                Pixel::Simple(pixel) => pixel.id = Some(stable_id()),
+                Pixel::Complex(pixel) => pixel.set_id(stable_id()),
+            }
+        }
+    }
     pub fn check_if_value_rounded(&self) -> IsRounded {
         match self {
             Pixel::Simple(_) => IsRounded::No,
@@ -25,7 +48,12 @@ impl Pixel {
 }
 impl SimplePixel {
     pub fn new(progress: Minutes, date: String) -> SimplePixel {
-        SimplePixel { progress, date }
+        SimplePixel {
+            progress,
+            date,
+            task: None,
+            id: None,
+        }
     }
 
     pub fn progress(&self) -> Minutes {
@@ -35,4 +63,12 @@ impl SimplePixel {
     pub fn date(&self) -> &str {
         &self.date
     }
+
+    pub fn task(&self) -> Option<&str> {
+        self.task.as_deref()
+    }
+
+    pub fn set_task(&mut self, task: Option<String>) {
+        self.task = task;
+    }
 }
@@ -1,9 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{error::Result, stats::pixela::subjects::SubjectDataType};
+use crate::{error::Result, stats::pixela::subjects::SubjectDataType, utils::misc};
 
 use super::{
-    helpers::{check_if_quantity_is_big_enough, send_to_pixela},
+    helpers::put_to_pixela,
     pixela_user::PixelaUser,
     subjects::{Progress, Subject, SubjectUnit},
 };
@@ -13,6 +13,10 @@ pub struct ComplexPixel {
     progress: Progress,
     date: String,
     subject: Subject,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
 }
 
 impl ComplexPixel {
@@ -21,11 +25,17 @@ impl ComplexPixel {
             progress,
             date,
             subject,
+            task: None,
+            id: None,
         }
     }
-    pub async fn upload(&self, client: reqwest::Client, pixela_user: &PixelaUser) -> Result<()> {
-        check_if_quantity_is_big_enough(self)?;
-        send_to_pixela(self, &client, pixela_user.token()).await
+    /// Uploads the pixel as the day's absolute value, see `UploadLedger`.
+    pub async fn upload(
+        &self,
+        client: reqwest::Client,
+        pixela_user: &PixelaUser,
+    ) -> Result<String> {
+        put_to_pixela(self, &client, pixela_user.token()).await
     }
     pub fn progress(&self) -> &Progress {
         &self.progress
@@ -48,18 +58,13 @@ impl ComplexPixel {
             .unwrap()
             .to_string()
     }
+    /// The quantity in the graph's unit, whole numbers for int graphs.
     pub fn sendable_string(&self) -> String {
-        match (self.subject.unit(), self.subject.data_type()) {
-            (&SubjectUnit::Hours, &SubjectDataType::Int) => {
-                format!("{}", self.progress.hours_floored(&SubjectUnit::Minutes))
-            }
-            (&SubjectUnit::Hours, &SubjectDataType::Float) => {
-                format!("{}", self.progress.hours(&SubjectUnit::Minutes))
-            }
-            (&SubjectUnit::Minutes, &SubjectDataType::Int) => {
-                format!("{}", self.progress.minutes(&SubjectUnit::Minutes))
-            }
-            _ => panic!("Not allowed"),
+        let unit = self.subject.unit();
+        let quantity = self.progress.convert(&unit.local(), unit);
+        match self.subject.data_type() {
+            SubjectDataType::Int => format!("{}", quantity.floor() as usize),
+            SubjectDataType::Float => format!("{}", misc::round_to_precision(quantity, 2)),
         }
     }
     pub fn display_string(&self) -> String {
@@ -67,17 +72,28 @@ impl ComplexPixel {
             (&SubjectUnit::Hours, &SubjectDataType::Float) => {
                 format!("{}h", self.progress.hours(&SubjectUnit::Minutes))
             }
-            (&SubjectUnit::Minutes, &SubjectDataType::Int)
-            | (&SubjectUnit::Hours, &SubjectDataType::Int) => {
+            (&SubjectUnit::Count, _) => format!("{}×", self.progress.get_as_int()),
+            (&SubjectUnit::Minutes, _) | (&SubjectUnit::Hours, &SubjectDataType::Int) => {
                 format!("{}m", self.progress.minutes(&SubjectUnit::Minutes))
             }
-            _ => panic!("Not allowed"),
         }
     }
 
     pub fn subject(&self) -> &Subject {
         &self.subject
     }
+    pub fn task(&self) -> Option<&str> {
+        self.task.as_deref()
+    }
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+    pub fn set_id(&mut self, id: String) {
+        self.id = Some(id);
+    }
+    pub fn set_task(&mut self, task: Option<String>) {
+        self.task = task;
+    }
     pub fn into_aggregate(&mut self) {
         if self.subject().graph_name().contains("Aggregated") {
             return;
@@ -86,6 +102,20 @@ impl ComplexPixel {
         self.subject.set_graph_name(new_name);
     }
 
+    /// The pixel as one of the `aggregate` graph, `None` for the aggregate's own pixels and
+    /// for counts going to a time graph or the other way round. The minutes are converted
+    /// to the aggregate's unit when it's written.
+    pub fn mirrored(&self, aggregate: &Subject) -> Option<ComplexPixel> {
+        if self.subject.url() == aggregate.url() || self.subject.is_count() != aggregate.is_count()
+        {
+            return None;
+        }
+        let mut pixel =
+            ComplexPixel::new(self.progress.clone(), aggregate.clone(), self.date.clone());
+        pixel.into_aggregate();
+        Some(pixel)
+    }
+
     pub(crate) fn is_rounded(&self) -> IsRounded {
         match (self.subject().unit(), self.subject().data_type()) {
             (SubjectUnit::Hours, SubjectDataType::Int) => {
@@ -108,6 +138,8 @@ pub enum IsRounded {
 }
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
 
     #[test]
@@ -119,4 +151,68 @@ mod tests {
         let pixel = ComplexPixel::new(progress, subject, "80".to_string());
         assert_eq!(pixel.is_rounded(), IsRounded::No)
     }
+
+    fn sent(unit: &str, data_type: SubjectDataType, progress: Progress) -> String {
+        let aliases = HashMap::from([("commits".to_string(), "count".to_string())]);
+        let mut subject = Subject::new_dummy();
+        subject.set_unit(SubjectUnit::parse(unit, &aliases).unwrap());
+        subject.set_datatype(data_type);
+        ComplexPixel::new(progress, subject, "20240304".into()).sendable_string()
+    }
+    fn subject(unit: SubjectUnit, url: &str) -> Subject {
+        let mut subject = Subject::new_dummy();
+        subject.set_unit(unit);
+        subject.set_url(url.into());
+        subject
+    }
+    fn mirror(unit: SubjectUnit, url: &str) -> Option<ComplexPixel> {
+        let aggregate = subject(SubjectUnit::Hours, "all-study");
+        ComplexPixel::new(Progress::Int(90), subject(unit, url), "2024/03/04".into())
+            .mirrored(&aggregate)
+    }
+
+    #[test]
+    fn int_hour_graphs_get_whole_hours() {
+        assert_eq!(sent("hrs", SubjectDataType::Int, Progress::Int(130)), "2");
+    }
+    #[test]
+    fn float_graphs_get_fractions() {
+        assert_eq!(
+            sent("hours", SubjectDataType::Float, Progress::Int(90)),
+            "1.5"
+        );
+        assert_eq!(
+            sent("Mins", SubjectDataType::Float, Progress::Int(25)),
+            "25"
+        );
+    }
+    #[test]
+    fn aliased_count_graphs_are_sent_as_is() {
+        assert_eq!(sent("commits", SubjectDataType::Int, Progress::Int(3)), "3");
+    }
+    #[test]
+    fn unknown_units_are_not_parsed() {
+        assert!(SubjectUnit::parse("km", &HashMap::new()).is_none());
+    }
+    #[test]
+    fn remote_values_turn_into_minutes() {
+        // a remote hour graph's 1.5 is 90 minutes, counts don't turn into time
+        assert_eq!(Progress::Float(1.5).minutes(&SubjectUnit::Hours), 90);
+        assert_eq!(Progress::Int(4).minutes(&SubjectUnit::Count), 4);
+    }
+    #[test]
+    fn mirrors_into_the_aggregates_unit() {
+        // 90 minutes of a minute graph are one whole hour of the aggregate
+        let mirrored = mirror(SubjectUnit::Minutes, "maths").unwrap();
+        assert_eq!(mirrored.subject().graph_name(), "(Aggregated) None");
+        assert_eq!(mirrored.sendable_string(), "1");
+    }
+    #[test]
+    fn counts_are_not_mirrored() {
+        assert!(mirror(SubjectUnit::Count, "commits").is_none());
+    }
+    #[test]
+    fn the_aggregate_is_not_mirrored_into_itself() {
+        assert!(mirror(SubjectUnit::Hours, "all-study").is_none());
+    }
 }
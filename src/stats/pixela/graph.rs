@@ -1,22 +1,24 @@
 use core::f64;
-use std::{collections::HashMap, fs};
+use std::{collections::HashMap, fs, path::PathBuf};
 
-use chrono::{Duration, NaiveDate};
-use directories::ProjectDirs;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate};
 use ratatui::{style::Style, widgets::Bar};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::{
-    error::{Error, PixelaResponseError, Result, SettingsError},
+    error::{PixelaResponseError, Result, SettingsError},
+    settings::data_dir,
     ui::{BLUE, GREEN, RED, YELLOW},
+    GRAPH_CACHE_MAX_AGE_MINUTES,
 };
 
 use super::{
     complex_pixel::ComplexPixel,
+    helpers::response_error,
     pixela_user::PixelaUser,
-    subjects::{Progress, Subject},
+    subjects::{Progress, Subject, SubjectUnit},
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,55 +26,144 @@ pub struct Graph {
     data: Vec<DataPoint>,
     color: PixelaColors,
     subject: Subject,
+    #[serde(default)]
+    range: GraphRange,
+    #[serde(default)]
+    page: i64,
+    /// rfc3339, missing in graphs saved by older versions
+    #[serde(default)]
+    downloaded_at: Option<String>,
+    /// the last refresh didn't reach Pixela, so this is all there is
+    #[serde(skip)]
+    refresh_failed: bool,
 }
 impl Graph {
+    fn cache_dir() -> Result<PathBuf> {
+        Ok(data_dir()?.join("graphs"))
+    }
+    /// Keeps the last downloaded graph of the subject, so it shows while offline.
     pub fn save_graph(&self) -> Result<()> {
-        let path = ProjectDirs::from("romodoro", "mejxedev", "romodoro")
-            .ok_or(SettingsError::HomeDirNotFound)?;
-        let path = path.config_dir();
-        let path = path.join("graphs");
+        let path = Graph::cache_dir()?;
         if !path.exists() {
             fs::create_dir_all(&path)?
         }
-        let filename = format!("{}.json", self.subject.graph_name());
-        let json_string: String = serde_json::to_string(self).expect("format should be correct");
+        let filename = format!("{}.json", self.subject.id());
+        let json_string: String = serde_json::to_string(self)?;
         fs::write(path.join(filename), json_string)?;
         Ok(())
     }
-    fn load_graph(name: String) -> Result<Graph> {
-        let path = ProjectDirs::from("romodoro", "mejxedev", "romodoro")
-            .ok_or(SettingsError::HomeDirNotFound)?;
-        let path = path.config_dir();
-        let path = path.join("graphs");
-        if !path.exists() {
-            fs::create_dir_all(&path)?;
-            return Err(Error::SettingsError(SettingsError::LoadError(
-                "User does not have a progress file".to_string(),
-            )));
-        };
-        if let Ok(data) = &fs::read_to_string(path.join(format!("{}.json", name))) {
-            let val: serde_json::Result<Graph> = serde_json::from_str(data);
-            if let Ok(val) = val {
-                Ok(val)
-            } else {
-                Err(Error::SettingsError(SettingsError::LoadError(
-                    "Cannot load your graph".to_string(),
-                )))
-            }
-        } else {
-            Err(Error::SettingsError(SettingsError::LoadError(
-                "Cannot load your graph".to_string(),
-            )))
-        }
+    fn load_graph(path: PathBuf) -> Result<Graph> {
+        let data = fs::read_to_string(path).map_err(|_| {
+            SettingsError::LoadError("User does not have a cached graph".to_string())
+        })?;
+        serde_json::from_str(&data)
+            .map_err(|_| SettingsError::LoadError("Cannot load your graph".to_string()).into())
+    }
+    pub fn cached(subject: &Subject) -> Option<Graph> {
+        let path = Graph::cache_dir().ok()?;
+        Graph::load_graph(path.join(format!("{}.json", subject.id()))).ok()
+    }
+    /// The most recently downloaded graph of any subject, shown on startup.
+    pub fn latest_cached() -> Option<Graph> {
+        let path = Graph::cache_dir().ok()?;
+        fs::read_dir(path)
+            .ok()?
+            .flatten()
+            .filter_map(|entry| Graph::load_graph(entry.path()).ok())
+            .max_by_key(|graph| graph.downloaded_at())
+    }
+    pub fn shows(&self, subject: &Subject, range: GraphRange, page: i64) -> bool {
+        self.subject.id() == subject.id() && self.range == range && self.page == page
+    }
+    pub fn downloaded_at(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(self.downloaded_at.as_ref()?).ok()
+    }
+    pub fn age(&self) -> Option<Duration> {
+        self.downloaded_at()
+            .map(|downloaded_at| Local::now().fixed_offset() - downloaded_at)
+    }
+    /// Old enough that it should be downloaded again.
+    pub fn is_stale(&self) -> bool {
+        self.age()
+            .is_none_or(|age| age > Duration::minutes(GRAPH_CACHE_MAX_AGE_MINUTES))
+    }
+    pub fn refresh_failed(&self) -> bool {
+        self.refresh_failed
+    }
+    pub fn set_refresh_failed(&mut self, refresh_failed: bool) {
+        self.refresh_failed = refresh_failed;
+    }
+    pub fn range(&self) -> GraphRange {
+        self.range
+    }
+    pub fn page(&self) -> i64 {
+        self.page
     }
     pub async fn download_graph(
         user: PixelaUser,
         client: Client,
         subject: Subject,
+        range: GraphRange,
+        page: i64,
     ) -> Result<Graph> {
-        // gets 30 latests pixels
-        let start_date = (chrono::Local::now() - Duration::days(29)).format("%Y%m%d");
-        let today = (chrono::Local::now() + Duration::days(2)).format("%Y%m%d");
+        let (start, end) = range.window(page, chrono::Local::now().date_naive());
+        let mut graph = Graph::download_between(user, client, subject, start, end).await?;
+        graph.range = range;
+        graph.page = page;
+        Ok(graph)
+    }
+    /// Downloads the last `days` days, Pixela allows up to a year in one request.
+    pub async fn download_days(
+        user: PixelaUser,
+        client: Client,
+        subject: Subject,
+        days: i64,
+    ) -> Result<Graph> {
+        let end = chrono::Local::now().date_naive();
+        let start = end - Duration::days(days - 1);
+        Graph::download_between(user, client, subject, start, end).await
+    }
+    /// Downloads `start..=end` a year at a time, so any span fits.
+    pub async fn download_span(
+        user: PixelaUser,
+        client: Client,
+        subject: Subject,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Graph> {
+        let mut chunk_end = (start + Duration::days(364)).min(end);
+        let mut graph = Graph::download_between(
+            user.clone(),
+            client.clone(),
+            subject.clone(),
+            start,
+            chunk_end,
+        )
+        .await?;
+        while chunk_end < end {
+            let chunk_start = chunk_end + Duration::days(1);
+            chunk_end = (chunk_start + Duration::days(364)).min(end);
+            let chunk = Graph::download_between(
+                user.clone(),
+                client.clone(),
+                subject.clone(),
+                chunk_start,
+                chunk_end,
+            )
+            .await?;
+            graph.data.extend(chunk.data);
+        }
+        Ok(graph)
+    }
+    async fn download_between(
+        user: PixelaUser,
+        client: Client,
+        subject: Subject,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Graph> {
+        let start_date = start.format("%Y%m%d");
+        let today = (end + Duration::days(2)).format("%Y%m%d");
         let request = client
             .get(format!("{}/pixels", subject.url()))
             .query(
@@ -93,8 +184,12 @@ impl Graph {
                         subject: subject.clone(),
                         data: data_points,
                         color: subject.color().clone(), // TODO: Hardcoded
+                        range: GraphRange::default(),
+                        page: 0,
+                        downloaded_at: Some(Local::now().to_rfc3339()),
+                        refresh_failed: false,
                     };
-                    g.fill_empty_days();
+                    g.fill_empty_days(start, end);
                     Ok(g)
                 } else {
                     Err(PixelaResponseError::FatalError(
@@ -108,11 +203,7 @@ impl Graph {
                 }
             }
 
-            false => Err(PixelaResponseError::FatalError(
-                request.error_for_status().expect_err("failed").to_string(),
-                status,
-            )
-            .into()),
+            false => Err(response_error(request).await),
         }
     }
     pub fn datapoints_into_f64(&self) -> Vec<(f64, f64)> {
@@ -129,7 +220,7 @@ impl Graph {
         });
         days
     }
-    fn fill_empty_days(&mut self) {
+    fn fill_empty_days(&mut self, start: NaiveDate, end: NaiveDate) {
         let data_map: HashMap<NaiveDate, Progress> = self
             .data
             .iter()
@@ -141,9 +232,8 @@ impl Graph {
             })
             .collect();
         let mut result: Vec<DataPoint> = vec![];
-        let today = chrono::Local::now().naive_local().date();
-        let mut start_date = today - Duration::days(29);
-        while start_date <= today {
+        let mut start_date = start;
+        while start_date <= end {
             let prgrs = data_map.get(&start_date).unwrap_or(&Progress::Int(0));
             result.push(DataPoint {
                 date: start_date.format("%Y%m%d").to_string(),
@@ -173,22 +263,57 @@ impl Graph {
         }
         ((0.0, 0.0), (0.0, 0.0))
     }
-    pub fn get_max_quantity(&self) -> u64 {
-        self.data().iter().fold(u64::MIN, |current, next| {
-            current.max(next.quantity_as_u64(&self.subject))
-        })
-    }
-    pub fn into_bars(&self) -> Vec<Bar<'_>> {
-        self.data()
+    pub fn get_max_quantity(&self, aggregation: Aggregation) -> u64 {
+        self.aggregate(aggregation)
             .iter()
-            .map(|dp| {
+            .map(|(_, hours)| *hours)
+            .max()
+            .unwrap_or_default()
+    }
+    pub fn into_bars(&self, aggregation: Aggregation) -> Vec<Bar<'_>> {
+        self.aggregate(aggregation)
+            .into_iter()
+            .map(|(label, hours)| {
                 Bar::default()
-                    .value(dp.quantity_as_u64(self.subject()))
-                    .label(dp.label())
+                    .value(hours)
+                    .label(label)
                     .style(Style::default().fg(self.subject().color().to_ratatui_color()))
             })
             .collect()
     }
+    /// Hours (or counts) per day, week or month, oldest first.
+    pub fn aggregate(&self, aggregation: Aggregation) -> Vec<(String, u64)> {
+        let mut buckets: Vec<(NaiveDate, f64)> = vec![];
+        for dp in self.data() {
+            let Some(day) = dp.day() else {
+                continue;
+            };
+            let bucket = aggregation.bucket(day);
+            let hours = dp
+                .quantity
+                .convert(self.subject.unit(), &SubjectUnit::Hours);
+            match buckets.last_mut() {
+                Some((last, total)) if *last == bucket => *total += hours,
+                _ => buckets.push((bucket, hours)),
+            }
+        }
+        buckets
+            .into_iter()
+            .map(|(bucket, hours)| (aggregation.label(bucket), hours.floor() as u64))
+            .collect()
+    }
+    /// Replaces one day after it was edited on Pixela.
+    pub fn set_quantity(&mut self, day: NaiveDate, quantity: Progress) {
+        if let Some(dp) = self.data.iter_mut().find(|dp| dp.day() == Some(day)) {
+            dp.quantity = quantity;
+        }
+    }
+    /// First and last day the graph covers.
+    pub fn span(&self) -> Option<(NaiveDate, NaiveDate)> {
+        let first = self.data.first()?.day()?;
+        let last = self.data.last()?.day()?;
+        Some((first, last))
+    }
 
     pub fn data(&self) -> &[DataPoint] {
         &self.data
@@ -210,14 +335,14 @@ pub struct DataPoint {
 impl DataPoint {
     pub fn into_f64(&self, subject: &Subject) -> (f64, f64) {
         let date_f64: f64 = self.date.parse().expect("should be fine");
-        let progress_f64: f64 = self.quantity.minutes(subject.unit()) as f64 / 60.0;
-        (date_f64, progress_f64)
+        (date_f64, self.quantity_as_f64(subject))
     }
+    /// In hours, or the count for count graphs.
     pub fn quantity_as_f64(&self, subject: &Subject) -> f64 {
-        self.quantity.minutes(subject.unit()) as f64 / 60.0
+        self.quantity.convert(subject.unit(), &SubjectUnit::Hours)
     }
     pub fn quantity_as_u64(&self, subject: &Subject) -> u64 {
-        self.quantity.minutes(subject.unit()) as u64 / 60
+        self.quantity_as_f64(subject).floor() as u64
     }
     pub fn label(&self) -> String {
         self.date.split_at(6).1.into()
@@ -225,6 +350,12 @@ impl DataPoint {
     pub fn date(&self) -> &str {
         &self.date
     }
+    pub fn day(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.date, "%Y%m%d").ok()
+    }
+    pub fn quantity(&self) -> &Progress {
+        &self.quantity
+    }
 }
 impl From<ComplexPixel> for DataPoint {
     fn from(value: ComplexPixel) -> Self {
@@ -234,6 +365,102 @@ impl From<ComplexPixel> for DataPoint {
         }
     }
 }
+/// How much time one graph shows, pages go back from today.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphRange {
+    Week,
+    #[default]
+    Month,
+    Quarter,
+    Year,
+}
+impl GraphRange {
+    pub fn days(&self) -> i64 {
+        match self {
+            GraphRange::Week => 7,
+            GraphRange::Month => 30,
+            GraphRange::Quarter => 91,
+            // the most Pixela returns in one request
+            GraphRange::Year => 365,
+        }
+    }
+    pub fn next(&self) -> GraphRange {
+        match self {
+            GraphRange::Week => GraphRange::Month,
+            GraphRange::Month => GraphRange::Quarter,
+            GraphRange::Quarter => GraphRange::Year,
+            GraphRange::Year => GraphRange::Week,
+        }
+    }
+    /// First and last day shown `page` ranges before the current one.
+    pub fn window(&self, page: i64, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let end = today - Duration::days(self.days() * page);
+        (end - Duration::days(self.days() - 1), end)
+    }
+}
+impl std::fmt::Display for GraphRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            GraphRange::Week => "Week",
+            GraphRange::Month => "Month",
+            GraphRange::Quarter => "Quarter",
+            GraphRange::Year => "Year",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// How many days go into one bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Day,
+    Week,
+    Month,
+}
+impl Aggregation {
+    /// The finest grouping that needs at most `max_bars` bars.
+    pub fn fitting(start: NaiveDate, end: NaiveDate, max_bars: usize) -> Aggregation {
+        [Aggregation::Day, Aggregation::Week]
+            .into_iter()
+            .find(|aggregation| aggregation.bars(start, end) <= max_bars)
+            .unwrap_or(Aggregation::Month)
+    }
+    fn bars(&self, start: NaiveDate, end: NaiveDate) -> usize {
+        let mut buckets: Vec<NaiveDate> = start
+            .iter_days()
+            .take_while(|day| *day <= end)
+            .map(|day| self.bucket(day))
+            .collect();
+        buckets.dedup();
+        buckets.len()
+    }
+    fn bucket(&self, day: NaiveDate) -> NaiveDate {
+        match self {
+            Aggregation::Day => day,
+            Aggregation::Week => day - Duration::days(day.weekday().num_days_from_monday() as i64),
+            Aggregation::Month => day.with_day(1).unwrap_or(day),
+        }
+    }
+    fn label(&self, bucket: NaiveDate) -> String {
+        match self {
+            Aggregation::Day => bucket.format("%d").to_string(),
+            Aggregation::Week => bucket.format("W%V").to_string(),
+            Aggregation::Month => bucket.format("%b").to_string(),
+        }
+    }
+}
+impl std::fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Aggregation::Day => "day",
+            Aggregation::Week => "week",
+            Aggregation::Month => "month",
+        };
+        write!(f, "{name}")
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, PartialOrd, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum PixelaColors {
@@ -256,3 +483,52 @@ impl PixelaColors {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+    /// 90 minutes on the 4th of March, filled from the end of February
+    fn graph() -> Graph {
+        let mut graph = Graph {
+            data: vec![DataPoint {
+                date: "20240304".into(),
+                quantity: Progress::Int(90),
+            }],
+            color: PixelaColors::Shibafu,
+            subject: Subject::new_dummy(),
+            range: GraphRange::Week,
+            page: 0,
+            downloaded_at: None,
+            refresh_failed: false,
+        };
+        graph.fill_empty_days(date(2, 28), date(3, 10));
+        graph
+    }
+
+    #[test]
+    fn pages_go_back_a_whole_range() {
+        let (start, end) = GraphRange::Week.window(1, date(3, 31));
+        assert_eq!((start, end), (date(3, 18), date(3, 24)));
+    }
+    #[test]
+    fn wide_ranges_are_aggregated_to_fit() {
+        let (start, end) = GraphRange::Year.window(0, date(3, 31));
+        assert_eq!(Aggregation::fitting(start, end, 400), Aggregation::Day);
+        assert_eq!(Aggregation::fitting(start, end, 60), Aggregation::Week);
+        assert_eq!(Aggregation::fitting(start, end, 20), Aggregation::Month);
+    }
+    #[test]
+    fn empty_days_are_filled() {
+        assert_eq!(graph().data().len(), 12);
+    }
+    #[test]
+    fn weeks_sum_their_days() {
+        let weeks = graph().aggregate(Aggregation::Week);
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[1], ("W10".to_string(), 1));
+    }
+}
@@ -0,0 +1,60 @@
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::error::Result;
+
+use super::{helpers::response_error, pixela_user::PixelaUser, subjects::Subject};
+
+/// What Pixela's `/stats` endpoint knows about a whole graph, in the graph's unit.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GraphStats {
+    #[serde(skip)]
+    subject_id: String,
+    pub total_pixels_count: usize,
+    pub max_quantity: f64,
+    pub min_quantity: f64,
+    pub total_quantity: f64,
+    pub avg_quantity: f64,
+    pub todays_quantity: f64,
+}
+impl GraphStats {
+    pub async fn download(
+        user: PixelaUser,
+        client: Client,
+        subject: Subject,
+    ) -> Result<GraphStats> {
+        let request = client
+            .get(format!("{}/stats", subject.url()))
+            .header("X-USER-TOKEN", user.token())
+            .send()
+            .await?;
+        let status = request.status();
+        match status.is_success() {
+            true => {
+                let mut stats: GraphStats = request.json().await?;
+                stats.subject_id = subject.id().to_string();
+                Ok(stats)
+            }
+            false => Err(response_error(request).await),
+        }
+    }
+    pub fn is_for(&self, subject: &Subject) -> bool {
+        self.subject_id == subject.id()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pixela_stats() {
+        let json = r#"{"totalPixelsCount":4,"maxQuantity":5,"maxDate":"20240304","minQuantity":1.5,
+            "totalQuantity":12.5,"avgQuantity":3.13,"todaysQuantity":0,"yesterdayQuantity":2}"#;
+        let stats: GraphStats = serde_json::from_str(json).unwrap();
+        assert_eq!(stats.total_pixels_count, 4);
+        assert_eq!(stats.min_quantity, 1.5);
+        assert_eq!(stats.todays_quantity, 0.0);
+    }
+}
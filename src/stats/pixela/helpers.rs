@@ -1,64 +1,51 @@
+use std::{future::Future, time::Duration};
+
+use reqwest::StatusCode;
+use tokio::time;
+
 use crate::{
-    error::{PixelaResponseError, Result, StatsError},
-    MIN_MINS_INCREMENT,
+    error::{Error, PixelaResponseError, Result, StatsError},
+    MIN_MINS_INCREMENT, PIXELA_MAX_ATTEMPTS, PIXELA_RETRY_BASE_MS, PIXELA_RETRY_MAX_MS,
 };
 
 use super::{
     complex_pixel::ComplexPixel,
-    subjects::{SubjectDataType, SubjectUnit},
+    subjects::{Progress, Subject, SubjectDataType, SubjectUnit},
     utils::{PixelaResponse, PixelaValue},
 };
 
+/// The quantity of one day's pixel, zero when nothing was sent that day.
 pub async fn get_from_pixela(
-    pixel: &ComplexPixel,
+    subject: &Subject,
+    date: &str,
     client: &reqwest::Client,
     api_key: &str,
-) -> Result<usize> {
+) -> Result<Progress> {
     let request = client
-        .get(format!(
-            "{}/{}",
-            pixel.subject().url(),
-            pixel.date_pixela_formatted()
-        ))
+        .get(format!("{}/{}", subject.url(), date))
         .header("X-USER-TOKEN", api_key);
     let request = request.send().await?;
     let status = request.status();
     match status.is_success() {
         true => {
             let response = request.json::<PixelaValue>().await?;
-            Ok(response.quantity().parse().map_err(|_| {
-                PixelaResponseError::FatalError(
-                    "Failed parsing".into(),
-                    reqwest::StatusCode::BAD_REQUEST,
-                )
-            })?)
-        }
-        false => {
-            if status == reqwest::StatusCode::SERVICE_UNAVAILABLE
-                || status == reqwest::StatusCode::INTERNAL_SERVER_ERROR
-            {
-                Err(PixelaResponseError::RetryableError("Server is busy".into(), status).into())
-            } else if status == reqwest::StatusCode::NOT_FOUND {
-                return Ok(0);
-            } else {
-                Err(PixelaResponseError::FatalError(
-                    format!("Request denied: {}", request.text().await.unwrap()),
-                    status,
-                )
-                .into())
-            }
+            Ok(response.quantity().clone())
         }
+        false if status == StatusCode::NOT_FOUND => Ok(Progress::Int(0)),
+        false => Err(response_error(request).await),
     }
 }
 
-pub async fn send_to_pixela(
+/// Sets the day to the pixel's value, so sending it again changes nothing.
+/// Returns Pixela's message.
+pub async fn put_to_pixela(
     pixel: &ComplexPixel,
     client: &reqwest::Client,
     api_key: &str,
-) -> Result<()> {
+) -> Result<String> {
     let subject_url = pixel.subject().url();
     let date = pixel.date_pixela_formatted();
-    let url = format!("{subject_url}/{date}/add");
+    let url = format!("{subject_url}/{date}");
     let request = client
         .put(url)
         .json(&serde_json::json!({
@@ -67,24 +54,68 @@ pub async fn send_to_pixela(
         .header("X-USER-TOKEN", api_key)
         .send()
         .await?;
-    let status = request.status();
-    match status.is_success() {
+    match request.status().is_success() {
         true => {
-            request.json::<PixelaResponse>().await?;
-            Ok(())
+            let response = request.json::<PixelaResponse>().await?;
+            Ok(response.message().to_string())
         }
-        false => {
-            if status == reqwest::StatusCode::SERVICE_UNAVAILABLE
-                || status == reqwest::StatusCode::INTERNAL_SERVER_ERROR
-            {
-                Err(PixelaResponseError::RetryableError("Server is busy".into(), status).into())
-            } else {
-                Err(PixelaResponseError::FatalError(
-                    format!("Request denied: {}", request.text().await.unwrap()),
-                    status,
-                )
-                .into())
+        false => Err(response_error(request).await),
+    }
+}
+/// The error of a failed request. Rejections and a busy server are retryable, see `retried`.
+pub async fn response_error(response: reqwest::Response) -> Error {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    body_error(status, &body)
+}
+fn body_error(status: StatusCode, body: &str) -> Error {
+    let parsed = serde_json::from_str::<PixelaResponse>(body).ok();
+    match parsed {
+        Some(parsed) if parsed.is_rejected() => {
+            PixelaResponseError::RetryableError(parsed.message().to_string(), status).into()
+        }
+        _ if status == StatusCode::SERVICE_UNAVAILABLE
+            || status == StatusCode::INTERNAL_SERVER_ERROR =>
+        {
+            PixelaResponseError::RetryableError("Server is busy".into(), status).into()
+        }
+        Some(parsed) => {
+            PixelaResponseError::FatalError(format!("Request denied: {}", parsed.message()), status)
+                .into()
+        }
+        None => PixelaResponseError::FatalError(format!("Request denied: {body}"), status).into(),
+    }
+}
+/// How long to wait before trying for the `attempt + 1`th time, doubling up to a limit.
+pub fn backoff(attempt: u32) -> Duration {
+    let wait = PIXELA_RETRY_BASE_MS.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+    Duration::from_millis(wait.min(PIXELA_RETRY_MAX_MS))
+}
+/// Sends `request` again while it's retryable, up to `PIXELA_MAX_ATTEMPTS` times.
+/// Returns the result and how often Pixela turned it down.
+pub async fn retried<T, F, Fut>(mut request: F) -> (Result<T>, usize)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match request().await {
+            Err(Error::PixelaResponseError(PixelaResponseError::RetryableError(
+                message,
+                status,
+            ))) => {
+                if attempt >= PIXELA_MAX_ATTEMPTS {
+                    let message = format!("Gave up after {attempt} tries: {message}");
+                    return (
+                        Err(PixelaResponseError::FatalError(message, status).into()),
+                        attempt as usize,
+                    );
+                }
+                time::sleep(backoff(attempt)).await;
+                attempt += 1;
             }
+            result => return (result, attempt as usize - 1),
         }
     }
 }
@@ -92,11 +123,6 @@ pub fn check_if_quantity_is_big_enough(pixel: &ComplexPixel) -> Result<()> {
     let unit = pixel.subject().unit().clone();
     let dtype = pixel.subject().data_type().clone();
     let invalid = match (unit, dtype) {
-        (SubjectUnit::Hours, SubjectDataType::Int)
-            if pixel.progress().hours(&SubjectUnit::Minutes) < 1.0 =>
-        {
-            true
-        }
         (SubjectUnit::Minutes, SubjectDataType::Int)
             if pixel.progress().minutes(&SubjectUnit::Minutes) < MIN_MINS_INCREMENT =>
         {
@@ -107,6 +133,8 @@ pub fn check_if_quantity_is_big_enough(pixel: &ComplexPixel) -> Result<()> {
         {
             true
         }
+        (SubjectUnit::Count, _) if pixel.progress().get_as_int() == 0 => true,
+        // int hour graphs take anything, the upload ledger keeps what doesn't make an hour
         _ => false,
     };
     if invalid {
@@ -114,3 +142,47 @@ pub fn check_if_quantity_is_big_enough(pixel: &ComplexPixel) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn rejections_are_sent_again_with_growing_waits() {
+        assert!(backoff(1) < backoff(2));
+        assert_eq!(backoff(30), Duration::from_millis(PIXELA_RETRY_MAX_MS));
+
+        let tries = Cell::new(0);
+        let (result, rejections) = retried(|| {
+            tries.set(tries.get() + 1);
+            let tried = tries.get();
+            async move {
+                match tried < 3 {
+                    true => Err(PixelaResponseError::RetryableError(
+                        "Please retry this request.".into(),
+                        StatusCode::SERVICE_UNAVAILABLE,
+                    )
+                    .into()),
+                    false => Ok(tried),
+                }
+            }
+        })
+        .await;
+        assert_eq!((result.unwrap(), rejections), (3, 2));
+    }
+    #[test]
+    fn only_rejected_requests_are_asked_again() {
+        let rejected = r#"{"message":"Please retry this request. Your request for some APIs will be rejected 25% of the time because you are not a Pixela supporter. If you are interested in being a Pixela supporter, please see: https://github.com/a-know/Pixela/wiki/How-to-support-Pixela-by-Patreon-%EF%BC%8F-Use-Limited-Features","isSuccess":false,"isRejected":true}"#;
+        let denied = r#"{"message":"User `a-know` does not exist or the token is wrong.","isSuccess":false}"#;
+        let parse = |body| serde_json::from_str::<PixelaResponse>(body).unwrap();
+        assert!(parse(rejected).is_rejected());
+        assert!(!parse(denied).is_rejected());
+
+        let error = body_error(StatusCode::SERVICE_UNAVAILABLE, rejected);
+        assert!(error.is_transient());
+        assert!(error.to_string().contains("Please retry this request"));
+        assert!(!body_error(StatusCode::BAD_REQUEST, denied).is_transient());
+    }
+}
@@ -0,0 +1,177 @@
+use chrono::{Duration, Local, NaiveDate, NaiveTime};
+use strum_macros::Display;
+
+use crate::stats::session::{Phase, Session};
+
+use super::{
+    graph::Graph,
+    subjects::{Minutes, Subject, SubjectDataType, SubjectUnit},
+};
+
+/// `Session::source` of the sessions a pull adds.
+pub const PULL_SOURCE: &str = "pixela";
+
+/// Where a day's minutes were logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum DayOrigin {
+    /// only in the local history, not on Pixela (yet)
+    Local,
+    /// only on Pixela, logged on another machine or on the website
+    Remote,
+    Both,
+}
+
+/// One day of one subject after the merge, in minutes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedDay {
+    pub subject_name: String,
+    pub date: NaiveDate,
+    /// minutes of sessions recorded on this machine
+    pub local: Minutes,
+    pub remote: Minutes,
+    pub origin: DayOrigin,
+}
+
+/// The pulled graphs compared to the history, with the sessions that fill the gaps.
+#[derive(Debug, Default)]
+pub struct HistoryMerge {
+    pub days: Vec<MergedDay>,
+    /// what Pixela has on top of the history, one session per day
+    pub sessions: Vec<Session>,
+}
+impl HistoryMerge {
+    pub fn new(history: &[Session], graphs: &[Graph]) -> HistoryMerge {
+        let mut merge = HistoryMerge::default();
+        for graph in graphs {
+            let subject = graph.subject();
+            // blocks don't say how long they were
+            if subject.is_count() {
+                continue;
+            }
+            let remote = graph
+                .data()
+                .iter()
+                .filter_map(|point| Some((point.day()?, point.quantity().minutes(subject.unit()))));
+            merge.merge_subject(history, subject, remote);
+        }
+        merge.days.sort_by_key(|day| std::cmp::Reverse(day.date));
+        merge
+    }
+    fn merge_subject(
+        &mut self,
+        history: &[Session],
+        subject: &Subject,
+        remote: impl Iterator<Item = (NaiveDate, Minutes)>,
+    ) {
+        // hour graphs only store whole hours, less than that missing is rounding
+        let granularity = match (subject.unit(), subject.data_type()) {
+            (SubjectUnit::Hours, SubjectDataType::Int) => 60,
+            _ => 1,
+        };
+        for (date, remote) in remote {
+            let (mut local, mut pulled) = (0, 0);
+            for session in history.iter().filter(|session| {
+                session.phase() == Phase::Work
+                    && session.subject_id() == Some(subject.id())
+                    && session.start().date_naive() == date
+            }) {
+                let minutes = (session.duration() / 60) as Minutes;
+                match session.source() == Some(PULL_SOURCE) {
+                    true => pulled += minutes,
+                    false => local += minutes,
+                }
+            }
+            let origin = match (local > 0, remote > 0 || pulled > 0) {
+                (true, true) => DayOrigin::Both,
+                (true, false) => DayOrigin::Local,
+                (false, true) => DayOrigin::Remote,
+                (false, false) => continue,
+            };
+            let missing = remote.saturating_sub(local + pulled);
+            if missing >= granularity {
+                // pulled minutes are laid out from noon on, one after another
+                let start = date
+                    .and_time(NaiveTime::from_hms_opt(12, 0, 0).expect("noon exists"))
+                    .and_local_timezone(Local)
+                    .earliest()
+                    .map(|start| start + Duration::minutes(pulled as i64));
+                if let Some(start) = start {
+                    self.sessions.push(Session::imported(
+                        start,
+                        start + Duration::minutes(missing as i64),
+                        Some(subject.id().to_string()),
+                        Some(subject.graph_name().to_string()),
+                        PULL_SOURCE,
+                    ));
+                }
+            }
+            self.days.push(MergedDay {
+                subject_name: subject.graph_name().to_string(),
+                date,
+                local,
+                remote,
+                origin,
+            });
+        }
+    }
+    pub fn count(&self, origin: DayOrigin) -> usize {
+        self.days.iter().filter(|day| day.origin == origin).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+    /// 50 minutes logged here on the 4th
+    fn history(subject: &Subject) -> Vec<Session> {
+        let start = Local.with_ymd_and_hms(2024, 3, 4, 10, 0, 0).unwrap();
+        vec![Session::imported(
+            start,
+            start + Duration::minutes(50),
+            Some(subject.id().to_string()),
+            None,
+            "timewarrior",
+        )]
+    }
+    /// Pixela has 80 on the 4th and 30 from elsewhere on the 5th
+    fn remote() -> Vec<(NaiveDate, Minutes)> {
+        vec![(day(3), 0), (day(4), 80), (day(5), 30)]
+    }
+
+    #[test]
+    fn days_are_marked_with_their_origin() {
+        let subject = Subject::new_dummy();
+        let mut merge = HistoryMerge::default();
+        merge.merge_subject(&history(&subject), &subject, remote().into_iter());
+        let origins: Vec<DayOrigin> = merge.days.iter().map(|day| day.origin).collect();
+        assert_eq!(origins, vec![DayOrigin::Both, DayOrigin::Remote]);
+    }
+    #[test]
+    fn only_missing_minutes_are_pulled() {
+        let subject = Subject::new_dummy();
+        let mut merge = HistoryMerge::default();
+        merge.merge_subject(&history(&subject), &subject, remote().into_iter());
+        let pulled: Vec<i64> = merge.sessions.iter().map(Session::duration).collect();
+        assert_eq!(pulled, vec![30 * 60, 30 * 60]);
+    }
+    #[test]
+    fn pulling_again_adds_nothing() {
+        let subject = Subject::new_dummy();
+        let mut history = history(&subject);
+        let mut merge = HistoryMerge::default();
+        merge.merge_subject(&history, &subject, remote().into_iter());
+        history.append(&mut merge.sessions);
+
+        let mut again = HistoryMerge::default();
+        again.merge_subject(&history, &subject, remote().into_iter());
+        assert!(again.sessions.is_empty());
+        assert_eq!(again.count(DayOrigin::Remote), 1);
+    }
+}
@@ -1,8 +1,14 @@
 pub mod complex_pixel;
 pub mod graph;
+pub mod graph_stats;
 pub mod helpers;
+pub mod history_pull;
 pub mod pixela_client;
 pub mod pixela_user;
+pub mod remote_pixel;
+pub mod send_preview;
 pub mod subjects;
 mod traits;
+pub mod upload_archive;
+pub mod upload_ledger;
 pub mod utils;
@@ -1,14 +1,23 @@
-use std::{collections::HashMap, fs, future::Future, time::Duration, vec};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs,
+    future::Future,
+    vec,
+};
 
 use crate::{
     error::{Error, FatalError, PixelaResponseError, Result, SettingsError, StatsError},
+    settings::data_dir,
     stats::{
+        import::ImportQueue,
         pixel::{Pixel, SimplePixel},
-        pixela::subjects::SubjectDataType,
+        pixela::subjects::TimeUnit,
+        sync::{PixelMerge, SyncFolder},
     },
+    PIXELA_CONCURRENT_REQUESTS, PIXELA_MAX_ATTEMPTS,
 };
 use chrono::{DateTime, Local};
-use directories::ProjectDirs;
 use futures::{stream, StreamExt};
 use reqwest::{Client, StatusCode};
 use serde_json::Value;
@@ -16,9 +25,14 @@ use tokio::time;
 
 use super::{
     complex_pixel::ComplexPixel,
-    graph::Graph,
+    graph::{Graph, GraphRange},
+    graph_stats::GraphStats,
+    helpers::{backoff, check_if_quantity_is_big_enough, get_from_pixela, response_error, retried},
     pixela_user::PixelaUser,
+    send_preview::SendPreview,
     subjects::{Progress, Subject, SubjectUnit},
+    upload_archive::{ArchivedUpload, UploadArchive},
+    upload_ledger::UploadLedger,
     utils::StatefulList,
 };
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -41,6 +55,53 @@ impl PixelaTabs {
     }
 }
 
+/// Each pixel of a batch with its outcome, and how often Pixela turned requests down.
+pub struct Batch<T> {
+    pub outcomes: Vec<(ComplexPixel, std::result::Result<T, FatalError>)>,
+    pub rejections: usize,
+}
+
+/// One line on how a batch of uploads went.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BatchSummary {
+    pub written: usize,
+    pub failed: usize,
+    pub rejections: usize,
+}
+impl<T> From<&Batch<T>> for BatchSummary {
+    fn from(batch: &Batch<T>) -> Self {
+        let written = batch
+            .outcomes
+            .iter()
+            .filter(|(_, outcome)| outcome.is_ok())
+            .count();
+        BatchSummary {
+            written,
+            failed: batch.outcomes.len() - written,
+            rejections: batch.rejections,
+        }
+    }
+}
+impl Display for BatchSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.written + self.failed, self.failed) {
+            (0, _) => write!(f, "No day had to be written")?,
+            (total, 0) => write!(f, "Wrote {} of {total} days", self.written)?,
+            (total, failed) => {
+                write!(f, "Wrote {} of {total} days, {failed} failed", self.written)?
+            }
+        }
+        if self.rejections > 0 {
+            write!(
+                f,
+                ". Pixela turned down {} requests, they were sent again",
+                self.rejections
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct PixelaClient {
     pub client: reqwest::Client,
@@ -52,9 +113,28 @@ pub struct PixelaClient {
     pixels_to_send: Vec<usize>,
     current_subject_index: usize,
     current_graph: Option<Graph>,
+    graph_range: GraphRange,
+    /// how many ranges back from today the graph starts
+    graph_page: i64,
+    graph_stats: Option<GraphStats>,
+    /// subject id and what Pixela has for it today
+    today_on_pixela: Option<(String, Progress)>,
+    sync: Option<SyncFolder>,
+    /// ids of pixels sent or deleted since the sync folder was last written
+    removed_pixels: Vec<String>,
+    /// graph unit -> minutes, hours or count
+    unit_aliases: HashMap<String, String>,
+    /// graphs with a unit nothing maps to, as "name (unit)"
+    untracked_graphs: Vec<String>,
+    /// a day's remainder that doesn't fit the graph goes on to the next day
+    carry_over: bool,
+    /// id of the graph every upload is also added to
+    aggregate_graph: Option<String>,
+    /// days the upload ledger keeps written days for, 0 keeps everything
+    retention_days: u32,
 }
 impl PixelaClient {
-    pub fn try_new(user: PixelaUser) -> Result<PixelaClient> {
+    pub fn try_new(user: PixelaUser, client: Client) -> Result<PixelaClient> {
         let pixel_res = PixelaClient::load_pixels(&user);
         let pixel_vec: Vec<Pixel> = match pixel_res {
             Ok(pixels) => pixels,
@@ -71,7 +151,7 @@ impl PixelaClient {
         let mut subjects: StatefulList<Subject> = StatefulList::default();
         subjects.push(Subject::new_dummy());
         Ok(PixelaClient {
-            client: Client::new(),
+            client,
             pixels,
             user,
             subjects,
@@ -79,7 +159,19 @@ impl PixelaClient {
             focused_pane: PixelaTabs::Subject,
             pixels_to_send: vec![0; 2 * pixel_len],
             current_subject_index: 0,
-            current_graph: None,
+            // the last graph from the cache shows before Pixela is even reached
+            current_graph: Graph::latest_cached(),
+            graph_range: GraphRange::default(),
+            graph_page: 0,
+            graph_stats: None,
+            today_on_pixela: None,
+            sync: None,
+            removed_pixels: Vec::new(),
+            unit_aliases: HashMap::new(),
+            untracked_graphs: Vec::new(),
+            carry_over: false,
+            aggregate_graph: None,
+            retention_days: 0,
         })
     }
     pub fn add_pixel(
@@ -87,18 +179,18 @@ impl PixelaClient {
         date: DateTime<Local>,
         subject: Option<Subject>,
         progress: Progress,
+        task: Option<String>,
     ) {
         if let Some(sub) = subject {
-            self.pixels.push(Pixel::Complex(ComplexPixel::new(
-                progress,
-                sub,
-                date.format("%Y/%m/%d %H:%M").to_string(),
-            )));
+            let mut pixel =
+                ComplexPixel::new(progress, sub, date.format("%Y/%m/%d %H:%M").to_string());
+            pixel.set_task(task);
+            self.pixels.push(Pixel::Complex(pixel));
         } else {
-            self.pixels.push(Pixel::Simple(SimplePixel::new(
-                progress.into(),
-                date.format("%Y/%m/%d %H:%M").to_string(),
-            )));
+            let mut pixel =
+                SimplePixel::new(progress.into(), date.format("%Y/%m/%d %H:%M").to_string());
+            pixel.set_task(task);
+            self.pixels.push(Pixel::Simple(pixel));
         }
         if self.pixels.items().len() > self.pixels_to_send.len() {
             self.update_pixels_to_send_size();
@@ -122,24 +214,210 @@ impl PixelaClient {
     pub fn pixels_to_send_is_empty(&self) -> bool {
         !self.pixels_to_send.contains(&1)
     }
-    pub fn save_pixels(&self) -> Result<()> {
-        let path = ProjectDirs::from("rtuion", "rtuion", "rtuion")
-            .ok_or(SettingsError::HomeDirNotFound)?;
-        let path = path.config_dir();
-        let path = path.join("pixels");
+    pub fn save_pixels(&mut self) -> Result<()> {
+        let path = data_dir()?.join("pixels");
         if !path.exists() {
             fs::create_dir_all(&path)?
         }
+        self.pixels
+            .items_mut()
+            .iter_mut()
+            .for_each(Pixel::ensure_id);
         let filename = format!("{}.json", self.user.username());
         let json_string: String = serde_json::to_string(self.pixels.items()).unwrap();
         fs::write(path.join(filename), json_string)?;
+        if let Some(sync) = &self.sync {
+            sync.write_pixels(
+                self.user.username(),
+                self.pixels.items(),
+                &self.removed_pixels,
+            )?;
+            self.removed_pixels.clear();
+        }
         Ok(())
     }
+    pub fn set_sync_folder(&mut self, sync: Option<SyncFolder>) {
+        self.sync = sync;
+    }
+    pub fn set_unit_aliases(&mut self, unit_aliases: HashMap<String, String>) {
+        self.unit_aliases = unit_aliases;
+    }
+    pub fn set_carry_over(&mut self, carry_over: bool) {
+        self.carry_over = carry_over;
+    }
+    pub fn carry_over(&self) -> bool {
+        self.carry_over
+    }
+    pub fn set_retention_days(&mut self, retention_days: u32) {
+        self.retention_days = retention_days;
+    }
+    pub fn set_aggregate_graph(&mut self, aggregate_graph: Option<String>) {
+        self.aggregate_graph = aggregate_graph;
+    }
+    /// The graph every upload is also added to, if one is set.
+    fn aggregate_subject(&self) -> Result<Option<Subject>> {
+        let Some(id) = self.aggregate_graph.as_ref().filter(|id| !id.is_empty()) else {
+            return Ok(None);
+        };
+        let aggregate = self
+            .subjects
+            .items()
+            .iter()
+            .find(|subject| subject.id() == id);
+        match aggregate {
+            Some(subject) => Ok(Some(subject.clone())),
+            None => Err(StatsError::AggregateGraphNotFound(id.clone()).into()),
+        }
+    }
+    /// Reports an aggregate graph that isn't one of the user's, sends keep its minutes
+    /// until it is.
+    pub fn check_aggregate_graph(&self) -> Result<()> {
+        self.aggregate_subject().map(|_| ())
+    }
+    /// Adds the pixels' minutes to the aggregate graph's days in `ledger`, so they are
+    /// written in the same batch. Pixels that can't be added yet wait in the ledger and are
+    /// added with the next sync. Also returns how often Pixela turned the reads down.
+    async fn record_aggregate(
+        &self,
+        ledger: &mut UploadLedger,
+        pixels: &[Pixel],
+        recorded: &mut HashMap<String, Vec<Pixel>>,
+        errors: &mut Vec<FatalError>,
+    ) -> usize {
+        let mut pixels = pixels.to_vec();
+        pixels.append(&mut ledger.take_deferred());
+        let aggregate = match self.aggregate_subject() {
+            Ok(Some(aggregate)) => aggregate,
+            Ok(None) => return 0,
+            // reported on login, the minutes wait for the graph
+            Err(_) => {
+                ledger.defer(pixels);
+                return 0;
+            }
+        };
+        let mirrored: Vec<(&Pixel, ComplexPixel)> = pixels
+            .iter()
+            .filter_map(|pixel| match pixel {
+                Pixel::Complex(complex) => Some((pixel, complex.mirrored(&aggregate)?)),
+                Pixel::Simple(_) => None,
+            })
+            .collect();
+        let days = PixelaClient::combine_similiar_pixels(
+            mirrored
+                .iter()
+                .map(|(_, mirrored)| Pixel::Complex(mirrored.clone()))
+                .collect(),
+        );
+        let (mut remote_values, rejections) =
+            PixelaClient::fetch_remote_values(&self.client, &self.user, days).await;
+        UploadLedger::sort_for_recording(&mut remote_values);
+        for (day, remote) in remote_values {
+            let date = day.date_pixela_formatted();
+            let sources = mirrored
+                .iter()
+                .filter(|(_, mirrored)| mirrored.date_pixela_formatted() == date)
+                .map(|(pixel, _)| (*pixel).clone());
+            match remote {
+                Ok(remote) => {
+                    ledger.record(&day, &remote, self.carry_over);
+                    recorded
+                        .entry(UploadLedger::key(day.subject(), &date))
+                        .or_default()
+                        .extend(sources);
+                }
+                Err(err) => {
+                    ledger.defer(sources);
+                    errors.push(FatalError::new(
+                        format!(
+                            "{} couldn't be read, the minutes are added on the next sync: {err}",
+                            day.subject().graph_name()
+                        ),
+                        StatusCode::IM_A_TEAPOT,
+                    ));
+                }
+            }
+        }
+        rejections
+    }
+    /// The days `selected` would be sent to, with what Pixela has on them now.
+    pub async fn preview_send(
+        user: PixelaUser,
+        client: Client,
+        selected: Vec<Pixel>,
+        carry_over: bool,
+    ) -> Result<SendPreview> {
+        let pixels = PixelaClient::combine_similiar_pixels(selected);
+        let (remote_values, _) = PixelaClient::fetch_remote_values(&client, &user, pixels).await;
+        let remote_values = remote_values
+            .into_iter()
+            .map(|(pixel, remote)| (pixel, remote.ok()))
+            .collect();
+        Ok(SendPreview::new(
+            &UploadLedger::load()?,
+            remote_values,
+            carry_over,
+        ))
+    }
+    /// Leaves the selected pixels of `pixel`'s subject and day out of the next send.
+    pub fn unselect_day(&mut self, pixel: &ComplexPixel) {
+        for (index, item) in self.pixels.items().iter().enumerate() {
+            if let Pixel::Complex(item) = item {
+                if item.subject() == pixel.subject() && item.date_no_time() == pixel.date_no_time()
+                {
+                    self.pixels_to_send[index] = 0;
+                }
+            }
+        }
+    }
+    /// Graphs the last login left out because of their unit.
+    pub fn untracked_graphs(&self) -> &[String] {
+        &self.untracked_graphs
+    }
+    /// Takes in the pixels other machines have pending and drops the ones they sent or deleted.
+    pub fn merge_synced_pixels(&mut self) -> Result<PixelMerge> {
+        let Some(sync) = self.sync.clone() else {
+            return Err(SettingsError::SyncDirNotSet.into());
+        };
+        self.pixels
+            .items_mut()
+            .iter_mut()
+            .for_each(Pixel::ensure_id);
+        let local = std::mem::take(self.pixels.items_mut());
+        let mut merge = sync.merge_pixels(self.user.username(), local, &self.removed_pixels)?;
+        self.pixels.set_items(std::mem::take(&mut merge.pixels));
+        self.pixels_to_send = vec![0; 2 * self.pixels.items().len()];
+        self.pixels.refresh_state();
+        self.save_pixels()?;
+        Ok(merge)
+    }
+    /// Undoes `take_selected_pixels` for pixels that weren't sent, they stay selected.
+    fn put_back_pixels(&mut self, pixels: Vec<Pixel>) {
+        let ids: Vec<String> = pixels
+            .iter()
+            .filter_map(Pixel::id)
+            .map(String::from)
+            .collect();
+        self.removed_pixels.retain(|id| !ids.contains(id));
+        let start = self.pixels.items().len();
+        self.pixels.items_mut().extend(pixels);
+        self.pixels_to_send = vec![0; 2 * self.pixels.items().len()];
+        (start..self.pixels.items().len()).for_each(|index| self.select_pixel(index));
+        self.pixels.refresh_state();
+    }
+    fn select_pixels(&mut self, pixels: &[Pixel]) {
+        for index in 0..self.pixels.items().len() {
+            if pixels.contains(&self.pixels.items()[index]) {
+                self.select_pixel(index);
+            }
+        }
+    }
+    fn forget_pixel(&mut self, pixel: &Pixel) {
+        if let Some(id) = pixel.id() {
+            self.removed_pixels.push(id.to_string());
+        }
+    }
     fn load_pixels(user: &PixelaUser) -> Result<Vec<Pixel>> {
-        let path = ProjectDirs::from("rtuion", "rtuion", "rtuion")
-            .ok_or(SettingsError::HomeDirNotFound)?;
-        let path = path.config_dir();
-        let path = path.join("pixels");
+        let path = data_dir()?.join("pixels");
         if !path.exists() {
             fs::create_dir_all(&path)?;
             return Err(Error::SettingsError(SettingsError::LoadError(
@@ -158,18 +436,22 @@ impl PixelaClient {
             )))
         }
     }
-    pub async fn send_and_handle_response<F, Fut>(
+    /// Runs `func` on every pixel and returns each outcome. Rejected pixels are sent again
+    /// in rounds that wait longer each time, until `PIXELA_MAX_ATTEMPTS`.
+    pub async fn send_and_handle_response<F, Fut, T>(
         &mut self,
         mut pixel_pool: Vec<Pixel>,
         mut func: F,
-    ) -> Result<Vec<Pixel>>
+    ) -> Batch<T>
     where
         F: FnMut(ComplexPixel, Client, PixelaUser) -> Fut,
-        Fut: Future<Output = (ComplexPixel, Result<()>)>,
+        Fut: Future<Output = (ComplexPixel, Result<T>)>,
     {
-        let mut unresolved_pixels = Vec::<Pixel>::new();
-        let mut resolved_pixels = Vec::<Pixel>::new();
-        let mut errors: Vec<FatalError> = vec![];
+        let mut batch = Batch {
+            outcomes: Vec::new(),
+            rejections: 0,
+        };
+        let mut attempt = 1;
         while !pixel_pool.is_empty() {
             let pixels = std::mem::take(&mut pixel_pool);
             let futures = pixels.into_iter().filter_map(|pixel| match pixel {
@@ -180,63 +462,231 @@ impl PixelaClient {
                 }
                 Pixel::Simple(_) => None,
             });
-            let mut futures = stream::iter(futures).buffer_unordered(4);
+            let mut futures = stream::iter(futures).buffer_unordered(PIXELA_CONCURRENT_REQUESTS);
             while let Some((pixel, result)) = futures.next().await {
-                if let Err(err) = result {
-                    match err {
-                        Error::PixelaResponseError(PixelaResponseError::RetryableError(_, _)) => {
-                            pixel_pool.push(Pixel::Complex(pixel.clone()));
-                            time::sleep(Duration::from_millis(100)).await;
-                        }
-                        Error::PixelaResponseError(PixelaResponseError::FatalError(_, _)) => {
-                            // if not expected fatal error write it back to store later
-                            unresolved_pixels.push(Pixel::Complex(pixel.clone()));
-                            errors.push(err.try_into()?);
-                        }
-                        _ => {
-                            unresolved_pixels.push(Pixel::Complex(pixel.clone()));
-                            errors.push(FatalError::new(err.to_string(), StatusCode::IM_A_TEAPOT));
+                match result {
+                    Err(Error::PixelaResponseError(PixelaResponseError::RetryableError(
+                        message,
+                        status,
+                    ))) => {
+                        batch.rejections += 1;
+                        match attempt < PIXELA_MAX_ATTEMPTS {
+                            true => pixel_pool.push(Pixel::Complex(pixel)),
+                            false => {
+                                let message = format!("Gave up after {attempt} tries: {message}");
+                                batch
+                                    .outcomes
+                                    .push((pixel, Err(FatalError::new(message, status))));
+                            }
                         }
                     }
-                } else {
-                    resolved_pixels.push(Pixel::Complex(pixel.clone()));
+                    Err(err) => batch
+                        .outcomes
+                        .push((pixel, Err(PixelaClient::into_fatal(err)))),
+                    Ok(value) => batch.outcomes.push((pixel, Ok(value))),
+                }
+            }
+            if !pixel_pool.is_empty() {
+                time::sleep(backoff(attempt)).await;
+                attempt += 1;
+            }
+        }
+        batch
+    }
+    pub async fn send_pixels(&mut self) -> Result<(Vec<Pixel>, BatchSummary)> {
+        let mut ledger = UploadLedger::load()?;
+        ledger.prune(self.retention_days, Local::now().date_naive());
+        let selected = self.take_selected_pixels(true);
+        let taken = selected.clone();
+        // the pixels behind every day, they are archived with the day's upload
+        let mut sources = HashMap::<String, Vec<Pixel>>::new();
+        for pixel in &selected {
+            if let Pixel::Complex(complex) = pixel {
+                let key = UploadLedger::key(complex.subject(), &complex.date_pixela_formatted());
+                sources.entry(key).or_default().push(pixel.clone());
+            }
+        }
+        let pixel_pool = PixelaClient::combine_similiar_pixels(selected);
+        let mut resolved_pixels = Vec::<Pixel>::new();
+        let mut unresolved_pixels = Vec::<Pixel>::new();
+        let mut failed_days = HashSet::<String>::new();
+        let mut recorded = HashMap::<String, Vec<Pixel>>::new();
+        let mut errors: Vec<FatalError> = vec![];
+        let (mut remote_values, mut rejections) =
+            PixelaClient::fetch_remote_values(&self.client, &self.user, pixel_pool).await;
+        UploadLedger::sort_for_recording(&mut remote_values);
+        for (pixel, remote) in remote_values {
+            let remote = check_if_quantity_is_big_enough(&pixel).and(remote);
+            match remote {
+                Ok(remote) => {
+                    ledger.record(&pixel, &remote, self.carry_over);
+                    let key = UploadLedger::key(pixel.subject(), &pixel.date_pixela_formatted());
+                    if let Some(pixels) = sources.remove(&key) {
+                        recorded.insert(key, pixels);
+                    }
+                    resolved_pixels.push(Pixel::Complex(pixel));
+                }
+                Err(err) => {
+                    failed_days.insert(UploadLedger::key(
+                        pixel.subject(),
+                        &pixel.date_pixela_formatted(),
+                    ));
+                    unresolved_pixels.push(Pixel::Complex(pixel));
+                    errors.push(PixelaClient::into_fatal(err));
                 }
             }
         }
+        rejections += self
+            .record_aggregate(&mut ledger, &resolved_pixels, &mut recorded, &mut errors)
+            .await;
+        // from here on the minutes live in the ledger, a failed write is repeated from there
+        // on the next sync instead of putting the pixel back, which would count it twice.
+        // until it's saved nothing was written, so the pixels can still go back
+        if let Err(err) = ledger.save() {
+            self.put_back_pixels(taken);
+            return Err(err);
+        }
+        let batch = self.write_ledger(&mut ledger).await;
+        // the writes happened, so the pixels below are saved even when the ledger isn't
+        if let Err(err) = ledger.save() {
+            errors.push(PixelaClient::into_fatal(err));
+        }
+        let mut summary = BatchSummary::from(&batch);
+        summary.failed += failed_days.len();
+        summary.rejections += rejections;
+        errors.append(&mut PixelaClient::archive_writes(batch.outcomes, recorded));
+
+        self.pixels
+            .items_mut()
+            .append(&mut unresolved_pixels.clone());
+        let len = self.pixels_to_send.len();
+        self.pixels_to_send = vec![0; len];
+        self.pixels.refresh_state();
+        if let Err(err) = self.save_pixels() {
+            errors.push(PixelaClient::into_fatal(err));
+        }
         if !errors.is_empty() {
             return Err(PixelaResponseError::FatalSendingPixelsError {
                 errors,
                 pixels: unresolved_pixels,
+                summary,
+            }
+            .into());
+        }
+        Ok((resolved_pixels, summary))
+    }
+    /// Writes days whose last upload wasn't confirmed, like after a timeout, and the minutes
+    /// that didn't reach the aggregate graph yet.
+    pub async fn reconcile_uploads(&mut self) -> Result<()> {
+        let mut ledger = UploadLedger::load()?;
+        let mut recorded = HashMap::new();
+        let mut errors = vec![];
+        let rejections = self
+            .record_aggregate(&mut ledger, &[], &mut recorded, &mut errors)
+            .await;
+        ledger.save()?;
+        let batch = self.write_ledger(&mut ledger).await;
+        ledger.save()?;
+        let mut summary = BatchSummary::from(&batch);
+        summary.rejections += rejections;
+        errors.append(&mut PixelaClient::archive_writes(batch.outcomes, recorded));
+        if !errors.is_empty() {
+            return Err(PixelaResponseError::FatalSendingPixelsError {
+                errors,
+                pixels: vec![],
+                summary,
             }
             .into());
         }
-        Ok(resolved_pixels)
+        Ok(())
     }
-    pub async fn send_pixels(&mut self) -> Result<Vec<Pixel>> {
-        let pixel_pool = PixelaClient::combine_similiar_pixels(self.take_selected_pixels(true));
+    async fn write_ledger(&mut self, ledger: &mut UploadLedger) -> Batch<String> {
         let operation = |pixel: ComplexPixel, client: Client, user: PixelaUser| async move {
             let response = pixel.upload(client, &user).await;
             (pixel, response)
         };
-        let result: Result<Vec<Pixel>> =
-            match self.send_and_handle_response(pixel_pool, operation).await {
-                Ok(resolved_pixels) => Ok(resolved_pixels),
-                Err(mut err) => {
-                    if let Error::PixelaResponseError(
-                        PixelaResponseError::FatalSendingPixelsError { errors: _, pixels },
-                    ) = &mut err
-                    {
-                        self.pixels.items_mut().append(pixels);
-                    }
-                    Err(err)
+        let pool = ledger.unsynced().into_iter().map(Pixel::Complex).collect();
+        let batch = self.send_and_handle_response(pool, operation).await;
+        for (pixel, outcome) in &batch.outcomes {
+            if outcome.is_ok() {
+                ledger.mark_synced(pixel);
+            }
+        }
+        batch
+    }
+    /// Keeps every written day in the `UploadArchive`, with the pixels in `sources` that made it.
+    fn archive_writes(
+        outcomes: Vec<(ComplexPixel, std::result::Result<String, FatalError>)>,
+        mut sources: HashMap<String, Vec<Pixel>>,
+    ) -> Vec<FatalError> {
+        let mut errors = vec![];
+        let mut uploads = vec![];
+        for (pixel, outcome) in outcomes {
+            let key = UploadLedger::key(pixel.subject(), &pixel.date_pixela_formatted());
+            let pixels = sources.remove(&key).unwrap_or_default();
+            let response = match outcome {
+                Ok(message) => Ok(message),
+                Err(err) => {
+                    let message = err.to_string();
+                    errors.push(err);
+                    Err(message)
                 }
             };
-        let len = self.pixels_to_send.len();
-
-        self.pixels_to_send = vec![0; len];
-        self.pixels.refresh_state();
-        self.save_pixels()?;
-        result
+            uploads.push(ArchivedUpload::new(&pixel, pixels, response));
+        }
+        if !errors.is_empty() {
+            errors.push(FatalError::new(
+                "Days that failed are kept and set again on the next sync".into(),
+                StatusCode::IM_A_TEAPOT,
+            ));
+        }
+        if let Err(err) = UploadArchive::append_all(&uploads) {
+            errors.push(FatalError::new(
+                format!("Uploads could not be archived: {err}"),
+                StatusCode::IM_A_TEAPOT,
+            ));
+        }
+        errors
+    }
+    /// What Pixela has on each pixel's day, rejected requests are asked again.
+    /// Also returns how often Pixela turned them down.
+    async fn fetch_remote_values(
+        client: &Client,
+        user: &PixelaUser,
+        pixels: Vec<Pixel>,
+    ) -> (Vec<(ComplexPixel, Result<Progress>)>, usize) {
+        let futures = pixels.into_iter().filter_map(|pixel| match pixel {
+            Pixel::Complex(pixel) => {
+                let client = client.clone();
+                let user = user.clone();
+                Some(async move {
+                    let date = pixel.date_pixela_formatted();
+                    let (result, rejections) =
+                        retried(|| get_from_pixela(pixel.subject(), &date, &client, user.token()))
+                            .await;
+                    (pixel, result, rejections)
+                })
+            }
+            Pixel::Simple(_) => None,
+        });
+        let fetched: Vec<_> = stream::iter(futures)
+            .buffer_unordered(PIXELA_CONCURRENT_REQUESTS)
+            .collect()
+            .await;
+        let rejections = fetched.iter().map(|(_, _, rejections)| rejections).sum();
+        let values = fetched
+            .into_iter()
+            .map(|(pixel, result, _)| (pixel, result))
+            .collect();
+        (values, rejections)
+    }
+    fn into_fatal(err: Error) -> FatalError {
+        match err {
+            Error::PixelaResponseError(PixelaResponseError::FatalError(message, code)) => {
+                FatalError::new(message, code)
+            }
+            err => FatalError::new(err.to_string(), StatusCode::IM_A_TEAPOT),
+        }
     }
     pub fn get_current_subject(&self) -> Option<Subject> {
         if self.current_subject_index == 0 {
@@ -256,42 +706,37 @@ impl PixelaClient {
         };
         self.sync_subjects().await?;
         self.logged_in = true;
+        // queued imports can only be matched to graphs once they are known
+        self.add_queued_imports()?;
         Ok(())
     }
     async fn sync_subjects(&mut self) -> Result<()> {
-        let mut response = PixelaClient::request_subjects(
-            self.client.clone(),
-            self.user.username(),
-            self.user.token(),
-        );
-        loop {
-            match response.await {
-                Err(resp) => {
-                    match resp {
-                        Error::PixelaResponseError(PixelaResponseError::RetryableError(_, _)) => {
-                            response = PixelaClient::request_subjects(
-                                self.client.clone(),
-                                self.user.username(),
-                                self.user.token(),
-                            )
-                        }
-                        _ => return Err(resp),
-                    };
-                }
-                Ok(mut subjects) => {
-                    self.subjects.items_mut().append(&mut subjects);
-                    break;
-                }
-            }
-        }
-
+        let (response, _) = retried(|| {
+            PixelaClient::request_subjects(
+                self.client.clone(),
+                self.user.username(),
+                self.user.token(),
+                &self.unit_aliases,
+            )
+        })
+        .await;
+        let (mut subjects, untracked) = response?;
+        self.subjects.items_mut().append(&mut subjects);
+        self.untracked_graphs = untracked;
         Ok(())
     }
     pub async fn request_graph(&mut self) -> Result<()> {
         if let Some(subject_index) = self.subjects.state().selected() {
             if let Some(subject) = self.get_subject(subject_index) {
                 self.set_current_graph(Some(
-                    Graph::download_graph(self.user.clone(), self.client.clone(), subject).await?,
+                    Graph::download_graph(
+                        self.user.clone(),
+                        self.client.clone(),
+                        subject,
+                        self.graph_range,
+                        self.graph_page,
+                    )
+                    .await?,
                 ));
             }
         }
@@ -301,29 +746,35 @@ impl PixelaClient {
         client: Client,
         username: &str,
         api_key: &str,
-    ) -> Result<Vec<Subject>> {
+        unit_aliases: &HashMap<String, String>,
+    ) -> Result<(Vec<Subject>, Vec<String>)> {
         let url = format!("https://pixe.la/v1/users/{}/graphs", username);
         let request = client
             .get(url)
             .header("X-USER-TOKEN", api_key)
             .send()
             .await?;
-        match request.error_for_status() {
-            Ok(request) => {
+        match request.status().is_success() {
+            true => {
                 let mut json_data: Value = request.json().await?;
                 if let Some(graphs_value) = json_data["graphs"].take().as_array_mut() {
-                    let mut subjects: Vec<Subject> = graphs_value
-                        .iter_mut()
-                        .filter_map(|subject| serde_json::from_value(subject.take()).ok())
-                        .filter_map(|subject: Subject| {
-                            match *subject.unit() == SubjectUnit::Minutes
-                                && *subject.data_type() == SubjectDataType::Float
-                            {
-                                true => None,
-                                false => Some(subject),
+                    let mut subjects: Vec<Subject> = Vec::new();
+                    let mut untracked = Vec::new();
+                    for graph in graphs_value.iter_mut() {
+                        let unit = graph["unit"].as_str().unwrap_or_default().to_string();
+                        match SubjectUnit::parse(&unit, unit_aliases) {
+                            Some(parsed) => {
+                                graph["unit"] = serde_json::to_value(parsed)?;
+                                if let Ok(subject) = serde_json::from_value(graph.take()) {
+                                    subjects.push(subject);
+                                }
                             }
-                        })
-                        .collect();
+                            None => untracked.push(format!(
+                                "{} ({unit})",
+                                graph["name"].as_str().unwrap_or_default()
+                            )),
+                        }
+                    }
                     for subject in &mut subjects {
                         subject.set_url(format!(
                             "https://pixe.la/v1/users/{}/graphs/{}",
@@ -331,7 +782,7 @@ impl PixelaClient {
                             subject.id()
                         ));
                     }
-                    Ok(subjects)
+                    Ok((subjects, untracked))
                 } else {
                     Err(PixelaResponseError::FatalError(
                         "Request sucessful but response not serializable".to_string(),
@@ -340,26 +791,7 @@ impl PixelaClient {
                     .into())
                 }
             }
-            Err(err) => {
-                if let Some(status) = err.status() {
-                    if status == reqwest::StatusCode::SERVICE_UNAVAILABLE
-                        || status == reqwest::StatusCode::INTERNAL_SERVER_ERROR
-                    {
-                        Err(
-                            PixelaResponseError::RetryableError("Server is busy".into(), status)
-                                .into(),
-                        )
-                    } else {
-                        Err(PixelaResponseError::FatalError(
-                            format!("Request denied: {}", err),
-                            status,
-                        )
-                        .into())
-                    }
-                } else {
-                    Err(err.into())
-                }
-            }
+            false => Err(response_error(request).await),
         }
     }
     pub fn change_focused_pane(&mut self, forward: bool) {
@@ -370,11 +802,51 @@ impl PixelaClient {
     }
     pub fn delete_pixel(&mut self) -> Result<()> {
         if let Some(index) = self.pixels.state().selected() {
-            self.pixels.items_mut().remove(index);
+            let pixel = self.pixels.items_mut().remove(index);
+            self.forget_pixel(&pixel);
             self.save_pixels()?;
         };
         Ok(())
     }
+    /// Turns queued imports into pixels for the graphs they were mapped to.
+    pub fn add_queued_imports(&mut self) -> Result<usize> {
+        let queued = ImportQueue::load()?;
+        if queued.is_empty() {
+            return Ok(0);
+        }
+        let mut unmatched = Vec::new();
+        let mut added = 0;
+        for session in queued {
+            let subject = self
+                .subjects
+                .iter()
+                .filter(|subject| !subject.is_dummy())
+                .find(|subject| {
+                    [session.subject_id(), session.subject_name()]
+                        .into_iter()
+                        .flatten()
+                        .any(|name| {
+                            subject.id() == name || subject.graph_name().eq_ignore_ascii_case(name)
+                        })
+                })
+                .cloned();
+            match subject {
+                Some(subject) => {
+                    self.add_pixel(
+                        session.start().with_timezone(&Local),
+                        Some(subject),
+                        Progress::new_minutes(TimeUnit::Seconds(session.duration())),
+                        session.task().map(String::from),
+                    );
+                    added += 1;
+                }
+                None => unmatched.push(session),
+            }
+        }
+        self.save_pixels()?;
+        ImportQueue::replace(&unmatched)?;
+        Ok(added)
+    }
     pub fn subjects(&self) -> Vec<&Subject> {
         self.subjects.iter().collect()
     }
@@ -398,27 +870,22 @@ impl PixelaClient {
             PixelaTabs::Subject => self.subjects.select_previous(),
         }
     }
+    /// Sends only the highlighted pixel, through the same ledger as `send_pixels`.
     pub async fn push_pixel(&mut self) -> Result<()> {
         if let Some(index) = self.pixels.state().selected() {
-            if let Some(Pixel::Complex(pixel)) = self.pixels().get(index) {
-                loop {
-                    let response = pixel.upload(self.client.clone(), &self.user).await;
-                    match response {
-                        Ok(_) => {
-                            self.pixels().remove(index);
-                            break;
-                        }
-                        Err(err) => match err {
-                            Error::PixelaResponseError(PixelaResponseError::RetryableError(
-                                _,
-                                _,
-                            )) => {
-                                continue;
-                            }
-                            _ => return Err(err),
-                        },
-                    };
-                }
+            if let Some(pushed @ Pixel::Complex(_)) = self.pixels.items().get(index).cloned() {
+                // the rest of the selection waits for the next send
+                let selection: Vec<Pixel> = self
+                    .clone_selected_pixels(true)
+                    .into_iter()
+                    .filter(|pixel| *pixel != pushed)
+                    .collect();
+                let len = self.pixels_to_send.len();
+                self.pixels_to_send = vec![0; len];
+                self.select_pixel(index);
+                let sent = self.send_pixels().await;
+                self.select_pixels(&selection);
+                sent?;
             } else {
                 return Err(StatsError::WrongPixelData.into());
             }
@@ -488,11 +955,13 @@ impl PixelaClient {
                 }
             })
             .collect();
-        pixels
+        let taken: Vec<Pixel> = pixels
             .into_iter()
             .rev()
             .map(|index| self.pixels.items_mut().remove(index))
-            .collect()
+            .collect();
+        taken.iter().for_each(|pixel| self.forget_pixel(pixel));
+        taken
     }
     pub fn clone_selected_pixels(&self, complex_only: bool) -> Vec<Pixel> {
         let pixels: Vec<usize> = self
@@ -534,6 +1003,53 @@ impl PixelaClient {
         &mut self.current_graph
     }
 
+    pub fn graph_range(&self) -> GraphRange {
+        self.graph_range
+    }
+
+    pub fn graph_page(&self) -> i64 {
+        self.graph_page
+    }
+
+    pub fn set_graph_stats(&mut self, graph_stats: Option<GraphStats>) {
+        self.graph_stats = graph_stats;
+    }
+
+    /// Pixela's stats, only when they belong to the graph on screen.
+    pub fn graph_stats(&self) -> Option<&GraphStats> {
+        let graph = self.current_graph.as_ref()?;
+        self.graph_stats
+            .as_ref()
+            .filter(|stats| stats.is_for(graph.subject()))
+    }
+
+    pub fn set_today_on_pixela(&mut self, subject_id: String, progress: Option<Progress>) {
+        self.today_on_pixela = progress.map(|progress| (subject_id, progress));
+    }
+
+    /// Today's remote value of the tracked subject, if it was fetched.
+    pub fn today_on_pixela(&self) -> Option<&Progress> {
+        let subject = self.get_current_subject()?;
+        self.today_on_pixela
+            .as_ref()
+            .filter(|(id, _)| id == subject.id())
+            .map(|(_, progress)| progress)
+    }
+
+    /// Switches to the next range, starting again from today.
+    pub fn next_graph_range(&mut self) {
+        self.graph_range = self.graph_range.next();
+        self.graph_page = 0;
+    }
+
+    /// Moves one range back in time, or forward but never past today.
+    pub fn page_graph(&mut self, older: bool) {
+        self.graph_page = match older {
+            true => self.graph_page + 1,
+            false => (self.graph_page - 1).max(0),
+        };
+    }
+
     pub fn focused_pane(&self) -> PixelaTabs {
         self.focused_pane
     }
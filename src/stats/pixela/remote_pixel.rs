@@ -0,0 +1,154 @@
+use chrono::NaiveDate;
+use reqwest::Client;
+
+use crate::error::{Result, StatsError};
+
+use super::{
+    helpers::response_error,
+    pixela_user::PixelaUser,
+    subjects::{Progress, Subject, SubjectDataType},
+    utils::PixelaResponse,
+};
+
+/// One day of a graph as Pixela has it, the quantity is in the graph's unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemotePixel {
+    pub subject: Subject,
+    pub date: NaiveDate,
+    pub quantity: f64,
+}
+
+/// A change to a pixel that was already sent, instead of adding on top of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RemoteEdit {
+    Set(f64),
+    Subtract(f64),
+    Delete,
+}
+impl RemoteEdit {
+    /// The value Pixela will have once the edit went through.
+    pub fn after(&self, before: f64) -> f64 {
+        match self {
+            RemoteEdit::Set(quantity) => *quantity,
+            RemoteEdit::Subtract(quantity) => (before - quantity).max(0.0),
+            RemoteEdit::Delete => 0.0,
+        }
+    }
+    /// Keeps a subtraction within what the pixel holds, Pixela would go below zero otherwise.
+    pub fn capped(self, before: f64) -> RemoteEdit {
+        match self {
+            RemoteEdit::Subtract(quantity) => RemoteEdit::Subtract(quantity.min(before.max(0.0))),
+            edit => edit,
+        }
+    }
+    /// Reads a quantity typed in the graph's unit, int graphs only take whole numbers.
+    pub fn parse_quantity(input: &str, subject: &Subject) -> Result<f64> {
+        let quantity: f64 = input
+            .trim()
+            .parse()
+            .map_err(|_| StatsError::InvalidQuantity(format!("{input} is not a number")))?;
+        if quantity < 0.0 || !quantity.is_finite() {
+            return Err(StatsError::InvalidQuantity(format!("{input} can't be negative")).into());
+        }
+        if *subject.data_type() == SubjectDataType::Int && quantity.fract() != 0.0 {
+            return Err(StatsError::InvalidQuantity(format!(
+                "{} only stores whole {}",
+                subject.graph_name(),
+                subject.unit().to_string().to_lowercase()
+            ))
+            .into());
+        }
+        Ok(quantity)
+    }
+}
+impl RemotePixel {
+    pub fn new(subject: Subject, date: NaiveDate, quantity: f64) -> Self {
+        Self {
+            subject,
+            date,
+            quantity,
+        }
+    }
+    /// Sends the edit and returns the pixel as it is on Pixela afterwards.
+    pub async fn apply(
+        mut self,
+        edit: RemoteEdit,
+        user: PixelaUser,
+        client: Client,
+    ) -> Result<RemotePixel> {
+        let url = format!("{}/{}", self.subject.url(), self.date.format("%Y%m%d"));
+        let edit = edit.capped(self.quantity);
+        let request = match edit {
+            RemoteEdit::Set(quantity) => client
+                .put(url)
+                .json(&serde_json::json!({ "quantity": self.sendable(quantity) })),
+            RemoteEdit::Subtract(quantity) => client
+                .put(format!("{url}/subtract"))
+                .json(&serde_json::json!({ "quantity": self.sendable(quantity) })),
+            RemoteEdit::Delete => client.delete(url),
+        };
+        let request = request.header("X-USER-TOKEN", user.token()).send().await?;
+        match request.status().is_success() {
+            true => {
+                request.json::<PixelaResponse>().await?;
+                self.quantity = edit.after(self.quantity);
+                Ok(self)
+            }
+            false => Err(response_error(request).await),
+        }
+    }
+    pub fn progress(&self) -> Progress {
+        match self.subject.data_type() {
+            SubjectDataType::Int => Progress::Int(self.quantity.round() as usize),
+            SubjectDataType::Float => Progress::Float(self.quantity),
+        }
+    }
+    fn sendable(&self, quantity: f64) -> String {
+        match self.subject.data_type() {
+            SubjectDataType::Int => format!("{}", quantity.round() as usize),
+            SubjectDataType::Float => format!("{quantity}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantities_are_trimmed() {
+        let minutes = Subject::new_dummy();
+        assert_eq!(RemoteEdit::parse_quantity(" 45 ", &minutes).unwrap(), 45.0);
+    }
+    #[test]
+    fn int_graphs_refuse_fractions() {
+        let minutes = Subject::new_dummy();
+        assert!(RemoteEdit::parse_quantity("1.5", &minutes).is_err());
+    }
+    #[test]
+    fn negative_quantities_are_refused() {
+        let minutes = Subject::new_dummy();
+        assert!(RemoteEdit::parse_quantity("-3", &minutes).is_err());
+    }
+    #[test]
+    fn edits_preview_the_new_value() {
+        assert_eq!(RemoteEdit::Set(30.0).after(90.0), 30.0);
+        assert_eq!(RemoteEdit::Subtract(30.0).after(90.0), 60.0);
+        assert_eq!(RemoteEdit::Delete.after(90.0), 0.0);
+    }
+    #[test]
+    fn subtracting_stops_at_zero() {
+        assert_eq!(RemoteEdit::Subtract(120.0).after(90.0), 0.0);
+    }
+    #[test]
+    fn subtractions_are_capped_at_the_current_quantity() {
+        assert_eq!(
+            RemoteEdit::Subtract(120.0).capped(90.0),
+            RemoteEdit::Subtract(90.0)
+        );
+        assert_eq!(
+            RemoteEdit::Subtract(30.0).capped(90.0),
+            RemoteEdit::Subtract(30.0)
+        );
+    }
+}
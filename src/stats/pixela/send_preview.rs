@@ -0,0 +1,161 @@
+use super::{
+    complex_pixel::{ComplexPixel, IsRounded},
+    helpers::check_if_quantity_is_big_enough,
+    subjects::Progress,
+    upload_ledger::{LedgerDay, UploadLedger},
+};
+
+/// One day of one subject in the send confirmation.
+#[derive(Debug, Clone)]
+pub struct PreviewRow {
+    /// the selected pixels of the day, combined
+    pixel: ComplexPixel,
+    /// what Pixela has on the day, `None` when it couldn't be read
+    current: Option<Progress>,
+    day: LedgerDay,
+    selected: bool,
+}
+impl PreviewRow {
+    pub fn pixel(&self) -> &ComplexPixel {
+        &self.pixel
+    }
+    /// The day's value on Pixela right now, in the graph's unit.
+    pub fn current(&self) -> Option<f64> {
+        self.current.as_ref().map(Progress::get_as_float)
+    }
+    /// The value the day is set to, in the graph's unit.
+    pub fn new_value(&self) -> f64 {
+        self.day.written_quantity()
+    }
+    pub fn rounded(&self) -> IsRounded {
+        self.pixel.is_rounded()
+    }
+    /// Pixela would turn the day down, it starts out deselected.
+    pub fn too_small(&self) -> bool {
+        check_if_quantity_is_big_enough(&self.pixel).is_err()
+    }
+    /// Minutes an int hour graph can't take yet, they are carried instead of lost.
+    pub fn carry(&self) -> Option<String> {
+        match (self.day.carried_in(), self.day.remainder()) {
+            (0, 0) => None,
+            (0, left) => Some(format!("{left}m carried over")),
+            (carried, 0) => Some(format!("{carried}m carried in")),
+            (carried, left) => Some(format!("{carried}m carried in, {left}m carried over")),
+        }
+    }
+    pub fn selected(&self) -> bool {
+        self.selected
+    }
+}
+
+/// The days a send would set, each of which can be left out before confirming.
+#[derive(Debug, Clone, Default)]
+pub struct SendPreview {
+    rows: Vec<PreviewRow>,
+}
+impl SendPreview {
+    /// `pixels` are the combined pixels, each with what Pixela has on its day.
+    pub fn new(
+        ledger: &UploadLedger,
+        mut pixels: Vec<(ComplexPixel, Option<Progress>)>,
+        carry_over: bool,
+    ) -> SendPreview {
+        // in the order they are sent in, so they hand their remainder on the same way
+        UploadLedger::sort_for_recording(&mut pixels);
+        let days = ledger.preview(&pixels, carry_over);
+        let rows = pixels
+            .into_iter()
+            .zip(days)
+            .map(|((pixel, current), day)| {
+                let selected = check_if_quantity_is_big_enough(&pixel).is_ok();
+                PreviewRow {
+                    pixel,
+                    current,
+                    day,
+                    selected,
+                }
+            })
+            .collect();
+        SendPreview { rows }
+    }
+    pub fn rows(&self) -> &[PreviewRow] {
+        &self.rows
+    }
+    pub fn toggle(&mut self, index: usize) {
+        if let Some(row) = self.rows.get_mut(index) {
+            row.selected = !row.selected;
+        }
+    }
+    /// The days that were left out, their pixels stay where they are.
+    pub fn excluded(&self) -> impl Iterator<Item = &ComplexPixel> {
+        self.rows
+            .iter()
+            .filter(|row| !row.selected)
+            .map(|row| &row.pixel)
+    }
+    pub fn selected_count(&self) -> usize {
+        self.rows.iter().filter(|row| row.selected).count()
+    }
+    pub fn message(&self) -> String {
+        let mut message = "These days will be set, space leaves one out".to_string();
+        let unread = self.rows.iter().filter(|row| row.current.is_none()).count();
+        if unread > 0 {
+            message.push_str(&format!(
+                "\n{unread} days couldn't be read from Pixela, their current value is a guess"
+            ));
+        }
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::pixela::subjects::{Minutes, Subject, SubjectUnit};
+
+    fn pixel(minutes: Minutes, subject: Subject, date: &str) -> ComplexPixel {
+        ComplexPixel::new(Progress::Int(minutes), subject, date.into())
+    }
+    /// 90 minutes for an hour graph that has an hour on the 4th,
+    /// and 5 unread minutes for a minute graph on the 5th
+    fn preview() -> SendPreview {
+        let mut hours = Subject::new_dummy();
+        hours.set_unit(SubjectUnit::Hours);
+        SendPreview::new(
+            &UploadLedger::default(),
+            vec![
+                (pixel(5, Subject::new_dummy(), "2024/03/05"), None),
+                (pixel(90, hours, "2024/03/04"), Some(Progress::Int(1))),
+            ],
+            false,
+        )
+    }
+
+    #[test]
+    fn rows_show_current_and_new_values() {
+        let preview = preview();
+        let rows = preview.rows();
+        assert_eq!((rows[0].current(), rows[0].new_value()), (Some(1.0), 2.0));
+        assert_eq!((rows[1].current(), rows[1].new_value()), (None, 5.0));
+    }
+    #[test]
+    fn rounded_minutes_are_carried_over() {
+        let preview = preview();
+        let row = &preview.rows()[0];
+        assert_eq!(row.rounded(), IsRounded::Yes(1));
+        assert_eq!(row.carry(), Some("30m carried over".into()));
+    }
+    #[test]
+    fn days_too_small_for_pixela_start_out_left_out() {
+        let preview = preview();
+        assert!(preview.rows()[0].selected());
+        assert!(preview.rows()[1].too_small() && !preview.rows()[1].selected());
+    }
+    #[test]
+    fn toggled_rows_are_excluded() {
+        let mut preview = preview();
+        preview.toggle(0);
+        assert_eq!(preview.selected_count(), 0);
+        assert_eq!(preview.excluded().count(), 2);
+    }
+}
@@ -1,11 +1,9 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use strum_macros::Display;
 
-use crate::{
-    error::{Result, StatsError},
-    utils::misc,
-    MIN_HOUR_INCREMENT, MIN_MINS_INCREMENT,
-};
+use crate::{utils::misc, MIN_HOUR_INCREMENT, MIN_MINS_INCREMENT};
 
 use super::graph::PixelaColors;
 
@@ -37,38 +35,53 @@ pub enum SubjectUnit {
     Minutes,
     #[serde(rename = "hours", alias = "hrs", alias = "Hours")]
     Hours,
+    /// one for every finished work block
+    #[serde(rename = "count")]
+    Count,
 }
 impl SubjectUnit {
+    /// Reads the unit a graph was created with, `aliases` maps units of your own to
+    /// minutes, hours or count.
+    pub fn parse(unit: &str, aliases: &HashMap<String, String>) -> Option<SubjectUnit> {
+        let unit = unit.trim().to_lowercase();
+        let unit = aliases
+            .iter()
+            .find(|(alias, _)| alias.trim().to_lowercase() == unit)
+            .map_or(unit.clone(), |(_, target)| target.trim().to_lowercase());
+        match unit.as_str() {
+            "minutes" | "minute" | "mins" | "min" | "m" => Some(SubjectUnit::Minutes),
+            "hours" | "hour" | "hrs" | "hr" | "h" => Some(SubjectUnit::Hours),
+            "count" | "times" | "pomodoros" | "pomodoro" | "pomos" | "sessions" | "blocks" => {
+                Some(SubjectUnit::Count)
+            }
+            _ => None,
+        }
+    }
     pub fn short_string(&self) -> String {
         match self {
             SubjectUnit::Hours => "h".to_string(),
             SubjectUnit::Minutes => "m".to_string(),
+            SubjectUnit::Count => "×".to_string(),
+        }
+    }
+    /// How many minutes one of the unit is, `None` for counts.
+    pub fn in_minutes(&self) -> Option<f64> {
+        match self {
+            SubjectUnit::Minutes => Some(1.0),
+            SubjectUnit::Hours => Some(60.0),
+            SubjectUnit::Count => None,
+        }
+    }
+    /// The unit local pixels of the graph are kept in.
+    pub fn local(&self) -> SubjectUnit {
+        match self {
+            SubjectUnit::Count => SubjectUnit::Count,
+            _ => SubjectUnit::Minutes,
         }
     }
 }
 
 impl Subject {
-    fn try_new(
-        id: String,
-        url: String,
-        graph_name: String,
-        data_type: SubjectDataType,
-        unit: SubjectUnit,
-        color: PixelaColors,
-    ) -> Result<Subject> {
-        if data_type == SubjectDataType::Float && unit == SubjectUnit::Minutes {
-            Err(StatsError::SubjectCreationFailed.into())
-        } else {
-            Ok(Subject {
-                id,
-                url,
-                graph_name,
-                data_type,
-                unit,
-                color,
-            })
-        }
-    }
     pub fn new_dummy() -> Subject {
         Subject {
             id: "".to_string(),
@@ -124,9 +137,12 @@ impl Subject {
         match self.unit() {
             SubjectUnit::Hours if *self.data_type() == SubjectDataType::Float => MIN_MINS_INCREMENT,
             SubjectUnit::Hours => MIN_HOUR_INCREMENT,
-            SubjectUnit::Minutes => MIN_MINS_INCREMENT,
+            SubjectUnit::Minutes | SubjectUnit::Count => MIN_MINS_INCREMENT,
         }
     }
+    pub fn is_count(&self) -> bool {
+        *self.unit() == SubjectUnit::Count
+    }
 
     pub fn set_unit(&mut self, unit: SubjectUnit) {
         self.unit = unit;
@@ -154,6 +170,7 @@ impl TimeUnit {
         }
     }
 }
+/// A quantity in some graph's unit. Local pixels keep minutes, or blocks for count graphs.
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Progress {
     Int(Minutes),
@@ -185,21 +202,23 @@ impl Progress {
             Progress::Int(num) => *num /= divider,
         }
     }
-    pub fn minutes(&self, unit: &SubjectUnit) -> Minutes {
-        match unit {
-            SubjectUnit::Hours => self.get_as_int() * 60,
-            SubjectUnit::Minutes => self.get_as_int(),
+    /// The quantity, given in `from`, in `to`. Counts aren't time, they stay as they are.
+    pub fn convert(&self, from: &SubjectUnit, to: &SubjectUnit) -> f64 {
+        match (from.in_minutes(), to.in_minutes()) {
+            (Some(from), Some(to)) => self.get_as_float() * from / to,
+            _ => self.get_as_float(),
         }
     }
+    /// In minutes, or the count for count graphs.
+    pub fn minutes(&self, unit: &SubjectUnit) -> Minutes {
+        self.convert(unit, &SubjectUnit::Minutes).round() as Minutes
+    }
     pub fn hours(&self, unit: &SubjectUnit) -> Hours {
-        match unit {
-            SubjectUnit::Hours => misc::round_to_precision(self.get_as_float(), 2),
-            SubjectUnit::Minutes => misc::round_to_precision(self.get_as_float() / 60.0, 2),
-        }
+        misc::round_to_precision(self.convert(unit, &SubjectUnit::Hours), 2)
     }
     pub fn hours_floored(&self, unit: &SubjectUnit) -> HoursRounded {
         match unit {
-            SubjectUnit::Hours => self.get_as_int(),
+            SubjectUnit::Hours | SubjectUnit::Count => self.get_as_int(),
             SubjectUnit::Minutes => self.get_as_int() / 60,
         }
     }
@@ -0,0 +1,326 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate};
+use ratatui::widgets::ListState;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Result, settings::data_dir, stats::pixel::Pixel};
+
+use super::{complex_pixel::ComplexPixel, subjects::Minutes};
+
+/// One write of a day to Pixela and the local pixels that went into it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedUpload {
+    /// rfc3339
+    uploaded_at: String,
+    subject_id: String,
+    subject_name: String,
+    /// yyyymmdd
+    date: String,
+    /// the combined minutes of `pixels`, zero when a failed day was written again
+    sent: Minutes,
+    /// the absolute value written for the day, in minutes
+    value: Minutes,
+    /// Pixela's message, or the error when the write failed
+    response: String,
+    success: bool,
+    pixels: Vec<Pixel>,
+}
+impl ArchivedUpload {
+    pub fn new(
+        written: &ComplexPixel,
+        pixels: Vec<Pixel>,
+        response: std::result::Result<String, String>,
+    ) -> Self {
+        let sent = pixels
+            .iter()
+            .map(|pixel| match pixel {
+                Pixel::Complex(pixel) => pixel.progress().get_as_int(),
+                Pixel::Simple(pixel) => pixel.progress(),
+            })
+            .sum();
+        let (success, response) = match response {
+            Ok(message) => (true, message),
+            Err(message) => (false, message),
+        };
+        Self {
+            uploaded_at: Local::now().to_rfc3339(),
+            subject_id: written.subject().id().to_string(),
+            subject_name: written.subject().graph_name().to_string(),
+            date: written.date_pixela_formatted(),
+            sent,
+            value: written.progress().get_as_int(),
+            response,
+            success,
+            pixels,
+        }
+    }
+    pub fn uploaded_at(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.uploaded_at).ok()
+    }
+    pub fn date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.date, "%Y%m%d").ok()
+    }
+    pub fn subject_id(&self) -> &str {
+        &self.subject_id
+    }
+    pub fn subject_name(&self) -> &str {
+        &self.subject_name
+    }
+    pub fn sent(&self) -> Minutes {
+        self.sent
+    }
+    pub fn value(&self) -> Minutes {
+        self.value
+    }
+    pub fn response(&self) -> &str {
+        &self.response
+    }
+    pub fn success(&self) -> bool {
+        self.success
+    }
+    pub fn pixels(&self) -> &[Pixel] {
+        &self.pixels
+    }
+}
+
+/// Every upload, one JSON object per line in `upload_archive.jsonl`.
+pub struct UploadArchive;
+impl UploadArchive {
+    fn path() -> Result<PathBuf> {
+        Ok(data_dir()?.join("upload_archive.jsonl"))
+    }
+    pub fn append_all(uploads: &[ArchivedUpload]) -> Result<()> {
+        let path = UploadArchive::path()?;
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)?
+            }
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        for upload in uploads {
+            writeln!(file, "{}", serde_json::to_string(upload)?)?;
+        }
+        Ok(())
+    }
+    /// Newest first.
+    pub fn load() -> Result<Vec<ArchivedUpload>> {
+        let path = UploadArchive::path()?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(UploadArchive::parse(&fs::read_to_string(path)?))
+    }
+    fn parse(contents: &str) -> Vec<ArchivedUpload> {
+        let mut uploads: Vec<ArchivedUpload> = contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        uploads.sort_by_key(|upload| std::cmp::Reverse(upload.uploaded_at()));
+        uploads
+    }
+    /// Drops uploads older than `retention_days`, 0 keeps everything.
+    /// Lines that can't be read are kept as they are.
+    pub fn prune(retention_days: u32) -> Result<usize> {
+        if retention_days == 0 {
+            return Ok(0);
+        }
+        let path = UploadArchive::path()?;
+        if !path.exists() {
+            return Ok(0);
+        }
+        let contents = fs::read_to_string(&path)?;
+        let (kept, pruned) =
+            UploadArchive::without_expired(&contents, retention_days, Local::now());
+        if pruned > 0 {
+            fs::write(path, kept)?;
+        }
+        Ok(pruned)
+    }
+    /// The archive's lines without the expired uploads, in the order they were.
+    fn without_expired(contents: &str, days: u32, now: DateTime<Local>) -> (String, usize) {
+        let cutoff = now.fixed_offset() - Duration::days(days as i64);
+        let mut kept = String::new();
+        let mut pruned = 0;
+        for line in contents.lines() {
+            let expired = serde_json::from_str::<ArchivedUpload>(line)
+                .ok()
+                .and_then(|upload| upload.uploaded_at())
+                .is_some_and(|at| at < cutoff);
+            match expired {
+                true => pruned += 1,
+                false => {
+                    kept.push_str(line);
+                    kept.push('\n');
+                }
+            }
+        }
+        (kept, pruned)
+    }
+}
+
+/// Which uploads the archive view lists.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchiveFilter {
+    /// graph id
+    pub subject: Option<String>,
+    pub failed_only: bool,
+}
+impl ArchiveFilter {
+    pub fn matches(&self, upload: &ArchivedUpload) -> bool {
+        let subject = self
+            .subject
+            .as_ref()
+            .is_none_or(|subject| subject == upload.subject_id());
+        subject && (!self.failed_only || !upload.success())
+    }
+}
+
+/// The archive as browsed on the stats tab.
+#[derive(Debug)]
+pub struct ArchiveView {
+    uploads: Vec<ArchivedUpload>,
+    filter: ArchiveFilter,
+    state: ListState,
+}
+impl ArchiveView {
+    pub fn new(uploads: Vec<ArchivedUpload>) -> Self {
+        let mut view = Self {
+            uploads,
+            filter: ArchiveFilter::default(),
+            state: ListState::default(),
+        };
+        view.state.select_first();
+        view
+    }
+    pub fn shown(&self) -> Vec<&ArchivedUpload> {
+        self.uploads
+            .iter()
+            .filter(|upload| self.filter.matches(upload))
+            .collect()
+    }
+    pub fn selected(&self) -> Option<&ArchivedUpload> {
+        let shown = self.shown();
+        self.state
+            .selected()
+            .and_then(|index| shown.get(index.min(shown.len().saturating_sub(1))).copied())
+    }
+    pub fn select_next(&mut self) {
+        let last = self.shown().len().saturating_sub(1);
+        let next = self
+            .state
+            .selected()
+            .map_or(0, |index| (index + 1).min(last));
+        self.state.select(Some(next));
+    }
+    pub fn select_previous(&mut self) {
+        self.state.select_previous();
+    }
+    /// All subjects, then each subject that has uploads.
+    pub fn next_subject(&mut self) {
+        let mut subjects: Vec<&str> = self
+            .uploads
+            .iter()
+            .map(|upload| upload.subject_id())
+            .collect();
+        subjects.sort();
+        subjects.dedup();
+        let next = match &self.filter.subject {
+            None => subjects.first(),
+            Some(current) => subjects
+                .iter()
+                .position(|subject| subject == current)
+                .and_then(|index| subjects.get(index + 1)),
+        };
+        self.filter.subject = next.map(|subject| subject.to_string());
+        self.state.select_first();
+    }
+    pub fn toggle_failed_only(&mut self) {
+        self.filter.failed_only = !self.filter.failed_only;
+        self.state.select_first();
+    }
+    pub fn filter(&self) -> &ArchiveFilter {
+        &self.filter
+    }
+    /// The name of the subject the view is filtered to.
+    pub fn subject_name(&self) -> Option<&str> {
+        let subject = self.filter.subject.as_ref()?;
+        self.uploads
+            .iter()
+            .find(|upload| upload.subject_id() == subject)
+            .map(|upload| upload.subject_name())
+    }
+    pub fn state(&self) -> &ListState {
+        &self.state
+    }
+    pub fn state_mut(&mut self) -> &mut ListState {
+        &mut self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::pixela::subjects::{Progress, Subject};
+
+    fn written() -> ComplexPixel {
+        ComplexPixel::new(Progress::Int(90), Subject::new_dummy(), "20240304".into())
+    }
+    /// A successful upload from January
+    fn old() -> ArchivedUpload {
+        let pixel = Pixel::Complex(ComplexPixel::new(
+            Progress::Int(25),
+            Subject::new_dummy(),
+            "2024/03/04 10:00".into(),
+        ));
+        let mut old = ArchivedUpload::new(&written(), vec![pixel], Ok("Success.".into()));
+        old.uploaded_at = "2024-01-01T10:00:00+00:00".into();
+        old
+    }
+    fn failed() -> ArchivedUpload {
+        ArchivedUpload::new(&written(), vec![], Err("timed out".into()))
+    }
+
+    #[test]
+    fn upload_knows_what_was_sent_and_written() {
+        assert_eq!(old().sent(), 25);
+        assert_eq!(old().value(), 90);
+    }
+    #[test]
+    fn newest_uploads_come_first() {
+        let (old, failed) = (old(), failed());
+        let contents = format!(
+            "{}\n{}\n",
+            serde_json::to_string(&old).unwrap(),
+            serde_json::to_string(&failed).unwrap()
+        );
+        assert_eq!(UploadArchive::parse(&contents), vec![failed, old]);
+    }
+    #[test]
+    fn failed_only_filter_hides_successes() {
+        let filter = ArchiveFilter {
+            failed_only: true,
+            ..Default::default()
+        };
+        assert!(!filter.matches(&old()));
+        assert!(filter.matches(&failed()));
+    }
+    #[test]
+    fn uploads_past_the_retention_are_pruned() {
+        let failed = serde_json::to_string(&failed()).unwrap();
+        let contents = format!("{}\n{failed}\n", serde_json::to_string(&old()).unwrap());
+        let (kept, pruned) = UploadArchive::without_expired(&contents, 30, Local::now());
+        assert_eq!(pruned, 1);
+        assert_eq!(kept, format!("{failed}\n"));
+    }
+    #[test]
+    fn unreadable_lines_survive_pruning() {
+        let contents = "{\"written by\": \"a newer version\"}\n";
+        let (kept, pruned) = UploadArchive::without_expired(contents, 30, Local::now());
+        assert_eq!((kept.as_str(), pruned), (contents, 0));
+    }
+}
@@ -0,0 +1,373 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+};
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Result, settings::data_dir, stats::pixel::Pixel};
+
+use super::{
+    complex_pixel::ComplexPixel,
+    subjects::{Minutes, Progress, Subject, SubjectDataType, SubjectUnit},
+};
+
+/// What rtuion put on one day of one graph, next to what was there without it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerDay {
+    subject: Subject,
+    /// yyyymmdd
+    date: String,
+    /// minutes on Pixela that didn't come from rtuion, negative when some were taken away
+    base: f64,
+    /// every minute rtuion uploaded for the day
+    uploaded: Minutes,
+    /// remainder taken over from an earlier day
+    #[serde(default)]
+    carried_in: Minutes,
+    /// remainder handed on to a later day
+    #[serde(default)]
+    carried_out: Minutes,
+    /// false until Pixela confirmed the last write
+    synced: bool,
+}
+impl LedgerDay {
+    /// The absolute value the day should have, in minutes.
+    pub fn target(&self) -> Minutes {
+        let total = self.base + (self.uploaded + self.carried_in) as f64 - self.carried_out as f64;
+        total.round().max(0.0) as Minutes
+    }
+    /// What Pixela holds once `target` is written, int hour graphs drop the minutes past the hour.
+    fn written(&self) -> Minutes {
+        let unit = self.subject.unit();
+        match self.subject.data_type() {
+            SubjectDataType::Int => {
+                let sent = Progress::Int(self.target()).convert(&unit.local(), unit);
+                Progress::Float(sent.floor()).minutes(unit)
+            }
+            SubjectDataType::Float => self.target(),
+        }
+    }
+    /// Minutes the graph can't hold yet, they are written once they add up.
+    pub fn remainder(&self) -> Minutes {
+        self.target().saturating_sub(self.written())
+    }
+    pub fn carried_in(&self) -> Minutes {
+        self.carried_in
+    }
+    pub fn subject(&self) -> &Subject {
+        &self.subject
+    }
+    /// yyyymmdd
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+    /// What Pixela holds once the day is written, in the graph's unit.
+    pub fn written_quantity(&self) -> f64 {
+        let unit = self.subject.unit();
+        Progress::Int(self.written()).convert(&unit.local(), unit)
+    }
+    fn to_pixel(&self) -> ComplexPixel {
+        ComplexPixel::new(
+            Progress::Int(self.target()),
+            self.subject.clone(),
+            self.date.clone(),
+        )
+    }
+}
+
+/// Per-day totals that were uploaded, kept in `upload_ledger.json`.
+/// Uploads set the absolute value from here, so a retried request can't count twice.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct UploadLedger {
+    days: BTreeMap<String, LedgerDay>,
+    /// pixels whose minutes couldn't be added to the aggregate graph yet
+    #[serde(default)]
+    deferred: Vec<Pixel>,
+}
+impl UploadLedger {
+    fn path() -> Result<PathBuf> {
+        Ok(data_dir()?.join("upload_ledger.json"))
+    }
+    pub fn load() -> Result<UploadLedger> {
+        let path = UploadLedger::path()?;
+        if !path.exists() {
+            return Ok(UploadLedger::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+    pub fn save(&self) -> Result<()> {
+        let path = UploadLedger::path()?;
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)?
+            }
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+    /// `date` is yyyymmdd.
+    pub(crate) fn key(subject: &Subject, date: &str) -> String {
+        format!("{}/{date}", subject.url())
+    }
+    /// Adds a pixel's minutes to its day, `remote` is what Pixela has for the day right now.
+    /// With `carry_over` a new day starts with the remainder of the subject's last day.
+    pub fn record(&mut self, pixel: &ComplexPixel, remote: &Progress, carry_over: bool) {
+        let subject = pixel.subject();
+        let date = pixel.date_pixela_formatted();
+        let remote = remote.convert(subject.unit(), &subject.unit().local());
+        let key = UploadLedger::key(subject, &date);
+        match self.days.get_mut(&key) {
+            // after a confirmed write the rest of the day came from elsewhere, like the website.
+            // after an unconfirmed one the remote value can't be trusted, so the old base stays
+            Some(day) if day.synced => day.base = remote - (day.written() as f64 - day.base),
+            Some(_) => {}
+            None => {
+                let carried_in = match carry_over {
+                    true => self.take_remainder(subject, &key),
+                    false => 0,
+                };
+                self.days.insert(
+                    key.clone(),
+                    LedgerDay {
+                        subject: subject.clone(),
+                        date,
+                        base: remote,
+                        uploaded: 0,
+                        carried_in,
+                        carried_out: 0,
+                        synced: true,
+                    },
+                );
+            }
+        }
+        let day = self.days.get_mut(&key).expect("the day was just added");
+        day.uploaded += pixel.progress().minutes(&SubjectUnit::Minutes);
+        // nothing to write while Pixela holds what the day comes to, like minutes short of an hour
+        day.synced = day.synced && day.written() as f64 == remote;
+    }
+    /// Puts every graph's days in date order, the order to record them in so each day
+    /// takes over the remainder of the one before.
+    pub fn sort_for_recording<T>(pixels: &mut [(ComplexPixel, T)]) {
+        pixels.sort_by_cached_key(|(pixel, _)| {
+            (
+                pixel.subject().url().to_string(),
+                pixel.date_pixela_formatted(),
+            )
+        });
+    }
+    /// Hands on the remainder of the subject's last day before `key`.
+    /// What that day writes stays the same, it only loses the minutes past the hour.
+    fn take_remainder(&mut self, subject: &Subject, key: &str) -> Minutes {
+        let previous = self
+            .days
+            .range_mut(..key.to_string())
+            .rev()
+            .find(|(_, day)| day.subject.url() == subject.url());
+        match previous {
+            Some((_, day)) => {
+                let remainder = day.remainder();
+                day.carried_out += remainder;
+                remainder
+            }
+            None => 0,
+        }
+    }
+    /// The days `pixels` go to as they would be after recording them, with what Pixela has
+    /// on each day. Days that couldn't be read are assumed to hold what was last written.
+    pub fn preview(
+        &self,
+        pixels: &[(ComplexPixel, Option<Progress>)],
+        carry_over: bool,
+    ) -> Vec<LedgerDay> {
+        let mut ledger = self.clone();
+        let mut keys = vec![];
+        for (pixel, remote) in pixels {
+            let key = UploadLedger::key(pixel.subject(), &pixel.date_pixela_formatted());
+            let unit = pixel.subject().unit();
+            let remote = remote.clone().unwrap_or_else(|| {
+                Progress::Float(ledger.days.get(&key).map_or(0.0, |day| {
+                    Progress::Int(day.written()).convert(&unit.local(), unit)
+                }))
+            });
+            ledger.record(pixel, &remote, carry_over);
+            keys.push(key);
+        }
+        keys.iter()
+            .filter_map(|key| ledger.days.get(key).cloned())
+            .collect()
+    }
+    /// Days whose last write wasn't confirmed, as pixels carrying the absolute value.
+    pub fn unsynced(&self) -> Vec<ComplexPixel> {
+        self.days
+            .values()
+            .filter(|day| !day.synced)
+            .map(LedgerDay::to_pixel)
+            .collect()
+    }
+    /// Drops written days older than `retention_days`, 0 keeps everything. Days still to be
+    /// written and each subject's last day, which a remainder is carried from, stay.
+    pub fn prune(&mut self, retention_days: u32, today: NaiveDate) -> usize {
+        if retention_days == 0 {
+            return 0;
+        }
+        let cutoff = (today - Duration::days(retention_days as i64))
+            .format("%Y%m%d")
+            .to_string();
+        let mut last_days = HashMap::<String, String>::new();
+        for day in self.days.values() {
+            let last = last_days.entry(day.subject.url().to_string()).or_default();
+            if day.date > *last {
+                *last = day.date.clone();
+            }
+        }
+        let total = self.days.len();
+        self.days.retain(|_, day| {
+            !day.synced || day.date >= cutoff || last_days.get(day.subject.url()) == Some(&day.date)
+        });
+        total - self.days.len()
+    }
+    /// Keeps pixels for the aggregate graph until the next sync.
+    pub fn defer(&mut self, pixels: impl IntoIterator<Item = Pixel>) {
+        self.deferred.extend(pixels);
+    }
+    pub fn take_deferred(&mut self) -> Vec<Pixel> {
+        std::mem::take(&mut self.deferred)
+    }
+    pub fn mark_synced(&mut self, pixel: &ComplexPixel) {
+        let key = UploadLedger::key(pixel.subject(), &pixel.date_pixela_formatted());
+        if let Some(day) = self.days.get_mut(&key) {
+            day.synced = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(minutes: Minutes) -> ComplexPixel {
+        ComplexPixel::new(
+            Progress::Int(minutes),
+            Subject::new_dummy(),
+            "20240304".into(),
+        )
+    }
+    fn hour_pixel(minutes: Minutes, date: &str) -> ComplexPixel {
+        let mut subject = Subject::new_dummy();
+        subject.set_unit(SubjectUnit::Hours);
+        ComplexPixel::new(Progress::Int(minutes), subject, date.into())
+    }
+    /// Writes every unsynced day and returns what was sent.
+    fn write(ledger: &mut UploadLedger) -> Vec<String> {
+        let unsynced = ledger.unsynced();
+        unsynced.iter().for_each(|day| ledger.mark_synced(day));
+        unsynced.iter().map(ComplexPixel::sendable_string).collect()
+    }
+
+    #[test]
+    fn minutes_from_the_website_are_kept() {
+        let mut ledger = UploadLedger::default();
+        ledger.record(&pixel(30), &Progress::Int(10), false);
+        assert_eq!(ledger.unsynced()[0].progress(), &Progress::Int(40));
+    }
+    #[test]
+    fn retries_keep_the_same_target() {
+        let mut ledger = UploadLedger::default();
+        ledger.record(&pixel(30), &Progress::Int(10), false);
+        // the write timed out but went through, the next pixel must not count the first twice
+        ledger.record(&pixel(20), &Progress::Int(40), false);
+        assert_eq!(ledger.unsynced()[0].progress(), &Progress::Int(60));
+    }
+    #[test]
+    fn synced_days_are_not_written_again() {
+        let mut ledger = UploadLedger::default();
+        ledger.record(&pixel(30), &Progress::Int(10), false);
+        write(&mut ledger);
+        assert!(ledger.unsynced().is_empty());
+    }
+    #[test]
+    fn minutes_taken_away_on_the_website_stay_away() {
+        let mut ledger = UploadLedger::default();
+        ledger.record(&pixel(30), &Progress::Int(30), false);
+        write(&mut ledger);
+        // 60 were written, someone took 15 away
+        ledger.record(&pixel(5), &Progress::Int(45), false);
+        assert_eq!(ledger.unsynced()[0].progress(), &Progress::Int(50));
+    }
+    #[test]
+    fn whole_hour_graphs_keep_the_remainder() {
+        let mut ledger = UploadLedger::default();
+        // 40 minutes twice make an hour, the 20 left over wait for more
+        ledger.record(&hour_pixel(40, "20240304"), &Progress::Int(0), true);
+        assert!(write(&mut ledger).is_empty());
+        ledger.record(&hour_pixel(40, "20240304"), &Progress::Int(0), true);
+        assert_eq!(write(&mut ledger), vec!["1"]);
+        ledger.record(&hour_pixel(30, "20240304"), &Progress::Int(1), true);
+        assert!(write(&mut ledger).is_empty());
+        assert_eq!(ledger.days.values().next().unwrap().remainder(), 50);
+    }
+    #[test]
+    fn the_next_day_starts_with_the_remainder() {
+        let mut ledger = UploadLedger::default();
+        ledger.record(&hour_pixel(50, "20240304"), &Progress::Int(0), true);
+        write(&mut ledger);
+
+        let preview = ledger.preview(&[(hour_pixel(10, "20240305"), None)], true);
+        assert_eq!((preview[0].carried_in(), preview[0].remainder()), (50, 0));
+        ledger.record(&hour_pixel(10, "20240305"), &Progress::Int(0), true);
+        assert_eq!(write(&mut ledger), vec!["1"]);
+        assert_eq!(ledger.days.values().next().unwrap().remainder(), 0);
+    }
+    #[test]
+    fn days_fetched_out_of_order_carry_in_date_order() {
+        let mut ledger = UploadLedger::default();
+        let mut fetched = vec![
+            (hour_pixel(30, "20240306"), Progress::Int(0)),
+            (hour_pixel(50, "20240304"), Progress::Int(0)),
+            (hour_pixel(50, "20240305"), Progress::Int(0)),
+        ];
+        UploadLedger::sort_for_recording(&mut fetched);
+        for (pixel, remote) in &fetched {
+            ledger.record(pixel, remote, true);
+        }
+        // 50 wait on the 4th, the 5th makes an hour of 100 and hands 40 on to the 6th
+        let carried: Vec<_> = ledger
+            .days
+            .values()
+            .map(|day| (day.carried_in(), day.remainder()))
+            .collect();
+        assert_eq!(carried, vec![(0, 0), (50, 0), (40, 10)]);
+        assert_eq!(write(&mut ledger), vec!["1", "1"]);
+    }
+    #[test]
+    fn ledgers_from_before_deferred_pixels_load() {
+        let mut ledger: UploadLedger = serde_json::from_str(r#"{"days":{}}"#).unwrap();
+        assert!(ledger.take_deferred().is_empty());
+    }
+    #[test]
+    fn written_days_past_the_retention_are_pruned() {
+        let mut ledger = UploadLedger::default();
+        ledger.record(&hour_pixel(60, "20240101"), &Progress::Int(0), false);
+        ledger.record(&hour_pixel(60, "20240301"), &Progress::Int(0), false);
+        write(&mut ledger);
+        let today = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        assert_eq!(ledger.prune(30, today), 1);
+        assert_eq!(ledger.days.values().next().unwrap().date(), "20240301");
+    }
+    #[test]
+    fn unwritten_and_last_days_outlive_the_retention() {
+        let mut ledger = UploadLedger::default();
+        ledger.record(&hour_pixel(60, "20240201"), &Progress::Int(0), false);
+        ledger.record(&hour_pixel(60, "20240301"), &Progress::Int(0), false);
+        write(&mut ledger);
+        // the write of this one is still to come
+        ledger.record(&hour_pixel(60, "20240101"), &Progress::Int(0), false);
+        let today = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        assert_eq!(ledger.prune(30, today), 1);
+        let dates: Vec<&str> = ledger.days.values().map(LedgerDay::date).collect();
+        assert_eq!(dates, vec!["20240101", "20240301"]);
+    }
+}
@@ -1,6 +1,8 @@
 use ratatui::widgets::ListState;
 use serde::{Deserialize, Serialize};
 
+use super::subjects::Progress;
+
 #[derive(Deserialize, Serialize)]
 pub struct PixelaResponse {
     #[serde(alias = "isSuccess")]
@@ -9,13 +11,22 @@ pub struct PixelaResponse {
     is_rejected: Option<bool>,
     message: String,
 }
+impl PixelaResponse {
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    /// Pixela randomly turned the request down, it goes through when asked again.
+    pub fn is_rejected(&self) -> bool {
+        self.is_rejected.unwrap_or(false)
+    }
+}
 #[derive(Deserialize, Serialize)]
 pub struct PixelaValue {
-    quantity: String,
+    quantity: Progress,
 }
 
 impl PixelaValue {
-    pub fn quantity(&self) -> &str {
+    pub fn quantity(&self) -> &Progress {
         &self.quantity
     }
 }
@@ -0,0 +1,167 @@
+use chrono::{DateTime, FixedOffset, Local};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use strum_macros::Display;
+
+use crate::timers::{counters::CounterMode, helper_structs::TimerState};
+
+use super::pixela::subjects::{Seconds, Subject};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Phase {
+    Work,
+    Break,
+}
+impl From<TimerState> for Phase {
+    fn from(value: TimerState) -> Self {
+        match value {
+            TimerState::Work(_) => Phase::Work,
+            TimerState::Break(_) => Phase::Break,
+        }
+    }
+}
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Plan {
+    Pomodoro,
+    Flowmodoro,
+}
+impl From<CounterMode> for Plan {
+    fn from(value: CounterMode) -> Self {
+        match value {
+            CounterMode::Countdown => Plan::Pomodoro,
+            CounterMode::Countup => Plan::Flowmodoro,
+        }
+    }
+}
+
+/// One finished work or break phase, as recorded by the timer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Session {
+    #[serde(with = "rfc3339")]
+    start: DateTime<FixedOffset>,
+    #[serde(with = "rfc3339")]
+    end: DateTime<FixedOffset>,
+    duration: Seconds,
+    phase: Phase,
+    plan: Plan,
+    iteration: u8,
+    subject_id: Option<String>,
+    subject_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task: Option<String>,
+    /// the tool an imported session came from, `None` for our own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    /// the machine a session came from through the sync folder, `None` for this one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    device: Option<String>,
+}
+impl Session {
+    pub fn new(
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        duration: Seconds,
+        phase: Phase,
+        plan: Plan,
+        iteration: u8,
+        subject: Option<&Subject>,
+    ) -> Session {
+        let subject = subject.filter(|subject| !subject.is_dummy());
+        Session {
+            start: start.fixed_offset(),
+            end: end.fixed_offset(),
+            duration,
+            phase,
+            plan,
+            iteration,
+            subject_id: subject.map(|subject| subject.id().to_string()),
+            subject_name: subject.map(|subject| subject.graph_name().to_string()),
+            task: None,
+            source: None,
+            device: None,
+        }
+    }
+    /// A work session read from another tool's export.
+    pub fn imported(
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        subject_id: Option<String>,
+        subject_name: Option<String>,
+        source: &str,
+    ) -> Session {
+        Session {
+            start: start.fixed_offset(),
+            end: end.fixed_offset(),
+            duration: (end - start).num_seconds(),
+            phase: Phase::Work,
+            plan: Plan::Pomodoro,
+            iteration: 0,
+            subject_id,
+            subject_name,
+            task: None,
+            source: Some(source.to_string()),
+            device: None,
+        }
+    }
+    /// Identifies a session by the time it covered, so importing the same rows twice is a no-op.
+    pub fn dedup_key(&self) -> String {
+        format!("{}-{}", self.start().timestamp(), self.end().timestamp())
+    }
+    pub fn with_task(mut self, task: Option<String>) -> Session {
+        self.task = task;
+        self
+    }
+    pub fn with_device(mut self, device: Option<String>) -> Session {
+        self.device = device;
+        self
+    }
+    pub fn start(&self) -> DateTime<FixedOffset> {
+        self.start
+    }
+    pub fn end(&self) -> DateTime<FixedOffset> {
+        self.end
+    }
+    pub fn duration(&self) -> Seconds {
+        self.duration
+    }
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+    pub fn plan(&self) -> Plan {
+        self.plan
+    }
+    pub fn iteration(&self) -> u8 {
+        self.iteration
+    }
+    pub fn subject_id(&self) -> Option<&str> {
+        self.subject_id.as_deref()
+    }
+    pub fn subject_name(&self) -> Option<&str> {
+        self.subject_name.as_deref()
+    }
+    pub fn task(&self) -> Option<&str> {
+        self.task.as_deref()
+    }
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+    pub fn device(&self) -> Option<&str> {
+        self.device.as_deref()
+    }
+}
+
+/// Dates stay rfc3339 strings in the files, a row with anything else doesn't deserialize.
+mod rfc3339 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(date: &DateTime<FixedOffset>, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&date.to_rfc3339())
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<DateTime<FixedOffset>, D::Error> {
+        let date = String::deserialize(d)?;
+        DateTime::parse_from_rfc3339(&date).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,428 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use chrono::{Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::Result,
+    settings::{data_dir, Settings},
+};
+
+use super::{history::History, pixel::Pixel, pixela::history_pull::PULL_SOURCE, session::Session};
+
+/// An id that stays with a pixel across machines.
+pub fn stable_id() -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let seed = format!(
+        "{}-{}-{}",
+        std::process::id(),
+        Local::now().timestamp_nanos_opt().unwrap_or_default(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    hex::encode(&Sha256::digest(seed)[..8])
+}
+
+/// One machine's pixels in the sync folder.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DevicePixels {
+    pending: Vec<Pixel>,
+    /// ids of pixels that were sent or deleted, so other machines drop them too
+    removed: Vec<String>,
+    /// id in `removed` -> yyyymmdd it was removed on
+    #[serde(default)]
+    removed_on: HashMap<String, String>,
+}
+impl DevicePixels {
+    /// Drops ids removed more than `retention_days` ago, 0 keeps everything.
+    /// Ids from before the dates were kept count from `today`.
+    fn prune(&mut self, retention_days: u32, today: NaiveDate) {
+        let today_formatted = today.format("%Y%m%d").to_string();
+        for id in &self.removed {
+            self.removed_on
+                .entry(id.clone())
+                .or_insert_with(|| today_formatted.clone());
+        }
+        if retention_days == 0 {
+            return;
+        }
+        let cutoff = (today - Duration::days(retention_days as i64))
+            .format("%Y%m%d")
+            .to_string();
+        self.removed_on
+            .retain(|_, removed_on| *removed_on >= cutoff);
+        let removed_on = &self.removed_on;
+        self.removed.retain(|id| removed_on.contains_key(id));
+    }
+}
+
+/// The same pixel or session differs between two machines, the local one is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub device: String,
+    pub description: String,
+}
+
+#[derive(Debug, Default)]
+pub struct PixelMerge {
+    pub pixels: Vec<Pixel>,
+    pub added: usize,
+    pub removed: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+#[derive(Debug, Default)]
+pub struct SessionMerge {
+    /// sessions other machines recorded that aren't in the history yet
+    pub sessions: Vec<Session>,
+    pub conflicts: Vec<Conflict>,
+    /// rows of the other machines' history files that couldn't be read
+    pub skipped_rows: usize,
+}
+
+/// What a sync took in from the other machines.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub pixels_added: usize,
+    pub pixels_removed: usize,
+    pub sessions_added: usize,
+    pub conflicts: Vec<Conflict>,
+    pub skipped_rows: usize,
+}
+
+/// A folder shared between machines, where every machine only writes its own files:
+/// `devices/<device>/pixels/<username>.json` and `devices/<device>/history.jsonl`.
+#[derive(Debug, Clone)]
+pub struct SyncFolder {
+    dir: PathBuf,
+    device: String,
+    /// days the ids of removed pixels are kept for, 0 keeps them forever
+    retention_days: u32,
+}
+impl SyncFolder {
+    pub fn new(dir: PathBuf, device: String, retention_days: u32) -> Self {
+        Self {
+            dir,
+            device,
+            retention_days,
+        }
+    }
+    /// None without a sync dir, or when this machine has no name and none can be saved.
+    pub fn from_settings(settings: &Settings) -> Option<SyncFolder> {
+        let sync_settings = &settings.sync_settings;
+        let dir = sync_settings.dir.as_ref().filter(|dir| !dir.is_empty())?;
+        let device = sync_settings
+            .device
+            .clone()
+            .filter(|device| !device.is_empty())
+            .or_else(SyncFolder::hostname)
+            .or_else(|| SyncFolder::device_id().ok())?;
+        let retention_days = settings.archive_settings.retention_days;
+        Some(SyncFolder::new(PathBuf::from(dir), device, retention_days))
+    }
+    fn hostname() -> Option<String> {
+        std::env::var("HOSTNAME")
+            .or_else(|_| std::env::var("COMPUTERNAME"))
+            .ok()
+            .or_else(|| fs::read_to_string("/etc/hostname").ok())
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+    }
+    /// A name made up for this machine the first time it's needed, kept in `device_id`
+    /// so two machines without a hostname don't write to the same files.
+    fn device_id() -> Result<String> {
+        let path = data_dir()?.join("device_id");
+        if let Ok(id) = fs::read_to_string(&path) {
+            if !id.trim().is_empty() {
+                return Ok(id.trim().to_string());
+            }
+        }
+        let id = format!("rtuion-{}", stable_id());
+        fs::create_dir_all(data_dir()?)?;
+        fs::write(path, &id)?;
+        Ok(id)
+    }
+    pub fn device(&self) -> &str {
+        &self.device
+    }
+    fn device_dir(&self, device: &str) -> PathBuf {
+        self.dir.join("devices").join(device)
+    }
+    /// Every machine that wrote to the folder, this one included.
+    fn devices(&self) -> Result<Vec<String>> {
+        let path = self.dir.join("devices");
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let mut devices = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                devices.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        devices.sort();
+        Ok(devices)
+    }
+    fn pixels_path(&self, device: &str, username: &str) -> PathBuf {
+        self.device_dir(device)
+            .join("pixels")
+            .join(format!("{username}.json"))
+    }
+    fn read_pixels(&self, device: &str, username: &str) -> Result<DevicePixels> {
+        let path = self.pixels_path(device, username);
+        if !path.exists() {
+            return Ok(DevicePixels::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+    /// Replaces this machine's pixel file, `removed` adds to the ids it already dropped.
+    pub fn write_pixels(&self, username: &str, pixels: &[Pixel], removed: &[String]) -> Result<()> {
+        let mut own = self.read_pixels(&self.device, username)?;
+        own.pending = pixels.to_vec();
+        for id in removed {
+            if !own.removed.contains(id) {
+                own.removed.push(id.clone());
+            }
+        }
+        own.prune(self.retention_days, Local::now().date_naive());
+        let path = self.pixels_path(&self.device, username);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(&own)?)?;
+        Ok(())
+    }
+    /// The local pixels plus the ones other machines have pending, without any that were
+    /// sent or deleted anywhere. `local` needs ids, see `Pixel::ensure_id`.
+    pub fn merge_pixels(
+        &self,
+        username: &str,
+        local: Vec<Pixel>,
+        removed: &[String],
+    ) -> Result<PixelMerge> {
+        let mut others = Vec::new();
+        let mut tombstones: HashSet<String> = removed.iter().cloned().collect();
+        for device in self.devices()? {
+            let pixels = self.read_pixels(&device, username)?;
+            tombstones.extend(pixels.removed);
+            if device != self.device {
+                others.push((device, pixels.pending));
+            }
+        }
+        Ok(merge_pixels(local, others, &tombstones))
+    }
+    /// Recorded here, pulled sessions are Pixela's and every machine can pull them itself.
+    fn is_shared(session: &Session) -> bool {
+        session.device().is_none() && session.source() != Some(PULL_SOURCE)
+    }
+    /// Replaces this machine's history file with the sessions recorded here.
+    pub fn write_sessions(&self, history: &[Session]) -> Result<()> {
+        let mut contents = String::new();
+        for session in history
+            .iter()
+            .filter(|session| SyncFolder::is_shared(session))
+        {
+            contents.push_str(&serde_json::to_string(session)?);
+            contents.push('\n');
+        }
+        let dir = self.device_dir(&self.device);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("history.jsonl"), contents)?;
+        Ok(())
+    }
+    /// Adds one session to this machine's history file, the whole file is written on sync.
+    pub fn append_session(&self, session: &Session) -> Result<()> {
+        if !SyncFolder::is_shared(session) {
+            return Ok(());
+        }
+        let dir = self.device_dir(&self.device);
+        fs::create_dir_all(&dir)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join("history.jsonl"))?;
+        writeln!(file, "{}", serde_json::to_string(session)?)?;
+        Ok(())
+    }
+    pub fn merge_sessions(&self, history: &[Session]) -> Result<SessionMerge> {
+        let mut others = Vec::new();
+        let mut skipped_rows = 0;
+        for device in self.devices()? {
+            let path = self.device_dir(&device).join("history.jsonl");
+            if device == self.device || !path.exists() {
+                continue;
+            }
+            let (sessions, skipped) = History::parse(&fs::read_to_string(path)?);
+            skipped_rows += skipped;
+            others.push((device, sessions));
+        }
+        let mut merge = merge_sessions(history, others);
+        merge.skipped_rows = skipped_rows;
+        Ok(merge)
+    }
+}
+
+fn merge_pixels(
+    local: Vec<Pixel>,
+    others: Vec<(String, Vec<Pixel>)>,
+    tombstones: &HashSet<String>,
+) -> PixelMerge {
+    let mut merge = PixelMerge::default();
+    for pixel in local {
+        match pixel.id().is_some_and(|id| tombstones.contains(id)) {
+            true => merge.removed += 1,
+            false => merge.pixels.push(pixel),
+        }
+    }
+    for (device, pending) in others {
+        for pixel in pending {
+            let Some(id) = pixel.id() else {
+                continue;
+            };
+            if tombstones.contains(id) {
+                continue;
+            }
+            match merge.pixels.iter().find(|known| known.id() == Some(id)) {
+                Some(known) if *known != pixel => merge.conflicts.push(Conflict {
+                    device: device.clone(),
+                    description: format!("pixel {id} differs"),
+                }),
+                Some(_) => {}
+                None => {
+                    merge.pixels.push(pixel);
+                    merge.added += 1;
+                }
+            }
+        }
+    }
+    merge
+}
+
+fn merge_sessions(history: &[Session], others: Vec<(String, Vec<Session>)>) -> SessionMerge {
+    let mut merge = SessionMerge::default();
+    let mut known: HashMap<String, Session> = history
+        .iter()
+        .map(|session| (session.dedup_key(), session.clone().with_device(None)))
+        .collect();
+    for (device, sessions) in others {
+        for session in sessions {
+            let key = session.dedup_key();
+            match known.get(&key) {
+                Some(local) if *local != session => merge.conflicts.push(Conflict {
+                    device: device.clone(),
+                    description: format!(
+                        "session at {} differs",
+                        session.start().format("%Y-%m-%d %H:%M")
+                    ),
+                }),
+                Some(_) => {}
+                None => {
+                    known.insert(key, session.clone());
+                    merge
+                        .sessions
+                        .push(session.with_device(Some(device.clone())));
+                }
+            }
+        }
+    }
+    merge
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+    use crate::stats::pixel::SimplePixel;
+
+    fn pixel(minutes: usize) -> Pixel {
+        let mut pixel = Pixel::Simple(SimplePixel::new(minutes, "2024/03/04 10:00".into()));
+        pixel.ensure_id();
+        pixel
+    }
+    fn session(minutes: i64) -> Session {
+        let start = Local.with_ymd_and_hms(2024, 3, 4, 10, 0, 0).unwrap();
+        Session::imported(start, start + Duration::minutes(minutes), None, None, "x")
+    }
+
+    #[test]
+    fn pixels_from_other_devices_are_added() {
+        let (kept, theirs) = (pixel(25), pixel(50));
+        let merge = merge_pixels(
+            vec![kept.clone()],
+            vec![("desktop".into(), vec![kept.clone(), theirs.clone()])],
+            &HashSet::new(),
+        );
+        assert_eq!(merge.pixels, vec![kept, theirs]);
+        assert_eq!((merge.added, merge.removed), (1, 0));
+    }
+    #[test]
+    fn sent_pixels_are_removed_everywhere() {
+        let sent = pixel(25);
+        // this machine sent the pixel, the desktop still has it
+        let tombstones = HashSet::from([sent.id().unwrap().to_string()]);
+        let merge = merge_pixels(
+            vec![sent.clone()],
+            vec![("desktop".into(), vec![sent])],
+            &tombstones,
+        );
+        assert!(merge.pixels.is_empty());
+        assert_eq!((merge.added, merge.removed), (0, 1));
+    }
+    #[test]
+    fn pixels_changed_elsewhere_are_conflicts() {
+        let kept = pixel(25);
+        let mut changed = kept.clone();
+        if let Pixel::Simple(simple) = &mut changed {
+            simple.set_task(Some("elsewhere".into()));
+        }
+        let merge = merge_pixels(
+            vec![kept.clone()],
+            vec![("desktop".into(), vec![changed])],
+            &HashSet::new(),
+        );
+        assert_eq!(merge.pixels, vec![kept]);
+        assert_eq!(merge.conflicts.len(), 1);
+    }
+    #[test]
+    fn old_tombstones_are_pruned() {
+        let mut pixels = DevicePixels {
+            removed: vec!["old".into(), "new".into()],
+            removed_on: HashMap::from([("old".into(), "20240101".into())]),
+            ..Default::default()
+        };
+        pixels.prune(30, NaiveDate::from_ymd_opt(2024, 3, 4).unwrap());
+        assert_eq!(pixels.removed, vec!["new".to_string()]);
+        assert_eq!(pixels.removed_on["new"], "20240304");
+    }
+    #[test]
+    fn finished_sessions_are_appended() {
+        let dir = tempfile::tempdir().unwrap();
+        let sync = SyncFolder::new(dir.path().to_path_buf(), "laptop".into(), 0);
+        sync.write_sessions(&[session(25)]).unwrap();
+        sync.append_session(&session(50)).unwrap();
+        let path = dir.path().join("devices/laptop/history.jsonl");
+        let (sessions, skipped) = History::parse(&fs::read_to_string(path).unwrap());
+        assert_eq!((sessions, skipped), (vec![session(25), session(50)], 0));
+    }
+    #[test]
+    fn sessions_from_other_devices_keep_their_device() {
+        let merge = merge_sessions(&[], vec![("desktop".into(), vec![session(50)])]);
+        assert_eq!(merge.sessions.len(), 1);
+        assert_eq!(merge.sessions[0].device(), Some("desktop"));
+    }
+    #[test]
+    fn sessions_changed_elsewhere_are_conflicts() {
+        let theirs = session(25).with_task(Some("elsewhere".into()));
+        let merge = merge_sessions(&[session(25)], vec![("desktop".into(), vec![theirs])]);
+        assert!(merge.sessions.is_empty());
+        assert_eq!(merge.conflicts.len(), 1);
+    }
+}
@@ -0,0 +1,25 @@
+use std::collections::HashMap;
+
+use crate::stats::pixela::subjects::Subject;
+
+pub mod task;
+pub mod task_list;
+pub mod taskwarrior;
+pub mod todo_txt;
+
+/// Finds the Pixela subject for a project, through the configured mapping
+/// (project -> graph id) or by matching the graph id or name.
+pub fn subject_for_project<'a>(
+    project: &str,
+    mapping: &HashMap<String, String>,
+    subjects: &[&'a Subject],
+) -> Option<&'a Subject> {
+    let wanted = mapping.get(project).map(String::as_str).unwrap_or(project);
+    subjects
+        .iter()
+        .find(|subject| {
+            !subject.is_dummy()
+                && (subject.id() == wanted || subject.graph_name().eq_ignore_ascii_case(wanted))
+        })
+        .copied()
+}
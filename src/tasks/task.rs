@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use crate::stats::pixela::subjects::Subject;
+
+use super::{taskwarrior::TaskwarriorTask, todo_txt::TodoItem};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Task {
+    id: u64,
+    title: String,
+    estimate: u8,
+    #[serde(default)]
+    completed: u8,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    subject_id: Option<String>,
+    #[serde(default)]
+    subject_name: Option<String>,
+    #[serde(default)]
+    todo: Option<String>,
+    #[serde(default)]
+    taskwarrior: Option<String>,
+}
+impl Task {
+    pub fn new(id: u64, title: String, estimate: u8) -> Task {
+        Task {
+            id,
+            title,
+            estimate,
+            completed: 0,
+            done: false,
+            subject_id: None,
+            subject_name: None,
+            todo: None,
+            taskwarrior: None,
+        }
+    }
+    pub fn from_todo(id: u64, item: &TodoItem, estimate: u8) -> Task {
+        let mut task = Task::new(id, item.key(), estimate);
+        task.todo = Some(item.key());
+        task.done = item.done;
+        task
+    }
+    pub fn from_taskwarrior(id: u64, item: &TaskwarriorTask, estimate: u8) -> Task {
+        let mut task = Task::new(id, item.description.clone(), estimate);
+        task.taskwarrior = Some(item.uuid.clone());
+        task
+    }
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    pub fn estimate(&self) -> u8 {
+        self.estimate
+    }
+    pub fn completed(&self) -> u8 {
+        self.completed
+    }
+    pub fn done(&self) -> bool {
+        self.done
+    }
+    pub fn subject_id(&self) -> Option<&str> {
+        self.subject_id.as_deref()
+    }
+    pub fn subject_name(&self) -> Option<&str> {
+        self.subject_name.as_deref()
+    }
+    /// Key of the todo.txt line this task was picked from.
+    pub fn todo(&self) -> Option<&str> {
+        self.todo.as_deref()
+    }
+    /// UUID of the Taskwarrior task this task was picked from.
+    pub fn taskwarrior(&self) -> Option<&str> {
+        self.taskwarrior.as_deref()
+    }
+    pub fn set_subject(&mut self, subject: Option<&Subject>) {
+        let subject = subject.filter(|subject| !subject.is_dummy());
+        self.subject_id = subject.map(|subject| subject.id().to_string());
+        self.subject_name = subject.map(|subject| subject.graph_name().to_string());
+    }
+    pub fn increase_estimate(&mut self) {
+        self.estimate = self.estimate.saturating_add(1);
+    }
+    pub fn decrease_estimate(&mut self) {
+        self.estimate = self.estimate.saturating_sub(1).max(1);
+    }
+    pub fn complete_pomodoro(&mut self) {
+        self.completed = self.completed.saturating_add(1);
+    }
+    pub fn toggle_done(&mut self) {
+        self.done = !self.done;
+    }
+    pub fn over_estimate(&self) -> bool {
+        self.completed > self.estimate
+    }
+    pub fn progress_string(&self) -> String {
+        format!("{}/{}", self.completed, self.estimate)
+    }
+}
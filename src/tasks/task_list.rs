@@ -0,0 +1,282 @@
+use std::{fs, io, path::PathBuf};
+
+use chrono::Local;
+use crossterm::event::KeyEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Result, SettingsError},
+    settings::{data_dir, Settings},
+    stats::pixela::{subjects::Subject, utils::StatefulList},
+    DEFAULT_TASK_ESTIMATE,
+};
+
+use super::{task::Task, taskwarrior::TaskwarriorTask, todo_txt::TodoItem};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TaskFile {
+    #[serde(default)]
+    active: Option<u64>,
+    #[serde(default)]
+    tasks: Vec<Task>,
+}
+#[derive(Debug)]
+pub struct TaskList {
+    pub tasks: StatefulList<Task>,
+    active: Option<u64>,
+    input: Option<String>,
+    path: Option<PathBuf>,
+}
+impl Default for TaskList {
+    fn default() -> Self {
+        TaskList {
+            tasks: StatefulList::new(Vec::new()),
+            active: None,
+            input: None,
+            path: None,
+        }
+    }
+}
+impl TaskList {
+    /// Loads `tasks.json` from the config dir, starting empty when there is none yet.
+    pub fn load() -> Result<TaskList> {
+        TaskList::load_from(TaskList::default_path()?)
+    }
+    /// An unreadable file is moved aside and reported, so the next save can't overwrite it.
+    fn load_from(path: PathBuf) -> Result<TaskList> {
+        let file: TaskFile = match fs::read_to_string(&path) {
+            Ok(data) => match serde_json::from_str(&data) {
+                Ok(file) => file,
+                Err(_) => {
+                    let stamp = Local::now().format("%Y%m%d%H%M%S");
+                    let aside = path.with_extension(format!("corrupt-{stamp}.json"));
+                    fs::rename(&path, &aside)?;
+                    return Err(SettingsError::LoadError(format!(
+                        "tasks.json couldn't be read, it was moved to {}",
+                        aside.display()
+                    ))
+                    .into());
+                }
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => TaskFile::default(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(TaskList {
+            tasks: StatefulList::new(file.tasks),
+            active: file.active,
+            input: None,
+            path: Some(path),
+        })
+    }
+    fn default_path() -> Result<PathBuf> {
+        Ok(data_dir()?.join("tasks.json"))
+    }
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)?
+            }
+        }
+        let file = TaskFile {
+            active: self.active,
+            tasks: self.tasks.items().to_vec(),
+        };
+        fs::write(path, serde_json::to_string(&file)?)?;
+        Ok(())
+    }
+    fn next_id(&self) -> u64 {
+        self.tasks
+            .iter()
+            .map(|task| task.id())
+            .max()
+            .map_or(1, |id| id + 1)
+    }
+    pub fn add(&mut self, title: String) {
+        let id = self.next_id();
+        self.push_selected(Task::new(id, title, DEFAULT_TASK_ESTIMATE));
+    }
+    fn push_selected(&mut self, task: Task) {
+        self.tasks.push(task);
+        let last = self.tasks.items().len() - 1;
+        self.tasks.state_mut().select(Some(last));
+    }
+    /// Activates the task linked to a todo.txt item, creating it on first pick.
+    pub fn pick_todo(&mut self, item: &TodoItem, subject: Option<&Subject>) {
+        let key = item.key();
+        let estimate = item
+            .tag("est")
+            .and_then(|estimate| estimate.parse().ok())
+            .unwrap_or(DEFAULT_TASK_ESTIMATE);
+        self.pick_linked(
+            |task| task.todo() == Some(&key),
+            |id| Task::from_todo(id, item, estimate),
+            subject,
+        );
+    }
+    /// Activates the task linked to a Taskwarrior task, creating it on first pick.
+    pub fn pick_taskwarrior(&mut self, item: &TaskwarriorTask, subject: Option<&Subject>) {
+        self.pick_linked(
+            |task| task.taskwarrior() == Some(item.uuid.as_str()),
+            |id| Task::from_taskwarrior(id, item, DEFAULT_TASK_ESTIMATE),
+            subject,
+        );
+    }
+    fn pick_linked(
+        &mut self,
+        linked: impl Fn(&Task) -> bool,
+        create: impl FnOnce(u64) -> Task,
+        subject: Option<&Subject>,
+    ) {
+        let index = match self.tasks.iter().position(linked) {
+            Some(index) => index,
+            None => {
+                self.push_selected(create(self.next_id()));
+                self.tasks.items().len() - 1
+            }
+        };
+        let task = &mut self.tasks.items_mut()[index];
+        if subject.is_some() {
+            task.set_subject(subject);
+        }
+        self.active = Some(task.id());
+        self.tasks.state_mut().select(Some(index));
+    }
+    pub fn remove_selected(&mut self) {
+        if let Some(index) = self.tasks.state().selected() {
+            if index < self.tasks.items().len() {
+                let task = self.tasks.items_mut().remove(index);
+                if self.active == Some(task.id()) {
+                    self.active = None;
+                }
+            }
+        }
+        self.tasks.refresh_state();
+    }
+    pub fn selected(&self) -> Option<&Task> {
+        self.tasks.items().get(self.tasks.state().selected()?)
+    }
+    pub fn selected_mut(&mut self) -> Option<&mut Task> {
+        let index = self.tasks.state().selected()?;
+        self.tasks.items_mut().get_mut(index)
+    }
+    /// Makes the selected task the active one, or clears it when it already is.
+    pub fn toggle_active_selected(&mut self) {
+        let Some(id) = self.selected().map(|task| task.id()) else {
+            return;
+        };
+        self.active = match self.active == Some(id) {
+            true => None,
+            false => Some(id),
+        };
+    }
+    pub fn active_task(&self) -> Option<&Task> {
+        let active = self.active?;
+        self.tasks.iter().find(|task| task.id() == active)
+    }
+    pub fn is_active(&self, task: &Task) -> bool {
+        self.active == Some(task.id())
+    }
+    pub fn complete_active_pomodoro(&mut self) -> Option<&Task> {
+        let active = self.active?;
+        let task = self
+            .tasks
+            .items_mut()
+            .iter_mut()
+            .find(|task| task.id() == active)?;
+        task.complete_pomodoro();
+        Some(task)
+    }
+    pub fn set_selected_subject(&mut self, subject: Option<&Subject>) {
+        if let Some(task) = self.selected_mut() {
+            // picking the subject the task already has unassigns it
+            let same = subject.map(|subject| subject.id()) == task.subject_id();
+            task.set_subject(if same { None } else { subject });
+        }
+    }
+    pub fn is_typing(&self) -> bool {
+        self.input.is_some()
+    }
+    pub fn input_text(&self) -> Option<&str> {
+        self.input.as_deref()
+    }
+    pub fn start_input(&mut self) {
+        self.input = Some(String::new());
+    }
+    pub fn cancel_input(&mut self) {
+        self.input = None;
+    }
+    pub fn input(&mut self, key: KeyEvent, clipboard_response: Option<String>) {
+        if let Some(text) = self.input.as_mut() {
+            Settings::process_input(key, text, clipboard_response);
+        }
+    }
+    pub fn finish_input(&mut self) {
+        if let Some(title) = self.input.take() {
+            let title = title.trim();
+            if !title.is_empty() {
+                self.add(title.to_string());
+            }
+        }
+    }
+    pub fn select_next(&mut self) {
+        self.tasks.select_next()
+    }
+    pub fn select_previous(&mut self) {
+        self.tasks.select_previous()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two tasks, the second one selected
+    fn tasks() -> TaskList {
+        let mut tasks = TaskList::default();
+        tasks.add("Write report".into());
+        tasks.add("Read paper".into());
+        tasks
+    }
+
+    #[test]
+    fn pomodoros_without_active_task_count_for_nothing() {
+        assert!(tasks().complete_active_pomodoro().is_none());
+    }
+    #[test]
+    fn completed_pomodoros_count_against_active_task() {
+        let mut tasks = tasks();
+        tasks.toggle_active_selected();
+        tasks.complete_active_pomodoro();
+        tasks.complete_active_pomodoro();
+        let active = tasks.active_task().unwrap();
+        assert_eq!(active.title(), "Read paper");
+        assert_eq!(active.progress_string(), "2/1");
+        assert!(active.over_estimate());
+    }
+    #[test]
+    fn removing_the_active_task_clears_it() {
+        let mut tasks = tasks();
+        tasks.toggle_active_selected();
+        tasks.remove_selected();
+        assert!(tasks.active_task().is_none());
+        assert_eq!(tasks.tasks.items().len(), 1);
+    }
+    #[test]
+    fn unreadable_task_files_are_moved_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        fs::write(&path, "{\"tasks\": [").unwrap();
+
+        assert!(TaskList::load_from(path.clone()).is_err());
+        assert!(!path.exists());
+        let aside: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        assert_eq!(aside, ["{\"tasks\": ["]);
+        assert!(TaskList::load_from(path).unwrap().tasks.items().is_empty());
+    }
+}
@@ -0,0 +1,239 @@
+use std::{process::Stdio, time::Duration};
+
+use serde::Deserialize;
+use tokio::{
+    process::Command,
+    sync::mpsc::{self, Sender},
+    time,
+};
+
+use crate::{
+    app::Event,
+    error::{IntegrationError, Result},
+    settings::TaskwarriorSettings,
+    stats::pixela::subjects::Subject,
+    TASKWARRIOR_TIMEOUT_SECS,
+};
+
+use super::{subject_for_project, task::Task};
+
+// keeps `task` from prompting, we have no terminal to answer it on
+const QUIET_ARGS: [&str; 3] = ["rc.confirmation=off", "rc.bulk=0", "rc.verbose=nothing"];
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct TaskwarriorTask {
+    pub uuid: String,
+    pub description: String,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub urgency: f64,
+}
+impl TaskwarriorTask {
+    pub fn label(&self) -> String {
+        let mut label = self.description.clone();
+        if let Some(project) = &self.project {
+            label.push_str(&format!(" | {project}"));
+        }
+        for tag in &self.tags {
+            label.push_str(&format!(" +{tag}"));
+        }
+        label
+    }
+    pub fn subject<'a>(
+        &self,
+        settings: &TaskwarriorSettings,
+        subjects: &[&'a Subject],
+    ) -> Option<&'a Subject> {
+        let project = self.project.as_deref()?;
+        // nested projects fall back to their parents, "work.thesis" -> "work"
+        let mut candidates = vec![project];
+        candidates.extend(
+            project
+                .rmatch_indices('.')
+                .map(|(index, _)| &project[..index]),
+        );
+        candidates
+            .into_iter()
+            .find_map(|project| subject_for_project(project, &settings.projects, subjects))
+    }
+}
+
+/// A queued `task` command and where to report it failing.
+type Queued = (Vec<String>, Option<Sender<Event>>);
+
+/// Runs `task` commands one after another in the background, so start/stop/modify
+/// for the same task never overtake each other.
+#[derive(Debug)]
+pub struct Taskwarrior {
+    pomodoro_uda: Option<String>,
+    annotate: bool,
+    follow_phases: bool,
+    queue: mpsc::UnboundedSender<Queued>,
+    /// the task `start` was last sent for
+    started: Option<String>,
+    event_tx: Option<Sender<Event>>,
+}
+impl Taskwarrior {
+    pub fn new(settings: &TaskwarriorSettings) -> Option<Taskwarrior> {
+        if !settings.enabled {
+            return None;
+        }
+        let (queue, mut rx) = mpsc::unbounded_channel::<Queued>();
+        let command = settings.command.clone();
+        tokio::spawn(async move {
+            while let Some((args, event_tx)) = rx.recv().await {
+                if let (Err(err), Some(event_tx)) =
+                    (Taskwarrior::run(&command, &args).await, event_tx)
+                {
+                    let _ = event_tx.send(Event::BackgroundError(err)).await;
+                }
+            }
+        });
+        Some(Taskwarrior {
+            pomodoro_uda: settings.pomodoro_uda.clone().filter(|uda| !uda.is_empty()),
+            annotate: settings.annotate,
+            follow_phases: settings.follow_phases,
+            queue,
+            started: None,
+            event_tx: None,
+        })
+    }
+    pub fn set_event_tx(&mut self, event_tx: Sender<Event>) {
+        self.event_tx = Some(event_tx);
+    }
+    async fn run(command: &str, args: &[String]) -> Result<Vec<u8>> {
+        let call = format!("{command} {}", args.join(" "));
+        let output = Command::new(command)
+            .args(QUIET_ARGS)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .output();
+        // dropping the output future kills a `task` that hangs
+        let output = time::timeout(Duration::from_secs(TASKWARRIOR_TIMEOUT_SECS), output)
+            .await
+            .map_err(|_| IntegrationError::TimedOut(call.clone()))??;
+        match output.status.success() {
+            true => Ok(output.stdout),
+            false => Err(IntegrationError::ExportFailed(call).into()),
+        }
+    }
+    pub async fn pending(settings: &TaskwarriorSettings) -> Result<Vec<TaskwarriorTask>> {
+        let args = ["status:pending".to_string(), "export".to_string()];
+        let output = Taskwarrior::run(&settings.command, &args).await?;
+        let mut tasks: Vec<TaskwarriorTask> = serde_json::from_slice(&output)?;
+        tasks.sort_by(|a, b| b.urgency.total_cmp(&a.urgency));
+        Ok(tasks)
+    }
+    fn queue(&self, uuid: &str, args: &[&str]) {
+        let mut command = vec![uuid.to_string()];
+        command.extend(args.iter().map(|arg| arg.to_string()));
+        let _ = self.queue.send((command, self.event_tx.clone()));
+    }
+    /// Starts the task, stopping the one started before.
+    pub fn start(&mut self, task: &Task) {
+        let uuid = task.taskwarrior().filter(|_| self.follow_phases);
+        if self.started.as_deref() == uuid {
+            return;
+        }
+        self.stop();
+        if let Some(uuid) = uuid {
+            self.queue(uuid, &["start"]);
+            self.started = Some(uuid.to_string());
+        }
+    }
+    /// Stops the task that was started, whichever task is active now.
+    pub fn stop(&mut self) {
+        if let Some(uuid) = self.started.take() {
+            self.queue(&uuid, &["stop"]);
+        }
+    }
+    /// Records a finished pomodoro as an annotation and/or in the configured UDA.
+    pub fn record_pomodoro(&self, task: &Task, minutes: i64) {
+        let Some(uuid) = task.taskwarrior() else {
+            return;
+        };
+        if let Some(uda) = &self.pomodoro_uda {
+            self.queue(uuid, &["modify", &format!("{uda}:{}", task.completed())]);
+        }
+        if self.annotate {
+            let note = format!("rtuion: pomodoro {} done ({minutes} min)", task.completed());
+            self.queue(uuid, &["annotate", &note]);
+        }
+    }
+    pub fn set_done(&self, task: &Task) {
+        let Some(uuid) = task.taskwarrior() else {
+            return;
+        };
+        match task.done() {
+            true => self.queue(uuid, &["done"]),
+            false => self.queue(uuid, &["modify", "status:pending"]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task() -> TaskwarriorTask {
+        let export = r#"[{"id":1,"uuid":"a1b2","description":"Write chapter","project":"thesis.writing",
+            "tags":["deep"],"urgency":4.2,"status":"pending"}]"#;
+        let mut tasks: Vec<TaskwarriorTask> = serde_json::from_str(export).unwrap();
+        tasks.remove(0)
+    }
+
+    #[test]
+    fn label_shows_project_and_tags() {
+        assert_eq!(task().label(), "Write chapter | thesis.writing +deep");
+    }
+    /// A Taskwarrior whose every command fails
+    fn failing_taskwarrior() -> Taskwarrior {
+        let settings = TaskwarriorSettings {
+            enabled: true,
+            command: "false".into(),
+            ..Default::default()
+        };
+        Taskwarrior::new(&settings).unwrap()
+    }
+    #[tokio::test]
+    async fn switching_tasks_stops_the_started_one() {
+        let mut taskwarrior = failing_taskwarrior();
+        let mut other = task();
+        other.uuid = "c3d4".into();
+        taskwarrior.start(&Task::from_taskwarrior(1, &task(), 1));
+        taskwarrior.start(&Task::from_taskwarrior(2, &other, 1));
+        assert_eq!(taskwarrior.started.as_deref(), Some("c3d4"));
+        taskwarrior.start(&Task::new(3, "Not in Taskwarrior".into(), 1));
+        assert!(taskwarrior.started.is_none());
+    }
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failed_commands_are_reported() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut taskwarrior = failing_taskwarrior();
+        taskwarrior.set_event_tx(tx);
+        taskwarrior.start(&Task::from_taskwarrior(1, &task(), 1));
+        let event = time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap();
+        assert!(matches!(event, Some(Event::BackgroundError(_))));
+    }
+    #[test]
+    fn subprojects_fall_back_to_the_parent_project() {
+        let subject: Subject = serde_json::from_str(
+            r#"{"id":"thesis-graph","name":"Thesis","type":"int","unit":"minutes","color":"sora"}"#,
+        )
+        .unwrap();
+        let mut settings = TaskwarriorSettings::default();
+        settings
+            .projects
+            .insert("thesis".into(), "thesis-graph".into());
+        let found = task().subject(&settings, &[&subject]);
+        assert_eq!(found.map(|subject| subject.id()), Some("thesis-graph"));
+    }
+}
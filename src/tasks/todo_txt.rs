@@ -0,0 +1,351 @@
+use std::{fs, path::PathBuf};
+
+use chrono::NaiveDate;
+
+use crate::{
+    error::{Result, SettingsError},
+    settings::TodoTxtSettings,
+    stats::pixela::subjects::Subject,
+    TODO_TXT_WRITE_ATTEMPTS,
+};
+
+use super::{subject_for_project, task::Task};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// One line of a todo.txt file, see <https://github.com/todotxt/todo.txt>.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TodoItem {
+    pub done: bool,
+    pub priority: Option<char>,
+    pub completion_date: Option<NaiveDate>,
+    pub creation_date: Option<NaiveDate>,
+    pub description: String,
+}
+impl TodoItem {
+    pub fn parse(line: &str) -> Option<TodoItem> {
+        let mut rest = line.trim();
+        if rest.is_empty() {
+            return None;
+        }
+        let done = rest.starts_with("x ");
+        if done {
+            rest = rest[2..].trim_start();
+        }
+        let mut priority = None;
+        if !done {
+            let bytes = rest.as_bytes();
+            if bytes.len() > 3
+                && bytes[0] == b'('
+                && bytes[1].is_ascii_uppercase()
+                && bytes[2] == b')'
+                && bytes[3] == b' '
+            {
+                priority = Some(bytes[1] as char);
+                rest = rest[4..].trim_start();
+            }
+        }
+        let mut completion_date = None;
+        if done {
+            if let Some((date, tail)) = take_date(rest) {
+                completion_date = Some(date);
+                rest = tail;
+            }
+        }
+        let mut creation_date = None;
+        if let Some((date, tail)) = take_date(rest) {
+            creation_date = Some(date);
+            rest = tail;
+        }
+        Some(TodoItem {
+            done,
+            priority,
+            completion_date,
+            creation_date,
+            description: rest.to_string(),
+        })
+    }
+    pub fn projects(&self) -> Vec<&str> {
+        self.words_with_prefix('+')
+    }
+    pub fn contexts(&self) -> Vec<&str> {
+        self.words_with_prefix('@')
+    }
+    fn words_with_prefix(&self, prefix: char) -> Vec<&str> {
+        self.description
+            .split_whitespace()
+            .filter_map(|word| word.strip_prefix(prefix))
+            .filter(|word| !word.is_empty())
+            .collect()
+    }
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.description
+            .split_whitespace()
+            .filter_map(split_tag)
+            .find(|(tag, _)| *tag == key)
+            .map(|(_, value)| value)
+    }
+    pub fn set_tag(&mut self, key: &str, value: &str) {
+        let mut found = false;
+        let mut words: Vec<String> = self
+            .description
+            .split_whitespace()
+            .map(|word| match split_tag(word) {
+                Some((tag, _)) if tag == key => {
+                    found = true;
+                    format!("{key}:{value}")
+                }
+                _ => word.to_string(),
+            })
+            .collect();
+        if !found {
+            words.push(format!("{key}:{value}"));
+        }
+        self.description = words.join(" ");
+    }
+    /// The description without `key:value` tags, so it stays stable while we annotate it.
+    pub fn key(&self) -> String {
+        self.description
+            .split_whitespace()
+            .filter(|word| split_tag(word).is_none())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+    pub fn complete(&mut self, date: NaiveDate) {
+        if self.done {
+            return;
+        }
+        // completed tasks keep their priority as a tag, as the format suggests
+        if let Some(priority) = self.priority.take() {
+            self.set_tag("pri", &priority.to_string());
+        }
+        self.done = true;
+        self.completion_date = Some(date);
+    }
+    pub fn reopen(&mut self) {
+        if !self.done {
+            return;
+        }
+        if let Some(priority) = self.tag("pri").and_then(|pri| pri.chars().next()) {
+            self.priority = Some(priority);
+            self.description = self
+                .description
+                .split_whitespace()
+                .filter(|word| !word.starts_with("pri:"))
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+        self.done = false;
+        self.completion_date = None;
+    }
+    pub fn to_line(&self) -> String {
+        let mut parts = Vec::new();
+        if self.done {
+            parts.push("x".to_string());
+        }
+        if let Some(priority) = self.priority {
+            parts.push(format!("({priority})"));
+        }
+        if let Some(date) = self.completion_date {
+            parts.push(date.format(DATE_FORMAT).to_string());
+        }
+        if let Some(date) = self.creation_date {
+            parts.push(date.format(DATE_FORMAT).to_string());
+        }
+        parts.push(self.description.clone());
+        parts.join(" ")
+    }
+    /// Finds the Pixela subject for the first +project that maps to one.
+    pub fn subject<'a>(
+        &self,
+        settings: &TodoTxtSettings,
+        subjects: &[&'a Subject],
+    ) -> Option<&'a Subject> {
+        self.projects()
+            .into_iter()
+            .find_map(|project| subject_for_project(project, &settings.projects, subjects))
+    }
+}
+fn take_date(text: &str) -> Option<(NaiveDate, &str)> {
+    let (word, tail) = text.split_once(' ').unwrap_or((text, ""));
+    let date = NaiveDate::parse_from_str(word, DATE_FORMAT).ok()?;
+    Some((date, tail.trim_start()))
+}
+fn split_tag(word: &str) -> Option<(&str, &str)> {
+    let (key, value) = word.split_once(':')?;
+    let valid = !key.is_empty()
+        && !value.is_empty()
+        && !value.starts_with("//")
+        && !key.starts_with(['+', '@']);
+    valid.then_some((key, value))
+}
+
+#[derive(Debug)]
+pub struct TodoTxt {
+    path: PathBuf,
+    /// the file as it was read, to notice edits made since
+    read: String,
+    lines: Vec<String>,
+}
+impl TodoTxt {
+    pub fn load(settings: &TodoTxtSettings) -> Result<TodoTxt> {
+        let path = settings
+            .path
+            .as_ref()
+            .filter(|path| !path.is_empty())
+            .ok_or(SettingsError::WrongSetting(
+                "todo.txt path is not configured".into(),
+            ))?;
+        let path = PathBuf::from(path);
+        let read = fs::read_to_string(&path)
+            .map_err(|err| SettingsError::LoadError(format!("{}: {err}", path.display())))?;
+        let lines = read.lines().map(|line| line.to_string()).collect();
+        Ok(TodoTxt { path, read, lines })
+    }
+    pub fn open_items(&self) -> Vec<TodoItem> {
+        let mut items: Vec<TodoItem> = self
+            .lines
+            .iter()
+            .filter_map(|line| TodoItem::parse(line))
+            .filter(|item| !item.done)
+            .collect();
+        // prioritized items first, like most todo.txt clients list them
+        items.sort_by_key(|item| item.priority.unwrap_or('['));
+        items
+    }
+    pub fn update(&mut self, key: &str, change: impl FnOnce(&mut TodoItem)) -> bool {
+        for line in self.lines.iter_mut() {
+            if let Some(mut item) = TodoItem::parse(line).filter(|item| item.key() == key) {
+                change(&mut item);
+                *line = item.to_line();
+                return true;
+            }
+        }
+        false
+    }
+    /// Writes the lines unless the file changed since it was read, false when it did.
+    pub fn save(&self) -> Result<bool> {
+        if fs::read_to_string(&self.path)? != self.read {
+            return Ok(false);
+        }
+        let mut contents = self.lines.join("\n");
+        contents.push('\n');
+        fs::write(&self.path, contents)?;
+        Ok(true)
+    }
+}
+/// Writes a linked task's pomodoro count back into todo.txt, and its done state when it
+/// was `toggled` here, so a line completed in another editor stays completed.
+pub fn write_back(settings: &TodoTxtSettings, task: &Task, toggled: bool) -> Result<()> {
+    let Some(key) = task.todo() else {
+        return Ok(());
+    };
+    // an edit made while the line was updated is read again instead of being overwritten
+    for _ in 0..TODO_TXT_WRITE_ATTEMPTS {
+        let mut todo = TodoTxt::load(settings)?;
+        let updated = todo.update(key, |item| {
+            if task.completed() > 0 {
+                item.set_tag(&settings.pomodoro_tag, &task.completed().to_string());
+            }
+            match (toggled, task.done()) {
+                (true, true) => item.complete(chrono::Local::now().date_naive()),
+                (true, false) => item.reopen(),
+                (false, _) => {}
+            }
+        });
+        if !updated || todo.save()? {
+            return Ok(());
+        }
+    }
+    Err(SettingsError::SaveError(format!(
+        "{} kept changing while \"{}\" was written back",
+        settings.path.as_deref().unwrap_or_default(),
+        task.title()
+    ))
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str = "(A) 2024-01-02 Write thesis chapter +thesis @library due:2024-02-01";
+
+    /// The line with a pomodoro count, done on the 5th
+    fn completed() -> TodoItem {
+        let mut item = TodoItem::parse(LINE).unwrap();
+        item.set_tag("pomo", "3");
+        item.complete(NaiveDate::from_ymd_opt(2024, 1, 5).unwrap());
+        item
+    }
+
+    #[test]
+    fn parses_priority_projects_contexts_and_tags() {
+        let item = TodoItem::parse(LINE).unwrap();
+        assert_eq!(item.priority, Some('A'));
+        assert_eq!(item.projects(), vec!["thesis"]);
+        assert_eq!(item.contexts(), vec!["library"]);
+        assert_eq!(item.tag("due"), Some("2024-02-01"));
+        assert_eq!(item.key(), "Write thesis chapter +thesis @library");
+    }
+    #[test]
+    fn completing_keeps_the_priority_as_a_tag() {
+        assert_eq!(
+            completed().to_line(),
+            "x 2024-01-05 2024-01-02 Write thesis chapter +thesis @library due:2024-02-01 pomo:3 pri:A"
+        );
+    }
+    #[test]
+    fn completed_lines_keep_their_key() {
+        let reparsed = TodoItem::parse(&completed().to_line()).unwrap();
+        assert_eq!(reparsed, completed());
+        assert_eq!(reparsed.key(), "Write thesis chapter +thesis @library");
+    }
+    #[test]
+    fn completion_from_another_editor_is_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("todo.txt");
+        let settings = TodoTxtSettings {
+            path: Some(path.display().to_string()),
+            ..Default::default()
+        };
+        let mut task = Task::from_todo(1, &TodoItem::parse(LINE).unwrap(), 1);
+        task.complete_pomodoro();
+        // done elsewhere while the pomodoro ran
+        let mut done = TodoItem::parse(LINE).unwrap();
+        done.complete(NaiveDate::from_ymd_opt(2024, 1, 5).unwrap());
+        fs::write(&path, format!("{}\n", done.to_line())).unwrap();
+
+        write_back(&settings, &task, false).unwrap();
+        let written = TodoItem::parse(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(written.done);
+        assert_eq!(written.tag("pomo"), Some("1"));
+    }
+    #[test]
+    fn changed_files_are_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("todo.txt");
+        fs::write(&path, format!("{LINE}\n")).unwrap();
+        let settings = TodoTxtSettings {
+            path: Some(path.display().to_string()),
+            ..Default::default()
+        };
+        let todo = TodoTxt::load(&settings).unwrap();
+        fs::write(&path, format!("{LINE}\nNew line\n")).unwrap();
+
+        assert!(!todo.save().unwrap());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{LINE}\nNew line\n")
+        );
+    }
+    #[test]
+    fn reopening_restores_the_priority() {
+        let mut item = completed();
+        item.reopen();
+        assert_eq!(
+            item.to_line(),
+            "(A) 2024-01-02 Write thesis chapter +thesis @library due:2024-02-01 pomo:3"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
-    pomodoro_tab::PomodoroTab, settings_tab::SettingsTab, stats_tab::StatsTab,
-    ui_utils::FooterHint, YELLOW,
+    local_stats_tab::LocalStatsTab, pomodoro_tab::PomodoroTab, settings_tab::SettingsTab,
+    stats_tab::StatsTab, tasks_tab::TasksTab, ui_utils::FooterHint, YELLOW,
 };
 use crate::{app::App, ui::ui_utils::HintProvider, ui::BG, utils::tabs};
 use ratatui::{
@@ -18,7 +18,7 @@ pub struct AppWidget<'a> {
 
 impl Widget for &mut AppWidget<'_> {
     fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
-        let tabs = ["Timer", "Settings", "Stats"];
+        let tabs = ["Timer", "Tasks", "Settings", "Stats"];
         let tab_titles: Vec<Span> = tabs
             .iter()
             .map(|t| Span::styled(*t, Style::default().fg(Color::White)))
@@ -47,7 +47,7 @@ impl Widget for &mut AppWidget<'_> {
 
         let tab_layout = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Max(29), Constraint::Min(1)])
+            .constraints([Constraint::Max(38), Constraint::Min(1)])
             .split(layout[0]);
 
         if let Some(popup) = self.app_context.popup_as_mut() {
@@ -65,6 +65,19 @@ impl Widget for &mut AppWidget<'_> {
                 pomodoro_tab.render(layout[1], buf);
                 self.render_footer(layout[2], buf, hints);
             }
+            tabs::Tabs::TasksTab => {
+                if window_too_small(40, 10, area, buf) {
+                    return;
+                }
+                let typing = self.app_context.pomodoro().tasks().is_typing();
+                let mut tasks_tab = TasksTab::new(self.app_context.pomodoro_mut().tasks_mut());
+                let mut hints = tasks_tab.provide_hints();
+                tasks_tab.render(layout[1], buf);
+                if !typing {
+                    hints.append(&mut self.provide_hints());
+                }
+                self.render_footer(layout[2], buf, hints);
+            }
             tabs::Tabs::SettingsTab => {
                 if window_too_small(65, 30, area, buf) {
                     return;
@@ -86,20 +99,29 @@ impl Widget for &mut AppWidget<'_> {
                 if window_too_small(65, 30, area, buf) {
                     return;
                 }
-                let (rendered_stats, hints) = if let Some(stats_client) =
+                if let Some(heatmap) = self.app_context.heatmap() {
+                    let hints = heatmap.provide_hints();
+                    heatmap.render(layout[1], buf);
+                    self.render_footer(layout[2], buf, hints);
+                } else if let Some(archive) = self.app_context.archive_mut() {
+                    let hints = archive.provide_hints();
+                    archive.render(layout[1], buf);
+                    self.render_footer(layout[2], buf, hints);
+                } else if self.app_context.local_stats_shown() {
+                    let pixela_on = self.app_context.pomodoro().pixela_client().is_some();
+                    let stats = self.app_context.pomodoro_mut().local_stats();
+                    let local_stats = LocalStatsTab::new(stats, pixela_on);
+                    let hints = local_stats.provide_hints();
+                    local_stats.render(layout[1], buf);
+                    self.render_footer(layout[2], buf, hints);
+                } else if let Some(stats_client) =
                     self.app_context.pomodoro_mut().pixela_client_as_mut()
                 {
                     let mut stats = StatsTab::new(stats_client);
                     let hints = stats.provide_hints();
                     stats.render(layout[1], buf);
-                    (true, hints)
-                } else {
-                    (false, self.provide_hints())
-                };
-                if !rendered_stats {
-                    self.render_stats(layout[1], buf);
+                    self.render_footer(layout[2], buf, hints);
                 }
-                self.render_footer(layout[2], buf, hints);
             }
         }
         tabs_widget.render(tab_layout[0], buf);
@@ -152,13 +174,6 @@ impl<'a> AppWidget<'a> {
 
         footer.render(area, buf);
     }
-    fn render_stats(&self, area: Rect, buf: &mut Buffer) {
-        let text = Paragraph::new("Stats are turned off".to_string())
-            .alignment(Alignment::Center)
-            .style(Style::default().fg(Color::LightGreen));
-        text.render(area, buf);
-    }
-
     pub fn draw(&mut self, frame: &mut Frame) {
         frame.render_widget(self, frame.area());
     }
@@ -0,0 +1,160 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{palette::tailwind::SLATE, Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Block, BorderType, Borders, HighlightSpacing, List, ListItem, Paragraph, StatefulWidget,
+        Widget, Wrap,
+    },
+};
+
+use crate::stats::{
+    pixel::Pixel,
+    pixela::upload_archive::{ArchiveView, ArchivedUpload},
+};
+
+use super::{
+    helpers::{self, format_duration},
+    ui_utils::{FooterHint, HintProvider},
+    BLUE, GREEN, RED,
+};
+
+const SELECTED_STYLE: Style = Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD);
+
+impl Widget for &mut ArchiveView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(7)])
+            .split(area);
+        let subject = self.subject_name().unwrap_or("all subjects").to_string();
+        let failed_only = match self.filter().failed_only {
+            true => " · failed only",
+            false => "",
+        };
+        let block = Block::default()
+            .title(format!(" Upload archive · {subject}{failed_only} "))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(BLUE));
+        let inner_area = block.inner(layout[0]);
+
+        let items: Vec<ListItem> = self.shown().into_iter().map(upload_row).collect();
+        let total = items.len();
+        if items.is_empty() {
+            block.render(layout[0], buf);
+            Paragraph::new("Nothing uploaded yet")
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::Gray))
+                .bold()
+                .render(inner_area, buf);
+        } else {
+            let list = List::new(items)
+                .block(block)
+                .highlight_style(SELECTED_STYLE)
+                .highlight_symbol(">")
+                .highlight_spacing(HighlightSpacing::Always);
+            StatefulWidget::render(list, layout[0], buf, self.state_mut());
+            helpers::render_scroll_indicators(inner_area, buf, total, self.state(), BLUE);
+        }
+        render_details(self.selected(), layout[1], buf);
+    }
+}
+
+fn upload_row(upload: &ArchivedUpload) -> ListItem<'static> {
+    let date = upload.date().map_or("?".to_string(), |date| {
+        date.format("%a %d %b %Y").to_string()
+    });
+    let uploaded_at = upload
+        .uploaded_at()
+        .map_or("?".to_string(), |at| at.format("%d %b %H:%M").to_string());
+    let (mark, color) = match upload.success() {
+        true => ("✓", GREEN),
+        false => ("✗", RED),
+    };
+    ListItem::new(Line::from(vec![
+        Span::styled(format!("{mark} "), Style::default().fg(color)),
+        Span::raw(format!("{date:<16}")),
+        Span::styled(
+            format!("{:<14}", upload.subject_name()),
+            Style::default().fg(BLUE),
+        ),
+        Span::raw(format!(
+            "+{:<8} → {:<8}",
+            format_duration(upload.sent() as i64 * 60),
+            format_duration(upload.value() as i64 * 60)
+        )),
+        Span::styled(
+            format!(" sent {uploaded_at}"),
+            Style::default().fg(Color::Gray),
+        ),
+    ]))
+}
+
+fn render_details(upload: Option<&ArchivedUpload>, area: Rect, buf: &mut Buffer) {
+    let block = Block::default()
+        .title(" Details ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Gray));
+    let Some(upload) = upload else {
+        block.render(area, buf);
+        return;
+    };
+    let pixels: Vec<String> = upload
+        .pixels()
+        .iter()
+        .filter_map(|pixel| match pixel {
+            Pixel::Complex(pixel) => Some(format!(
+                "{} {}",
+                pixel.date(),
+                format_duration(pixel.progress().get_as_int() as i64 * 60)
+            )),
+            Pixel::Simple(_) => None,
+        })
+        .collect();
+    let pixels = match pixels.is_empty() {
+        true => "none, an earlier day was written again".to_string(),
+        false => pixels.join(", "),
+    };
+    let uploaded_at = upload.uploaded_at().map_or("?".to_string(), |at| {
+        at.format("%Y-%m-%d %H:%M:%S").to_string()
+    });
+    let response_color = match upload.success() {
+        true => GREEN,
+        false => RED,
+    };
+    let lines = vec![
+        Line::from(format!(
+            "{} on {}, uploaded {uploaded_at}",
+            upload.subject_name(),
+            upload
+                .date()
+                .map_or("?".to_string(), |date| date.to_string()),
+        ))
+        .bold(),
+        Line::from(format!(
+            "Added {}, the day was set to {}",
+            format_duration(upload.sent() as i64 * 60),
+            format_duration(upload.value() as i64 * 60)
+        )),
+        Line::from(format!("Response: {}", upload.response())).fg(response_color),
+        Line::from(format!("Pixels: {pixels}")).fg(Color::Gray),
+    ];
+    Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .block(block)
+        .render(area, buf);
+}
+
+impl HintProvider for ArchiveView {
+    fn provide_hints(&self) -> Vec<FooterHint> {
+        vec![
+            FooterHint::new("↑↓", "Scroll"),
+            FooterHint::new("f", "Subject"),
+            FooterHint::new("x", "Failed only"),
+            FooterHint::new("Esc", "Close archive"),
+        ]
+    }
+}
@@ -1,49 +1,129 @@
+use chrono::{Duration, NaiveDate};
 use ratatui::{
-    style::{Style, Stylize},
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style, Stylize},
     text::Line,
     widgets::{Bar, BarChart, BarGroup, Block, Widget},
 };
 
-use crate::stats::pixela::graph::Graph;
+use crate::stats::pixela::{
+    graph::{Aggregation, Graph},
+    subjects::Seconds,
+};
 
 impl Widget for &Graph {
     fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
     where
         Self: Sized,
     {
-        const BAR_WIDTH: u16 = 3;
-        const BAR_GAP: u16 = 2;
-        const PADDING: u16 = 4;
-        const MIN_BARS_TO_DISPLAY: u16 = 7;
-        const UPPER_PADDING: u64 = 2;
-
-        let available_width = area.width.saturating_sub(PADDING);
-        let max_bars = (available_width / (BAR_WIDTH + BAR_GAP)).max(MIN_BARS_TO_DISPLAY) as usize;
-
-        let all_bars = self.into_bars();
-        let bars_that_fit: Vec<_> = all_bars.into_iter().rev().take(max_bars).rev().collect();
-
-        // invisible bars for padding
-        let mut datapoints: Vec<Bar> = vec![
-            Bar::default().value(0).label("").style(
-                Style::new().fg(ratatui::style::Color::Reset)
-            );
-            2
-        ];
-        datapoints.extend(bars_that_fit);
-        let bar_chart = BarChart::default()
-            .block(
-                Block::default().title_top(
-                    Line::from(self.subject().graph_name().to_string())
-                        .bold()
-                        .centered()
-                        .fg(self.subject().color().to_ratatui_color()),
-                ),
-            )
-            .data(BarGroup::default().bars(&datapoints))
-            .bar_width(BAR_WIDTH)
-            .bar_gap(BAR_GAP)
-            .max(self.get_max_quantity() + UPPER_PADDING);
-        bar_chart.render(area, buf);
+        let Some((start, end)) = self.span() else {
+            return;
+        };
+        // a year of days doesn't fit, so wide ranges get a bar per week or month
+        let aggregation = Aggregation::fitting(start, end, max_bars(area.width));
+        let unit = match self.subject().is_count() {
+            true => "count",
+            false => "hours",
+        };
+        let title = Line::from(format!(
+            "{} · {} – {} · {unit} per {aggregation} · {}",
+            self.subject().graph_name(),
+            start.format("%d %b %Y"),
+            end.format("%d %b %Y"),
+            age_tag(self),
+        ))
+        .bold()
+        .centered()
+        .fg(self.subject().color().to_ratatui_color());
+        render_bars(
+            self.into_bars(aggregation),
+            title,
+            self.get_max_quantity(aggregation),
+            area,
+            buf,
+        );
+    }
+}
+
+/// How old the graph is, so a cached one isn't mistaken for live data.
+fn age_tag(graph: &Graph) -> String {
+    let age = match graph.age() {
+        Some(age) if age < Duration::minutes(1) => "updated just now".to_string(),
+        Some(age) if age < Duration::hours(1) => format!("cached {}m ago", age.num_minutes()),
+        Some(age) if age < Duration::days(1) => format!("cached {}h ago", age.num_hours()),
+        Some(age) => format!("cached {}d ago", age.num_days()),
+        None => "cached".to_string(),
+    };
+    match graph.refresh_failed() {
+        true => format!("{age}, offline"),
+        false => age,
+    }
+}
+
+/// Minutes worked per day, for the local stats.
+pub struct DailyTotals<'a> {
+    pub title: &'a str,
+    pub days: &'a [(NaiveDate, Seconds)],
+    pub color: Color,
+}
+impl Widget for &DailyTotals<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let bars: Vec<Bar> = self
+            .days
+            .iter()
+            .map(|(day, seconds)| {
+                Bar::default()
+                    .value((seconds / 60) as u64)
+                    .label(day.format("%d").to_string())
+                    .style(Style::default().fg(self.color))
+            })
+            .collect();
+        let max = self
+            .days
+            .iter()
+            .map(|(_, seconds)| (seconds / 60) as u64)
+            .max()
+            .unwrap_or_default();
+        let title = Line::from(self.title).bold().centered().fg(self.color);
+        render_bars(bars, title, max, area, buf);
     }
 }
+
+const BAR_WIDTH: u16 = 3;
+const BAR_GAP: u16 = 2;
+const PADDING: u16 = 4;
+const MIN_BARS_TO_DISPLAY: u16 = 7;
+const UPPER_PADDING: u64 = 2;
+
+fn max_bars(width: u16) -> usize {
+    let available_width = width.saturating_sub(PADDING);
+    (available_width / (BAR_WIDTH + BAR_GAP)).max(MIN_BARS_TO_DISPLAY) as usize
+}
+
+/// Draws as many of the latest bars as fit into `area`.
+fn render_bars(bars: Vec<Bar>, title: Line, max: u64, area: Rect, buf: &mut Buffer) {
+    let bars_that_fit: Vec<_> = bars
+        .into_iter()
+        .rev()
+        .take(max_bars(area.width))
+        .rev()
+        .collect();
+
+    // invisible bars for padding
+    let mut datapoints: Vec<Bar> = vec![
+        Bar::default()
+            .value(0)
+            .label("")
+            .style(Style::new().fg(Color::Reset));
+        2
+    ];
+    datapoints.extend(bars_that_fit);
+    let bar_chart = BarChart::default()
+        .block(Block::default().title_top(title))
+        .data(BarGroup::default().bars(&datapoints))
+        .bar_width(BAR_WIDTH)
+        .bar_gap(BAR_GAP)
+        .max(max + UPPER_PADDING);
+    bar_chart.render(area, buf);
+}
@@ -0,0 +1,129 @@
+use chrono::{Datelike, Duration};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph, Widget},
+};
+
+use crate::stats::heatmap::{Heatmap, HEATMAP_WEEKS};
+
+use super::{
+    helpers::format_quantity,
+    ui_utils::{FooterHint, HintProvider},
+};
+
+const SHADES: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+const WEEKDAY_LABELS: [&str; 7] = ["Mon", "", "Wed", "", "Fri", "", "Sun"];
+const LABEL_WIDTH: u16 = 4;
+
+impl Widget for &Heatmap {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let color = self.color().to_ratatui_color();
+        let block = Block::default()
+            .title(format!(" {} ", self.title()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(color));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1), // Months
+                Constraint::Length(7), // Weekdays
+                Constraint::Length(1), // Spacer
+                Constraint::Length(2), // Selected day and totals
+            ])
+            .flex(ratatui::layout::Flex::Center)
+            .split(inner);
+
+        // two columns per week when the whole year fits, otherwise only the latest weeks
+        let grid_width = inner.width.saturating_sub(LABEL_WIDTH);
+        let cell_width: u16 = if grid_width >= HEATMAP_WEEKS as u16 * 2 {
+            2
+        } else {
+            1
+        };
+        let shown_weeks = (grid_width / cell_width).min(HEATMAP_WEEKS as u16) as usize;
+        let first_week = HEATMAP_WEEKS as usize - shown_weeks;
+
+        let mut months = vec![Span::raw(" ".repeat(LABEL_WIDTH as usize))];
+        let mut last_month = None;
+        let mut pending = 0;
+        for week in first_week..HEATMAP_WEEKS as usize {
+            let monday = self.start() + Duration::weeks(week as i64);
+            // a new month gets its label over the first week that starts in it
+            if last_month != Some(monday.month()) && pending == 0 {
+                let label = monday.format("%b").to_string();
+                pending = label.len();
+                months.push(Span::raw(label));
+                last_month = Some(monday.month());
+            }
+            let width = (cell_width as usize).saturating_sub(pending);
+            pending = pending.saturating_sub(cell_width as usize);
+            months.push(Span::raw(" ".repeat(width)));
+        }
+        Line::from(months).fg(Color::Gray).render(layout[0], buf);
+
+        let rows: Vec<Line> = (0..7)
+            .map(|weekday| {
+                let mut cells = vec![Span::styled(
+                    format!(
+                        "{:<width$}",
+                        WEEKDAY_LABELS[weekday],
+                        width = LABEL_WIDTH as usize
+                    ),
+                    Style::default().fg(Color::Gray),
+                )];
+                for week in first_week..HEATMAP_WEEKS as usize {
+                    let Some(day) = self.day(week, weekday) else {
+                        break;
+                    };
+                    let level = self.level(day) as usize;
+                    let mut style = match level {
+                        0 => Style::default().fg(Color::DarkGray),
+                        _ => Style::default().fg(color),
+                    };
+                    if day == self.cursor() {
+                        style = style.add_modifier(Modifier::REVERSED);
+                    }
+                    let cell = SHADES[level].repeat(cell_width as usize);
+                    cells.push(Span::styled(cell, style));
+                }
+                Line::from(cells)
+            })
+            .collect();
+        Paragraph::new(rows).render(layout[1], buf);
+
+        let cursor = self.cursor();
+        let summary = vec![
+            Line::from(format!(
+                "{}: {}",
+                cursor.format("%a %d %b %Y"),
+                format_quantity(self.minutes_on(cursor) as f64, self.unit())
+            ))
+            .bold()
+            .fg(color),
+            Line::from(format!(
+                "{} in the last year, {} active days",
+                format_quantity(self.total() as f64, self.unit()),
+                self.active_days()
+            ))
+            .fg(Color::Gray),
+        ];
+        Paragraph::new(summary)
+            .alignment(Alignment::Center)
+            .render(layout[3], buf);
+    }
+}
+impl HintProvider for Heatmap {
+    fn provide_hints(&self) -> Vec<FooterHint> {
+        vec![
+            FooterHint::new("←→", "Week"),
+            FooterHint::new("↑↓", "Day"),
+            FooterHint::new("Esc", "Close heatmap"),
+        ]
+    }
+}
@@ -5,7 +5,13 @@ use ratatui::{
     widgets::{ListState, Paragraph, Widget},
 };
 
-use crate::stats::pixel::Pixel;
+use crate::{
+    stats::{
+        pixel::Pixel,
+        pixela::subjects::{Seconds, SubjectUnit},
+    },
+    utils::misc,
+};
 
 pub fn render_scroll_indicators(
     list_area: Rect,
@@ -48,3 +54,18 @@ pub fn render_scroll_indicators(
         down_arrow.render(arrow_area, buf);
     }
 }
+/// `1h 05m`, or just `45m` below an hour.
+pub fn format_duration(seconds: Seconds) -> String {
+    let minutes = seconds / 60;
+    match minutes >= 60 {
+        true => format!("{}h {:02}m", minutes / 60, minutes % 60),
+        false => format!("{minutes}m"),
+    }
+}
+/// A Pixela quantity in the graph's unit, shown like any other duration, counts as `3×`.
+pub fn format_quantity(quantity: f64, unit: &SubjectUnit) -> String {
+    match unit.in_minutes() {
+        Some(minutes) => format_duration((quantity * minutes * 60.0).round() as Seconds),
+        None => format!("{}×", misc::round_to_precision(quantity, 2)),
+    }
+}
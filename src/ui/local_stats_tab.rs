@@ -0,0 +1,161 @@
+use chrono::NaiveDate;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph, Widget},
+};
+
+use crate::stats::{
+    analytics::{LocalStats, WINDOW_DAYS},
+    pixela::subjects::Seconds,
+};
+
+use super::{
+    graph::DailyTotals,
+    helpers::format_duration,
+    ui_utils::{FooterHint, HintProvider},
+    BLUE, GREEN, ORANGE, YELLOW,
+};
+
+pub struct LocalStatsTab<'a> {
+    stats: &'a LocalStats,
+    pixela_on: bool,
+}
+impl Widget for &LocalStatsTab<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),      // Totals
+                Constraint::Fill(1),        // Subjects and records
+                Constraint::Percentage(40), // Daily chart
+            ])
+            .split(area);
+        let totals = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 4); 4])
+            .split(layout[0]);
+        let middle = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(layout[1]);
+
+        let stats = self.stats;
+        for (index, (title, seconds)) in [
+            (" Today ", stats.today),
+            (" This week ", stats.week),
+            (" This month ", stats.month),
+            (" All time ", stats.all_time),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            render_total(title, seconds, totals[index], buf);
+        }
+        self.render_subjects(middle[0], buf);
+        self.render_records(middle[1], buf);
+        let title = format!("Minutes per day, last {WINDOW_DAYS} days");
+        let chart = DailyTotals {
+            title: &title,
+            days: &stats.days,
+            color: GREEN,
+        };
+        chart.render(layout[2], buf);
+    }
+}
+impl<'a> LocalStatsTab<'a> {
+    pub fn new(stats: &'a LocalStats, pixela_on: bool) -> Self {
+        Self { stats, pixela_on }
+    }
+    fn render_subjects(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .title(format!(" Subjects, last {WINDOW_DAYS} days "))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(BLUE));
+        let total: Seconds = self.stats.subjects.iter().map(|(_, seconds)| seconds).sum();
+        let lines: Vec<Line> = match self.stats.subjects.is_empty() {
+            true => vec![Line::from("No finished work sessions yet").fg(Color::Gray)],
+            false => self
+                .stats
+                .subjects
+                .iter()
+                .map(|(subject, seconds)| {
+                    let share = *seconds as f64 / total.max(1) as f64;
+                    // leaves room for the name and the numbers
+                    let width = area.width.saturating_sub(40) as f64;
+                    let bar = "█".repeat((share * width).round() as usize);
+                    Line::from(format!(
+                        " {subject:<16.16} {:>7} {:>4.0}% {bar}",
+                        format_duration(*seconds),
+                        share * 100.0
+                    ))
+                    .fg(Color::White)
+                })
+                .collect(),
+        };
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+    fn render_records(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .title(" Averages & records ")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(ORANGE));
+        let stats = self.stats;
+        let lines = vec![
+            Line::from(format!(" Sessions: {}", stats.sessions)),
+            Line::from(format!(
+                " Per active day: {}",
+                format_duration(stats.daily_average)
+            )),
+            Line::from(format!(
+                " Per session: {}",
+                format_duration(stats.session_average)
+            )),
+            Line::from(format!(" Best day: {}", format_day(stats.best_day))).fg(GREEN),
+            Line::from(format!(" Worst day: {}", format_day(stats.worst_day))).fg(ORANGE),
+        ];
+        Paragraph::new(lines)
+            .style(Style::default().fg(Color::White))
+            .block(block)
+            .render(area, buf);
+    }
+}
+fn render_total(title: &str, seconds: Seconds, area: Rect, buf: &mut Buffer) {
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(YELLOW));
+    Paragraph::new(format_duration(seconds))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::White))
+        .bold()
+        .block(block)
+        .render(area, buf);
+}
+fn format_day(day: Option<(NaiveDate, Seconds)>) -> String {
+    match day {
+        Some((day, seconds)) => {
+            format!("{} ({})", day.format("%a %d %b"), format_duration(seconds))
+        }
+        None => "-".to_string(),
+    }
+}
+impl HintProvider for LocalStatsTab<'_> {
+    fn provide_hints(&self) -> Vec<FooterHint> {
+        let mut hints = vec![
+            FooterHint::new("<>", "Change Tabs"),
+            FooterHint::new("E", "Export history"),
+            FooterHint::new("H", "Heatmap"),
+            FooterHint::new("Y", "Sync devices"),
+        ];
+        if self.pixela_on {
+            hints.push(FooterHint::new("v", "Pixela view"));
+        }
+        hints
+    }
+}
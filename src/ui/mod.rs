@@ -1,11 +1,15 @@
 pub mod app_ui;
+pub mod archive;
 mod assets;
 pub mod graph;
+pub mod heatmap;
 pub mod helpers;
+pub mod local_stats_tab;
 pub mod pomodoro_tab;
 pub mod popup;
 pub mod settings_tab;
 pub mod stats_tab;
+pub mod tasks_tab;
 pub mod ui_utils;
 
 use ratatui::style::Color;
@@ -19,6 +19,7 @@ use ratatui::widgets::{Block, Padding};
 
 use crate::romodoro::Pomodoro;
 
+use super::helpers::format_quantity;
 use super::ui_utils::{FooterHint, HintProvider, UIHelper};
 
 pub struct PomodoroTab<'a> {
@@ -112,6 +113,7 @@ impl Widget for PomodoroTab<'_> {
                 Constraint::Length(3),  // Spacer
                 Constraint::Length(0),  // ASCII Animation
                 Constraint::Length(1),  // Subject select
+                Constraint::Length(1),  // Active task
                 Constraint::Length(1),  // ASCII Spacer
                 Constraint::Max(7),     // Pomodoro/Flowmodoro layouts
             ])
@@ -127,6 +129,7 @@ impl Widget for PomodoroTab<'_> {
                 Constraint::Length(2),  // ASCII Spacer
                 Constraint::Length(1),  // Timer
                 Constraint::Length(1),  // Subject select
+                Constraint::Length(1),  // Active task
                 Constraint::Length(3),  // ASCII Spacer
                 Constraint::Max(7),     // Pomodoro/Flowmodoro layouts
             ])
@@ -151,11 +154,12 @@ impl Widget for PomodoroTab<'_> {
         now_paragraph.render(layout[1], buf);
         timer_text.render(layout[3], buf);
         spacer.render(spacer_layout[0], buf);
-        let layout_spot = 8;
+        let layout_spot = 9;
         if hide_countdown {
             animation_text.render(layout[5], buf);
         };
         self.render_subject_select(layout[6], buf);
+        self.render_active_task(layout[7], buf);
         match self.pomodoro.timer.counter_mode() {
             CounterMode::Countdown => self.render_pomodoro_ui(layout[layout_spot], buf),
             CounterMode::Countup => self.render_flowmodoro_ui(layout[layout_spot], buf),
@@ -185,10 +189,29 @@ impl<'a> PomodoroTab<'a> {
             Some(_) => Style::default().fg(YELLOW),
             None => Style::default().fg(Color::Gray),
         };
-        let subject_paragraph =
-            UIHelper::create_settings_paragraph(&format!("Tracking: {subject_name}"), Some(style));
+        let today = match (&subject, self.pomodoro.pixela_client()) {
+            (Some(subject), Some(client)) => client.today_on_pixela().map(|progress| {
+                let today = format_quantity(progress.get_as_float(), subject.unit());
+                format!(" · today on Pixela: {today}")
+            }),
+            _ => None,
+        };
+        let subject_paragraph = UIHelper::create_settings_paragraph(
+            &format!("Tracking: {subject_name}{}", today.unwrap_or_default()),
+            Some(style),
+        );
         subject_paragraph.render(area, buf);
     }
+    fn render_active_task(&self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let Some(task) = self.pomodoro.tasks().active_task() else {
+            return;
+        };
+        let task_paragraph = UIHelper::create_settings_paragraph(
+            &format!("Task: {} ({})", task.title(), task.progress_string()),
+            Some(Style::default().fg(GREEN)),
+        );
+        task_paragraph.render(area, buf);
+    }
     fn render_flowmodoro_ui(
         &self,
         area: ratatui::prelude::Rect,
@@ -419,7 +442,11 @@ fn format_ascii_time(input: &str) -> String {
 }
 impl HintProvider for PomodoroTab<'_> {
     fn provide_hints(&self) -> Vec<FooterHint> {
-        let mut def = vec![FooterHint::new("r", "Reset timer")];
+        let mut def = vec![
+            FooterHint::new("r", "Reset timer"),
+            FooterHint::new("t", "Pick todo"),
+            FooterHint::new("T", "Pick Taskwarrior"),
+        ];
 
         let mut state_dependent = match self.pomodoro.timer.counter_mode() {
             CounterMode::Countup if self.pomodoro.timer.in_work_state() => vec![
@@ -1,6 +1,7 @@
 use ratatui::{
     layout::{Alignment, Constraint, Flex, Layout, Margin, Rect},
     style::{Color, Modifier, Style, Stylize},
+    text::Line,
     widgets::{
         Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Widget, Wrap,
     },
@@ -8,11 +9,17 @@ use ratatui::{
 
 use crate::{
     popup::{Popup, PopupKind},
-    stats::{pixel::Pixel, pixela::complex_pixel::IsRounded},
-    ui::helpers::render_scroll_indicators,
+    stats::{
+        pixel::Pixel,
+        pixela::{
+            complex_pixel::IsRounded,
+            send_preview::{PreviewRow, SendPreview},
+        },
+    },
+    ui::helpers::{format_quantity, render_scroll_indicators},
 };
 
-use super::{stats_tab::PixelToListWrapper, BG, GREEN, RED, YELLOW};
+use super::{stats_tab::PixelToListWrapper, BG, GREEN, ORANGE, RED, YELLOW};
 
 impl Widget for &mut Popup {
     fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
@@ -30,18 +37,27 @@ impl Widget for &mut Popup {
             PopupKind::ErrorPopup(error) => {
                 Popup::render_error_popup(&self.message, popup_area, buf, error);
             }
-            PopupKind::SendPixelsPopup(_, pixels, ref mut list_state) => {
+            PopupKind::SendPixelsPopup(_, preview, ref mut list_state) => {
                 Popup::render_confirm_list_popup(
                     &self.message,
                     popup_area,
                     buf,
-                    pixels,
+                    preview,
                     list_state,
                 );
             }
             PopupKind::ListPopup(pixels, ref mut list_state) => {
                 Popup::render_list_popup(&self.message, popup_area, buf, pixels, list_state);
             }
+            PopupKind::MessageListPopup(lines, ref mut list_state) => {
+                Popup::render_message_list_popup(&self.message, popup_area, buf, lines, list_state);
+            }
+            PopupKind::PickerPopup(_, items, ref mut list_state) => {
+                Popup::render_picker_popup(&self.message, popup_area, buf, items, list_state);
+            }
+            PopupKind::InputPopup(_, input) => {
+                Popup::render_input_popup(&self.message, popup_area, buf, input);
+            }
         }
     }
 }
@@ -152,7 +168,7 @@ impl Popup {
         message: &str,
         area: Rect,
         buf: &mut ratatui::prelude::Buffer,
-        pixels: &[Pixel],
+        preview: &SendPreview,
         list_state: &mut ListState,
     ) {
         let popup_block = Block::default()
@@ -184,22 +200,20 @@ impl Popup {
         question_paragraph.render(layout[0], buf);
 
         // List area
-        let pixel_items: Vec<ListItem> = pixels
+        let pixel_items: Vec<ListItem> = preview
+            .rows()
             .iter()
-            .map(|pix| {
-                let rounded = pix.check_if_value_rounded();
-                PixelToListWrapper {
-                    pixel: pix,
-                    selected: true,
-                    rounded,
-                    available_size: area.width,
-                }
-                .into()
-            })
+            .map(|row| preview_row(row, area.width))
             .collect();
 
         let list = List::new(pixel_items)
             .style(Style::default().fg(Color::White))
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">")
             .block(
                 Block::default()
                     .borders(Borders::ALL)
@@ -211,7 +225,7 @@ impl Popup {
 
         use ratatui::widgets::StatefulWidget;
         StatefulWidget::render(list, layout[1], buf, list_state);
-        render_scroll_indicators(layout[1], buf, pixels.len(), list_state, GREEN);
+        render_scroll_indicators(layout[1], buf, preview.rows().len(), list_state, GREEN);
 
         // Buttons
         let button_layout = Layout::default()
@@ -322,6 +336,222 @@ impl Popup {
         ok_paragraph.render(layout[2], buf);
     }
 }
+impl Popup {
+    fn render_message_list_popup(
+        message: &str,
+        area: Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        lines: &[String],
+        list_state: &mut ListState,
+    ) {
+        let popup_block = Block::default()
+            .borders(Borders::NONE)
+            .style(Style::default().bg(BG));
+        popup_block.render(area, buf);
+
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(20), // Message area
+                Constraint::Percentage(60), // List area
+                Constraint::Percentage(30), // Buttons area
+            ])
+            .split(area);
+
+        let message_paragraph = Paragraph::new(message)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::White))
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(YELLOW))
+                    .title(" Info ")
+                    .title_style(Style::default().fg(YELLOW)),
+            );
+        message_paragraph.render(layout[0], buf);
+
+        let list = List::new(
+            lines
+                .iter()
+                .map(|line| ListItem::new(line.as_str()))
+                .collect::<Vec<ListItem>>(),
+        )
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(YELLOW)),
+        );
+
+        use ratatui::widgets::StatefulWidget;
+        StatefulWidget::render(list, layout[1], buf, list_state);
+        render_scroll_indicators(layout[1], buf, lines.len(), list_state, GREEN);
+
+        let ok_paragraph = Paragraph::new("Press any key to continue")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(YELLOW))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(YELLOW)),
+            );
+        ok_paragraph.render(layout[2], buf);
+    }
+    fn render_picker_popup(
+        message: &str,
+        area: Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        items: &[String],
+        list_state: &mut ListState,
+    ) {
+        let popup_block = Block::default()
+            .borders(Borders::NONE)
+            .style(Style::default().bg(BG));
+        popup_block.render(area, buf);
+
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(20), // Message area
+                Constraint::Percentage(80), // List area
+            ])
+            .split(area);
+
+        let message_paragraph = Paragraph::new(message)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::White))
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(YELLOW))
+                    .title(" Pick one ")
+                    .title_style(Style::default().fg(YELLOW)),
+            );
+        message_paragraph.render(layout[0], buf);
+
+        let list = List::new(
+            items
+                .iter()
+                .map(|item| ListItem::new(item.as_str()))
+                .collect::<Vec<ListItem>>(),
+        )
+        .style(Style::default().fg(Color::White))
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">")
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(GREEN))
+                .title(" Enter: pick | Esc: close ")
+                .title_style(Style::default().fg(GREEN)),
+        );
+
+        use ratatui::widgets::StatefulWidget;
+        StatefulWidget::render(list, layout[1], buf, list_state);
+        render_scroll_indicators(layout[1], buf, items.len(), list_state, GREEN);
+    }
+    fn render_input_popup(
+        message: &str,
+        area: Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        input: &str,
+    ) {
+        let popup_block = Block::default()
+            .borders(Borders::NONE)
+            .style(Style::default().bg(BG));
+        popup_block.render(area, buf);
+
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(60), // Message area
+                Constraint::Length(3),      // Input area
+            ])
+            .split(area);
+
+        let message_paragraph = Paragraph::new(message)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::White))
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(YELLOW))
+                    .title(" Input ")
+                    .title_style(Style::default().fg(YELLOW)),
+            );
+        message_paragraph.render(layout[0], buf);
+
+        let input_paragraph = Paragraph::new(format!("{input}_"))
+            .style(Style::default().fg(Color::White))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(GREEN))
+                    .title(" Enter: confirm | Esc: cancel ")
+                    .title_style(Style::default().fg(GREEN)),
+            );
+        input_paragraph.render(layout[1], buf);
+    }
+}
+
+/// `graph | date | current → new | +added`, with what's rounded, carried or too small.
+fn preview_row(row: &PreviewRow, available_size: u16) -> ListItem<'static> {
+    let pixel = row.pixel();
+    let unit = pixel.subject().unit();
+    let current = row
+        .current()
+        .map_or("?".to_string(), |current| format_quantity(current, unit));
+    let values = format!(
+        "{} → {} | +{}",
+        current,
+        format_quantity(row.new_value(), unit),
+        pixel.display_string()
+    );
+    let mut text = format!(
+        " {} | {} | {values}",
+        pixel.subject().graph_name(),
+        pixel.date()
+    );
+    if text.len() + 5 >= available_size.into() {
+        text = format!(
+            " {} | {} | {values}",
+            pixel.subject().shortened_graph_name(),
+            pixel.date()
+        );
+    }
+    let mut flagged = false;
+    if let IsRounded::Yes(rounded_to) = row.rounded() {
+        text.push_str(&format!(" | rounded: {rounded_to}{}!", unit.short_string()));
+        flagged = true;
+    }
+    if row.too_small() {
+        text.push_str(" | below the minimum!");
+        flagged = true;
+    }
+    if let Some(carry) = row.carry() {
+        text.push_str(&format!(" | {carry}"));
+    }
+    match (row.selected(), flagged) {
+        (true, false) => Line::styled(format!(" ✓ {text}"), Style::default().fg(GREEN)),
+        (true, true) => Line::styled(format!(" ✓ {text}"), Style::default().fg(ORANGE)),
+        (false, _) => Line::styled(format!(" ☐ {text}"), Style::default().fg(Color::Gray)),
+    }
+    .into()
+}
 
 /// helper function to create a centered rect using up certain percentage of the available rect
 pub fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
@@ -1,5 +1,6 @@
 use crate::stats::pixel::Pixel;
 use crate::stats::pixela::complex_pixel::IsRounded;
+use crate::stats::pixela::graph_stats::GraphStats;
 use crate::stats::pixela::pixela_client::{PixelaClient, PixelaTabs};
 use crate::stats::pixela::subjects::{Subject, SubjectUnit};
 use crate::ui::ui_utils::FooterHint;
@@ -251,11 +252,24 @@ impl<'a> StatsTab<'a> {
         block.render(area, buf);
 
         if let Some(g) = self.pixela_client.current_graph() {
-            g.render(inner_area, buf);
+            match self.pixela_client.graph_stats() {
+                Some(stats) => {
+                    let layout = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Fill(1), Constraint::Length(24)])
+                        .split(inner_area);
+                    g.render(layout[0], buf);
+                    render_graph_stats(stats, g.subject(), layout[1], buf);
+                }
+                None => g.render(inner_area, buf),
+            }
         } else {
-            let placeholder_text = Paragraph::new("Press G to render a graph.")
-                .alignment(Alignment::Center)
-                .style(Style::default().fg(Color::Gray));
+            let placeholder_text = Paragraph::new(format!(
+                "Press G to render a graph of the last {}.",
+                self.pixela_client.graph_range().to_string().to_lowercase()
+            ))
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Gray));
             placeholder_text.render(inner_area, buf);
         }
     }
@@ -289,12 +303,18 @@ impl From<PixelToListWrapper<'_>> for ListItem<'_> {
                         complex.display_string()
                     )
                 };
+                if let Some(task) = complex.task() {
+                    text.push_str(&format!(" | {task}"));
+                }
                 (text, complex.subject().unit().clone())
             }
-            Pixel::Simple(simple) => (
-                format!(" {} | {}m", simple.date(), simple.progress()),
-                SubjectUnit::Minutes,
-            ),
+            Pixel::Simple(simple) => {
+                let mut text = format!(" {} | {}m", simple.date(), simple.progress());
+                if let Some(task) = simple.task() {
+                    text.push_str(&format!(" | {task}"));
+                }
+                (text, SubjectUnit::Minutes)
+            }
         };
         let pixel_text = Text::from(pixel_text);
         let unit_display = unit.short_string();
@@ -355,11 +375,52 @@ impl From<SubjectToListWrapper<'_>> for ListItem<'_> {
     }
 }
 
+fn render_graph_stats(stats: &GraphStats, subject: &Subject, area: Rect, buf: &mut Buffer) {
+    let block = Block::default()
+        .title(" Pixela stats ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(subject.color().to_ratatui_color()));
+    let unit = subject.unit();
+    let lines = vec![
+        Line::from(format!(
+            " Total: {}",
+            helpers::format_quantity(stats.total_quantity, unit)
+        )),
+        Line::from(format!(
+            " Average: {}",
+            helpers::format_quantity(stats.avg_quantity, unit)
+        )),
+        Line::from(format!(
+            " Max: {}",
+            helpers::format_quantity(stats.max_quantity, unit)
+        )),
+        Line::from(format!(
+            " Min: {}",
+            helpers::format_quantity(stats.min_quantity, unit)
+        )),
+        Line::from(format!(" Pixels: {}", stats.total_pixels_count)),
+        Line::from(format!(
+            " Today: {}",
+            helpers::format_quantity(stats.todays_quantity, unit)
+        ))
+        .fg(GREEN),
+    ];
+    Paragraph::new(lines)
+        .style(Style::default().fg(Color::White))
+        .block(block)
+        .render(area, buf);
+}
+
 impl HintProvider for StatsTab<'_> {
     fn provide_hints(&self) -> Vec<FooterHint> {
         let mut default = vec![
             FooterHint::new("<>", "Change Tabs"),
             FooterHint::new("↑↓", "Select"),
+            FooterHint::new("E", "Export history"),
+            FooterHint::new("v", "Local stats"),
+            FooterHint::new("A", "Upload archive"),
+            FooterHint::new("Y", "Sync devices"),
         ];
         let mut based_on_state = match self.pixela_client.focused_pane() {
             PixelaTabs::Pixels => {
@@ -376,6 +437,11 @@ impl HintProvider for StatsTab<'_> {
                 if !self.pixela_client.subjects.is_empty() {
                     vec![
                         FooterHint::new("G", "Render graph"),
+                        FooterHint::new("R", "Graph range"),
+                        FooterHint::new("[]", "Older/Newer"),
+                        FooterHint::new("e", "Edit on Pixela"),
+                        FooterHint::new("H", "Heatmap"),
+                        FooterHint::new("U", "Pull history"),
                         FooterHint::new("Space", "Track"),
                     ]
                 } else {
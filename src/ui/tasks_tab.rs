@@ -0,0 +1,167 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{palette::tailwind::SLATE, Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Block, BorderType, Borders, HighlightSpacing, List, ListItem, Paragraph, StatefulWidget,
+        Widget,
+    },
+};
+
+use crate::tasks::{task::Task, task_list::TaskList};
+
+use super::{
+    helpers,
+    ui_utils::{FooterHint, HintProvider},
+    BLUE, GREEN, ORANGE, YELLOW,
+};
+
+const SELECTED_STYLE: Style = Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD);
+pub struct TasksTab<'a> {
+    tasks: &'a mut TaskList,
+}
+impl Widget for &mut TasksTab<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),   // Task list
+                Constraint::Length(3), // Active task or input
+            ])
+            .split(area);
+        self.render_tasks(layout[0], buf);
+        if self.tasks.is_typing() {
+            self.render_input(layout[1], buf);
+        } else {
+            self.render_active(layout[1], buf);
+        }
+    }
+}
+impl<'a> TasksTab<'a> {
+    pub fn new(tasks: &'a mut TaskList) -> Self {
+        Self { tasks }
+    }
+    fn render_tasks(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .title(" Tasks ")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double)
+            .border_style(Style::default().fg(BLUE));
+        let inner_area = block.inner(area);
+        if self.tasks.tasks.is_empty() {
+            block.render(area, buf);
+            Paragraph::new("No tasks yet, press 'a' to add one")
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(Color::Gray))
+                .bold()
+                .render(inner_area, buf);
+            return;
+        }
+        if self.tasks.tasks.state().selected().is_none() {
+            self.tasks.tasks.state_mut().select_first();
+        }
+        let list = List::new(
+            self.tasks
+                .tasks
+                .iter()
+                .map(|task| {
+                    TaskToListWrapper {
+                        task,
+                        active: self.tasks.is_active(task),
+                    }
+                    .into()
+                })
+                .collect::<Vec<ListItem>>(),
+        )
+        .block(block)
+        .highlight_style(SELECTED_STYLE)
+        .highlight_symbol(">")
+        .highlight_spacing(HighlightSpacing::Always);
+        StatefulWidget::render(list, area, buf, self.tasks.tasks.state_mut());
+        helpers::render_scroll_indicators(
+            inner_area,
+            buf,
+            self.tasks.tasks.items().len(),
+            self.tasks.tasks.state(),
+            BLUE,
+        );
+    }
+    fn render_active(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .title(" Active ")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(GREEN));
+        let text = match self.tasks.active_task() {
+            Some(task) => format!("{} ({} pomodoros)", task.title(), task.progress_string()),
+            None => "No active task".to_string(),
+        };
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(YELLOW))
+            .block(block)
+            .render(area, buf);
+    }
+    fn render_input(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default()
+            .title(" New task ")
+            .borders(Borders::ALL)
+            .border_type(BorderType::QuadrantInside)
+            .border_style(Style::default().fg(YELLOW));
+        let text = self.tasks.input_text().unwrap_or_default();
+        Paragraph::new(Line::from(vec![
+            Span::raw(text.to_string()),
+            Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+        ]))
+        .style(Style::default().fg(Color::White))
+        .block(block)
+        .render(area, buf);
+    }
+}
+struct TaskToListWrapper<'a> {
+    task: &'a Task,
+    active: bool,
+}
+impl From<TaskToListWrapper<'_>> for ListItem<'_> {
+    fn from(value: TaskToListWrapper) -> Self {
+        let task = value.task;
+        let check = if task.done() { "✓" } else { "☐" };
+        let mut text = format!(" {check} {} | {}", task.title(), task.progress_string());
+        if let Some(subject) = task.subject_name() {
+            text.push_str(&format!(" | {subject}"));
+        }
+        let style = match (value.active, task.done(), task.over_estimate()) {
+            (true, _, _) => Style::default().fg(GREEN),
+            (false, true, _) => Style::default().fg(Color::Gray),
+            (false, false, true) => Style::default().fg(ORANGE),
+            (false, false, false) => Style::default().fg(Color::White),
+        };
+        if value.active {
+            text.push_str(" [Active]");
+        }
+        ListItem::new(Line::styled(text, style))
+    }
+}
+impl HintProvider for TasksTab<'_> {
+    fn provide_hints(&self) -> Vec<FooterHint> {
+        if self.tasks.is_typing() {
+            return vec![
+                FooterHint::new("Enter", "Save"),
+                FooterHint::new("Esc", "Cancel"),
+            ];
+        }
+        let mut hints = vec![FooterHint::new("a", "Add")];
+        if !self.tasks.tasks.is_empty() {
+            hints.append(&mut vec![
+                FooterHint::new("↑↓", "Select"),
+                FooterHint::new("Space", "Activate"),
+                FooterHint::new("+-", "Estimate"),
+                FooterHint::new("s", "Use tracked subject"),
+                FooterHint::new("c", "Done"),
+                FooterHint::new("d", "Delete"),
+            ]);
+        }
+        hints
+    }
+}
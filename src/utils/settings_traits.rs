@@ -1,7 +1,14 @@
+use std::collections::HashMap;
+
 use crate::{
-    settings::{PomodoroSettings, TimerSettings},
+    settings::{
+        ActivityWatchSettings, ArchiveSettings, HookSettings, MqttSettings, OrgClockSettings,
+        PomodoroSettings, TaskwarriorSettings, TimerSettings, TimewarriorSettings, TodoTxtSettings,
+        WebhookSettings,
+    },
     timers::helper_structs::TimerState,
-    DEFAULT_BREAK, DEFAULT_ITERATIONS, DEFAULT_WORK,
+    DEFAULT_ACTIVITYWATCH_URL, DEFAULT_ARCHIVE_RETENTION_DAYS, DEFAULT_BREAK, DEFAULT_HOOK_TIMEOUT,
+    DEFAULT_ITERATIONS, DEFAULT_MQTT_PORT, DEFAULT_WEBHOOK_RETRIES, DEFAULT_WORK,
 };
 
 impl Default for TimerSettings {
@@ -14,6 +21,99 @@ impl Default for TimerSettings {
         }
     }
 }
+impl Default for HookSettings {
+    fn default() -> Self {
+        HookSettings {
+            on_work_start: None,
+            on_break_start: None,
+            on_phase_end: None,
+            on_session_finish: None,
+            on_pixel_logged: None,
+            on_pixels_synced: None,
+            timeout_secs: DEFAULT_HOOK_TIMEOUT,
+        }
+    }
+}
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        WebhookSettings {
+            urls: Vec::new(),
+            secret: None,
+            max_retries: DEFAULT_WEBHOOK_RETRIES,
+        }
+    }
+}
+impl Default for MqttSettings {
+    fn default() -> Self {
+        MqttSettings {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: DEFAULT_MQTT_PORT,
+            client_id: "rtuion".to_string(),
+            topic_prefix: "rtuion".to_string(),
+            username: None,
+            password: None,
+            retain: true,
+        }
+    }
+}
+impl Default for ActivityWatchSettings {
+    fn default() -> Self {
+        ActivityWatchSettings {
+            enabled: false,
+            url: DEFAULT_ACTIVITYWATCH_URL.to_string(),
+            bucket: None,
+            include_breaks: true,
+        }
+    }
+}
+impl Default for TimewarriorSettings {
+    fn default() -> Self {
+        TimewarriorSettings {
+            enabled: false,
+            command: "timew".to_string(),
+            tags: vec!["rtuion".to_string()],
+        }
+    }
+}
+impl Default for OrgClockSettings {
+    fn default() -> Self {
+        OrgClockSettings {
+            enabled: false,
+            file: None,
+            heading: "rtuion".to_string(),
+            tags: Vec::new(),
+        }
+    }
+}
+impl Default for TodoTxtSettings {
+    fn default() -> Self {
+        TodoTxtSettings {
+            path: None,
+            projects: HashMap::new(),
+            pomodoro_tag: "pomo".to_string(),
+        }
+    }
+}
+impl Default for TaskwarriorSettings {
+    fn default() -> Self {
+        TaskwarriorSettings {
+            enabled: false,
+            command: "task".to_string(),
+            follow_phases: true,
+            annotate: true,
+            pomodoro_uda: None,
+            projects: HashMap::new(),
+        }
+    }
+}
+impl Default for ArchiveSettings {
+    fn default() -> Self {
+        ArchiveSettings {
+            retention_days: DEFAULT_ARCHIVE_RETENTION_DAYS,
+        }
+    }
+}
 impl From<TimerState> for PomodoroSettings {
     fn from(value: TimerState) -> Self {
         match value {
@@ -1,13 +1,15 @@
 #[derive(Debug, Clone, Copy)]
 pub enum Tabs {
     TimerTab,
+    TasksTab,
     SettingsTab,
     StatsTab,
 }
 impl Tabs {
     pub fn next(&mut self) {
         *self = match self {
-            Tabs::TimerTab => Tabs::SettingsTab,
+            Tabs::TimerTab => Tabs::TasksTab,
+            Tabs::TasksTab => Tabs::SettingsTab,
             Tabs::SettingsTab => Tabs::StatsTab,
             Tabs::StatsTab => Tabs::TimerTab,
         };
@@ -15,7 +17,8 @@ impl Tabs {
     pub fn prev(&mut self) {
         *self = match self {
             Tabs::TimerTab => Tabs::StatsTab,
-            Tabs::SettingsTab => Tabs::TimerTab,
+            Tabs::TasksTab => Tabs::TimerTab,
+            Tabs::SettingsTab => Tabs::TasksTab,
             Tabs::StatsTab => Tabs::SettingsTab,
         };
     }
@@ -24,8 +27,9 @@ impl From<Tabs> for usize {
     fn from(value: Tabs) -> Self {
         match value {
             Tabs::TimerTab => 0,
-            Tabs::SettingsTab => 1,
-            Tabs::StatsTab => 2,
+            Tabs::TasksTab => 1,
+            Tabs::SettingsTab => 2,
+            Tabs::StatsTab => 3,
         }
     }
 }